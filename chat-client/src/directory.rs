@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+/// The users currently registered with the chat server, keyed by endpoint.
+#[derive(Clone, Debug, Default)]
+pub struct UserDirectory {
+    users: HashMap<u64, String>,
+}
+
+impl UserDirectory {
+    pub fn new(users: impl IntoIterator<Item = chat_modrpc::RegisteredUser>) -> Self {
+        Self {
+            users: users.into_iter().map(|user| (user.endpoint, user.alias)).collect(),
+        }
+    }
+
+    pub fn alias(&self, endpoint: u64) -> Option<&str> {
+        self.users.get(&endpoint).map(String::as_str)
+    }
+
+    pub fn endpoint(&self, alias: &str) -> Option<u64> {
+        self.users.iter()
+            .find(|(_, existing_alias)| *existing_alias == alias)
+            .map(|(&endpoint, _)| endpoint)
+    }
+
+    pub fn contains_alias(&self, alias: &str) -> bool {
+        self.endpoint(alias).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.users.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+    }
+
+    /// All users, sorted by alias.
+    pub fn users(&self) -> Vec<chat_modrpc::RegisteredUser> {
+        let mut users: Vec<_> = self.users.iter()
+            .map(|(&endpoint, alias)| chat_modrpc::RegisteredUser {
+                endpoint,
+                alias: alias.clone(),
+            })
+            .collect();
+        users.sort_by(|a, b| a.alias.cmp(&b.alias));
+        users
+    }
+
+    /// Record that `endpoint` is registered as `alias`. An alias belongs to at most one endpoint,
+    /// so any other endpoint registered under it (e.g. from before a resumed session reconnected)
+    /// is dropped.
+    ///
+    /// Returns false if the endpoint was already registered under this alias.
+    pub fn insert(&mut self, endpoint: u64, alias: &str) -> bool {
+        if self.alias(endpoint) == Some(alias) {
+            return false;
+        }
+        self.users.retain(|_, existing_alias| existing_alias != alias);
+        self.users.insert(endpoint, alias.to_string());
+        true
+    }
+
+    /// Forget about an endpoint, returning the alias it was registered under.
+    pub fn remove(&mut self, endpoint: u64) -> Option<String> {
+        self.users.remove(&endpoint)
+    }
+}
@@ -0,0 +1,158 @@
+use std::{fmt, time::Duration};
+
+/// Something that happened in the chat, delivered over the channel given to
+/// [`ChatSession::new`](crate::ChatSession::new).
+#[derive(Clone, Debug)]
+pub enum ChatEvent {
+    /// A message was posted to the chat, either by a peer or by us.
+    Message(Message),
+    /// A user registered or resumed their session.
+    UserJoined(chat_modrpc::RegisteredUser),
+    /// A registered user disconnected. Only reported while
+    /// [`ChatSession::track_departures`](crate::ChatSession::track_departures) is running.
+    UserLeft(chat_modrpc::RegisteredUser),
+    /// A registered user registered again under a different alias.
+    UserRenamed {
+        endpoint: u64,
+        old_alias: String,
+        new_alias: String,
+    },
+    /// The server rejected one of our queued messages and it was dropped from the outbox. Queue
+    /// it again to retry.
+    SendFailed {
+        outgoing_id: String,
+        error: ChatError,
+    },
+    /// More messages were posted while we were disconnected than the server could replay. Only
+    /// the most recent of them follow.
+    MissedMessagesTruncated,
+    /// One of our requests failed. The caller also gets the error returned.
+    Error(ChatError),
+}
+
+#[derive(Clone, Debug)]
+pub struct Message {
+    pub alias: String,
+    pub content: String,
+    /// Whether we sent this message.
+    pub is_own: bool,
+    /// For our own messages sent through [`ChatSession::send`](crate::ChatSession::send), the ID
+    /// of the [`OutgoingMessage`](crate::OutgoingMessage). The same message can be delivered
+    /// again under this ID if it's sent again after an earlier attempt was abandoned, so match it
+    /// against the messages already shown first.
+    pub outgoing_id: Option<String>,
+    /// When the server logged the message, in milliseconds since the Unix epoch. Only known for
+    /// messages missed while disconnected, the rest are delivered as they're posted.
+    pub timestamp_ms: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChatError {
+    Register(chat_modrpc::RegisterError),
+    ResumeSession(chat_modrpc::ResumeSessionError),
+    SendMessage(chat_modrpc::SendMessageError),
+    SearchMessages(chat_modrpc::SearchMessagesError),
+    /// The connection was lost before the server responded. A session doesn't watch its
+    /// connection, so this is only reported by whoever does.
+    Disconnected,
+}
+
+impl ChatError {
+    /// How long the server asked us to wait before trying again, if we were rate limited.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::Register(chat_modrpc::RegisterError::RateLimited { retry_after_ms })
+            | Self::ResumeSession(chat_modrpc::ResumeSessionError::RateLimited { retry_after_ms })
+            | Self::SendMessage(chat_modrpc::SendMessageError::RateLimited { retry_after_ms })
+            | Self::SearchMessages(chat_modrpc::SearchMessagesError::RateLimited {
+                retry_after_ms,
+            }) => {
+                Some(Duration::from_millis((*retry_after_ms).into()))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ChatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Register(error) => match error {
+                chat_modrpc::RegisterError::Internal => {
+                    write!(f, "registration failed due to a server error")
+                }
+                chat_modrpc::RegisterError::UserAlreadyExists => {
+                    write!(f, "that alias is already taken")
+                }
+                chat_modrpc::RegisterError::ChatFull => {
+                    write!(f, "the chat is full")
+                }
+                chat_modrpc::RegisterError::RateLimited { retry_after_ms } => {
+                    let retry_after = RetryAfter(*retry_after_ms);
+                    write!(f, "too many registration attempts, try again in {retry_after}")
+                }
+                chat_modrpc::RegisterError::InvalidAlias => {
+                    write!(f, "that alias isn't allowed")
+                }
+            },
+            Self::ResumeSession(error) => match error {
+                chat_modrpc::ResumeSessionError::Internal => {
+                    write!(f, "couldn't resume the session due to a server error")
+                }
+                chat_modrpc::ResumeSessionError::InvalidSession => {
+                    write!(f, "the session has expired")
+                }
+                chat_modrpc::ResumeSessionError::RateLimited { retry_after_ms } => {
+                    let retry_after = RetryAfter(*retry_after_ms);
+                    write!(f, "too many attempts to resume the session, try again in {retry_after}")
+                }
+            },
+            Self::SendMessage(error) => match error {
+                chat_modrpc::SendMessageError::Internal => {
+                    write!(f, "message not sent due to a server error")
+                }
+                chat_modrpc::SendMessageError::NotRegistered => {
+                    write!(f, "message not sent, you aren't registered")
+                }
+                chat_modrpc::SendMessageError::MessageTooLong => {
+                    write!(f, "message not sent, it's too long")
+                }
+                chat_modrpc::SendMessageError::RateLimited { retry_after_ms } => {
+                    let retry_after = RetryAfter(*retry_after_ms);
+                    write!(f, "sending messages too fast, try again in {retry_after}")
+                }
+            },
+            Self::SearchMessages(error) => match error {
+                chat_modrpc::SearchMessagesError::Internal => {
+                    write!(f, "search failed due to a server error")
+                }
+                chat_modrpc::SearchMessagesError::NotRegistered => {
+                    write!(f, "search failed, you aren't registered")
+                }
+                chat_modrpc::SearchMessagesError::EmptyQuery => {
+                    write!(f, "there's nothing to search for")
+                }
+                chat_modrpc::SearchMessagesError::RateLimited { retry_after_ms } => {
+                    let retry_after = RetryAfter(*retry_after_ms);
+                    write!(f, "searching too often, try again in {retry_after}")
+                }
+            },
+            Self::Disconnected => write!(f, "lost connection to the chat server"),
+        }
+    }
+}
+
+impl std::error::Error for ChatError {}
+
+struct RetryAfter(u32);
+
+impl fmt::Display for RetryAfter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = self.0.div_ceil(1000);
+        if seconds == 1 {
+            write!(f, "1 second")
+        } else {
+            write!(f, "{seconds} seconds")
+        }
+    }
+}
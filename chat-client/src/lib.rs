@@ -0,0 +1,21 @@
+//! High-level chat client built on the generated `chat_modrpc::ChatClientHooks`.
+//!
+//! A [`ChatSession`] wraps one connection to the chat server. It keeps a [`UserDirectory`] in
+//! sync, reports what happens in the chat as [`ChatEvent`]s over a `localq` channel and exposes
+//! async methods to register and send messages. Messages are queued in an [`Outbox`] that
+//! outlives connections and flushed through whichever session is current. It doesn't spawn tasks
+//! or use timers of its own, so it runs the same on tokio and in the browser with
+//! `DioxusExecutor`.
+
+pub use directory::*;
+pub use event::*;
+pub use outbox::*;
+pub use outgoing::*;
+pub use session::*;
+
+mod directory;
+mod event;
+mod outbox;
+mod outgoing;
+mod pending;
+mod session;
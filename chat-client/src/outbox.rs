@@ -0,0 +1,70 @@
+use std::{cell::RefCell, collections::VecDeque};
+
+use crate::OutgoingMessage;
+
+/// Where an [`Outbox`] keeps its messages so they survive restarts.
+pub trait OutboxStorage {
+    fn load(&self) -> Vec<OutgoingMessage>;
+    /// Replace the stored messages. Called whenever the outbox changes.
+    fn save(&self, messages: &[OutgoingMessage]);
+}
+
+/// Our messages waiting to be accepted by the server, oldest first.
+///
+/// Messages can be queued whether or not we're connected. [`ChatSession::flush`] sends them in
+/// order and removes each once the server has accepted it. A message's ID doubles as its
+/// idempotency key, so one that reached the server before we lost the response isn't posted a
+/// second time when it's sent again.
+///
+/// [`ChatSession::flush`]: crate::ChatSession::flush
+pub struct Outbox {
+    messages: RefCell<VecDeque<OutgoingMessage>>,
+    storage: Box<dyn OutboxStorage>,
+}
+
+impl Outbox {
+    /// Open an outbox, picking up any messages left in `storage`.
+    pub fn new(storage: impl OutboxStorage + 'static) -> Self {
+        Self {
+            messages: RefCell::new(storage.load().into()),
+            storage: Box::new(storage),
+        }
+    }
+
+    /// Snapshot of the queued messages, oldest first.
+    pub fn messages(&self) -> Vec<OutgoingMessage> {
+        self.messages.borrow().iter().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.borrow().is_empty()
+    }
+
+    /// Queue a message to be sent after the ones already queued. Queueing a message that's
+    /// already in the outbox does nothing.
+    pub fn push(&self, message: OutgoingMessage) {
+        let mut messages = self.messages.borrow_mut();
+        if messages.iter().any(|queued| queued.id == message.id) {
+            return;
+        }
+        messages.push_back(message);
+        self.storage.save(messages.make_contiguous());
+    }
+
+    pub(crate) fn front(&self) -> Option<OutgoingMessage> {
+        self.messages.borrow().front().cloned()
+    }
+
+    pub(crate) fn remove(&self, id: &str) {
+        let mut messages = self.messages.borrow_mut();
+        let len = messages.len();
+        messages.retain(|message| message.id != id);
+        if messages.len() != len {
+            self.storage.save(messages.make_contiguous());
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+/// A message we're sending, with an ID that stays the same across retries so its delivery can be
+/// tracked. The ID is sent as the message's idempotency key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutgoingMessage {
+    pub id: String,
+    pub content: String,
+}
+
+/// Where one of our messages is on its way to the chat.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeliveryStatus {
+    /// Waiting to be sent or for the server to respond.
+    Pending,
+    /// Accepted by the server.
+    Delivered,
+    /// Rejected by the server or couldn't be sent, with the reason why.
+    Failed(String),
+}
+
+impl OutgoingMessage {
+    pub fn new(content: String) -> Self {
+        Self {
+            id: crate::session::random_token(),
+            content,
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use std::{
+    borrow::Borrow,
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    rc::Rc,
+};
+
+/// Pairs our own requests with the copies of them the server broadcasts back to every client.
+///
+/// Both the caller awaiting a request and the subscription watching the broadcast see its
+/// response, and either of them may be cancelled before it does. Whichever gets to the response
+/// first takes the value to deliver, so it's delivered exactly once as long as one of the two
+/// makes it.
+///
+/// Broadcast copies are matched to requests by key (the alias or idempotency key) rather than
+/// purely by order, so a request abandoned before it reached the server doesn't throw off the
+/// pairing of the ones after it.
+pub(crate) struct PendingRequests<K, T> {
+    queue: Queue<K, T>,
+}
+
+// Tracked requests whose broadcast copy hasn't been seen yet, oldest first
+type Queue<K, T> = Rc<RefCell<VecDeque<Rc<Pending<K, T>>>>>;
+
+struct Pending<K, T> {
+    key: K,
+    delivery: Cell<Option<T>>,
+}
+
+/// The caller's handle on a request it's making.
+pub(crate) struct PendingRequest<K, T> {
+    queue: Queue<K, T>,
+    pending: Rc<Pending<K, T>>,
+}
+
+/// The subscription's handle on the broadcast copy of one of our requests.
+pub(crate) struct BroadcastCopy<K, T> {
+    pending: Rc<Pending<K, T>>,
+}
+
+impl<K, T> PendingRequests<K, T> {
+    pub fn new() -> Self {
+        Self {
+            queue: Rc::new(RefCell::new(VecDeque::new())),
+        }
+    }
+
+    /// Start tracking a request before it's sent. `delivery` is handed to whichever of the caller
+    /// and the broadcast copy sees the response first.
+    pub fn track(&self, key: K, delivery: T) -> PendingRequest<K, T> {
+        let pending = Rc::new(Pending {
+            key,
+            delivery: Cell::new(Some(delivery)),
+        });
+        self.queue.borrow_mut().push_back(pending.clone());
+        PendingRequest {
+            queue: self.queue.clone(),
+            pending,
+        }
+    }
+
+    /// Match the broadcast copy of one of our requests to the oldest tracked request with the
+    /// same key. This must happen as soon as the request is seen, before waiting for its
+    /// response. Returns `None` if the request wasn't made through this tracker.
+    pub fn claim<Q>(&self, key: &Q) -> Option<BroadcastCopy<K, T>>
+    where
+        K: Borrow<Q>,
+        Q: PartialEq + ?Sized,
+    {
+        let mut queue = self.queue.borrow_mut();
+        let index = queue.iter().position(|pending| pending.key.borrow() == key)?;
+        let pending = queue.remove(index)?;
+        Some(BroadcastCopy { pending })
+    }
+}
+
+impl<K, T> PendingRequest<K, T> {
+    /// Take the value to deliver, unless the broadcast copy already has.
+    pub fn deliver(&self) -> Option<T> {
+        self.pending.delivery.take()
+    }
+}
+
+impl<K, T> Drop for PendingRequest<K, T> {
+    fn drop(&mut self) {
+        // Keep delivered requests around for their broadcast copy to claim, so it knows not to
+        // deliver again. Anything else was abandoned or failed - if it reaches the server after
+        // all, its broadcast copy is handled as if we weren't tracking it.
+        let delivery = self.pending.delivery.take();
+        let delivered = delivery.is_none();
+        self.pending.delivery.set(delivery);
+        if !delivered {
+            self.queue.borrow_mut().retain(|pending| !Rc::ptr_eq(pending, &self.pending));
+        }
+    }
+}
+
+impl<K, T> BroadcastCopy<K, T> {
+    /// Take the value to deliver, unless the caller already has.
+    pub fn deliver(&self) -> Option<T> {
+        self.pending.delivery.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caller_delivers_first() {
+        let requests = PendingRequests::new();
+        let request = requests.track("hello".to_string(), 1);
+
+        assert_eq!(request.deliver(), Some(1));
+        drop(request);
+
+        let copy = requests.claim("hello").expect("broadcast copy is matched");
+        assert_eq!(copy.deliver(), None);
+        assert!(requests.claim("hello").is_none());
+    }
+
+    #[test]
+    fn broadcast_copy_delivers_first() {
+        let requests = PendingRequests::new();
+        let request = requests.track("hello".to_string(), 1);
+
+        let copy = requests.claim("hello").expect("broadcast copy is matched");
+        assert_eq!(copy.deliver(), Some(1));
+        assert_eq!(request.deliver(), None);
+    }
+
+    #[test]
+    fn caller_delivers_when_broadcast_copy_is_cancelled() {
+        let requests = PendingRequests::new();
+        let request = requests.track("hello".to_string(), 1);
+
+        let copy = requests.claim("hello").expect("broadcast copy is matched");
+        drop(copy);
+        assert_eq!(request.deliver(), Some(1));
+    }
+
+    #[test]
+    fn broadcast_copy_delivers_when_caller_is_cancelled() {
+        let requests = PendingRequests::new();
+        let request = requests.track("hello".to_string(), 1);
+
+        let copy = requests.claim("hello").expect("broadcast copy is matched");
+        drop(request);
+        assert_eq!(copy.deliver(), Some(1));
+    }
+
+    #[test]
+    fn abandoned_request_is_untracked() {
+        let requests = PendingRequests::<String, i32>::new();
+        let request = requests.track("hello".to_string(), 1);
+        drop(request);
+
+        // If the request made it to the server anyway, its broadcast copy is delivered as an
+        // untracked request by the caller of `claim`.
+        assert!(requests.claim("hello").is_none());
+    }
+
+    #[test]
+    fn abandoned_request_does_not_shift_later_ones() {
+        let requests = PendingRequests::new();
+        let abandoned = requests.track("first".to_string(), 1);
+        let request = requests.track("second".to_string(), 2);
+        drop(abandoned);
+
+        let copy = requests.claim("second").expect("broadcast copy is matched");
+        assert_eq!(copy.deliver(), Some(2));
+        assert_eq!(request.deliver(), None);
+    }
+
+    #[test]
+    fn same_key_is_matched_in_order() {
+        let requests = PendingRequests::new();
+        let first = requests.track("hello".to_string(), 1);
+        let second = requests.track("hello".to_string(), 2);
+
+        assert_eq!(requests.claim("hello").unwrap().deliver(), Some(1));
+        assert_eq!(requests.claim("hello").unwrap().deliver(), Some(2));
+        assert_eq!(first.deliver(), None);
+        assert_eq!(second.deliver(), None);
+    }
+
+    #[test]
+    fn every_request_is_delivered_exactly_once() {
+        // Try every combination of which side sees the response first and which side (if any)
+        // is cancelled before seeing it.
+        for caller_first in [false, true] {
+            for cancelled in [None, Some("caller"), Some("broadcast")] {
+                let requests = PendingRequests::new();
+                let request = requests.track("hello".to_string(), ());
+                let copy = requests.claim("hello").expect("broadcast copy is matched");
+
+                let mut request = Some(request);
+                let mut copy = Some(copy);
+                match cancelled {
+                    Some("caller") => request = None,
+                    Some("broadcast") => copy = None,
+                    _ => {}
+                }
+
+                let caller_delivery = || request.as_ref().and_then(PendingRequest::deliver);
+                let copy_delivery = || copy.as_ref().and_then(BroadcastCopy::deliver);
+                let deliveries = if caller_first {
+                    [caller_delivery(), copy_delivery()]
+                } else {
+                    [copy_delivery(), caller_delivery()]
+                };
+
+                assert_eq!(
+                    deliveries.iter().flatten().count(), 1,
+                    "caller_first={caller_first} cancelled={cancelled:?}",
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,458 @@
+use std::{
+    cell::{Cell, Ref, RefCell},
+    fmt,
+    rc::Rc,
+};
+
+use crate::{
+    ChatError, ChatEvent, Message, Outbox, OutgoingMessage, UserDirectory,
+    pending::PendingRequests,
+};
+
+/// What a client needs to pick its session back up on a new connection.
+#[derive(Clone)]
+pub struct Credentials {
+    pub alias: String,
+    // Secret the server holds the digest of, revealed to resume the session
+    resume_token: String,
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Anyone with the token can take over the alias, so keep it out of logs.
+        f.debug_struct("Credentials")
+            .field("alias", &self.alias)
+            .field("resume_token", &"<redacted>")
+            .finish()
+    }
+}
+
+/// A chat client on one connection to the server.
+///
+/// Create a new session for every connection and carry [`Credentials`] over to it to stay
+/// registered under the same alias.
+pub struct ChatSession {
+    hooks: chat_modrpc::ChatClientHooks,
+    server_info: chat_modrpc::ServerInfo,
+    shared: Rc<Shared>,
+    // Set while `flush` is running
+    flushing: Cell<bool>,
+    // Taken by `track_departures`
+    departures: Cell<Option<std_modrpc::StreamSubscription<chat_modrpc::RegisteredUser>>>,
+}
+
+// State shared with the subscriptions
+struct Shared {
+    local_endpoint: modrpc::EndpointAddr,
+    users: RefCell<UserDirectory>,
+    credentials: RefCell<Option<Credentials>>,
+    events: localq::mpsc::Sender<ChatEvent>,
+    // Our own requests in flight, keyed by alias and idempotency key respectively
+    registrations: PendingRequests<String, Credentials>,
+    sent_messages: PendingRequests<String, Message>,
+}
+
+impl ChatSession {
+    /// Start a session on a newly established connection. `events` can be shared by the sessions
+    /// of successive connections.
+    pub fn new(
+        local_endpoint: modrpc::EndpointAddr,
+        init: chat_modrpc::ChatInitState,
+        hooks: chat_modrpc::ChatClientHooks,
+        events: localq::mpsc::Sender<ChatEvent>,
+    ) -> Self {
+        let shared = Rc::new(Shared {
+            local_endpoint,
+            users: RefCell::new(UserDirectory::new(init.users)),
+            credentials: RefCell::new(None),
+            events,
+            registrations: PendingRequests::new(),
+            sent_messages: PendingRequests::new(),
+        });
+        subscribe(&hooks, &shared);
+        // Subscribe right away so nobody who leaves before `track_departures` is polled is missed.
+        let departures = hooks.user_left.subscribe(None);
+
+        Self {
+            hooks,
+            server_info: init.server,
+            shared,
+            flushing: Cell::new(false),
+            departures: Cell::new(Some(departures)),
+        }
+    }
+
+    pub fn local_endpoint(&self) -> modrpc::EndpointAddr {
+        self.shared.local_endpoint
+    }
+
+    pub fn server_info(&self) -> &chat_modrpc::ServerInfo {
+        &self.server_info
+    }
+
+    pub fn users(&self) -> Ref<'_, UserDirectory> {
+        self.shared.users.borrow()
+    }
+
+    /// Our alias, once registered.
+    pub fn alias(&self) -> Option<String> {
+        self.shared.credentials.borrow().as_ref().map(|credentials| credentials.alias.clone())
+    }
+
+    /// What's needed to re-establish this session on a new connection, once registered.
+    pub fn credentials(&self) -> Option<Credentials> {
+        self.shared.credentials.borrow().clone()
+    }
+
+    /// Register under `alias`. If we're already registered under a different alias, the server
+    /// releases that one and peers see us as renamed.
+    ///
+    /// Our registration takes effect exactly once even if this future is dropped before the
+    /// response arrives, as long as the request made it to the server.
+    pub async fn register(&self, alias: &str) -> Result<(), ChatError> {
+        let resume_token = random_token();
+        let resume_digest = chat_common::resume_digest(&resume_token);
+        let pending = self.shared.registrations.track(alias.to_string(), Credentials {
+            alias: alias.to_string(),
+            resume_token,
+        });
+
+        let response = self.hooks.register.call(chat_modrpc::RegisterRequest {
+            alias: alias.to_string(),
+            resume_digest: Some(resume_digest),
+        })
+        .await;
+        if let Err(error) = response {
+            return Err(self.shared.report(ChatError::Register(error)).await);
+        }
+
+        if let Some(credentials) = pending.deliver() {
+            self.shared.registered(credentials);
+        }
+        Ok(())
+    }
+
+    /// Pick up the session from a previous connection. Resumes it if the server is still
+    /// holding our alias, and otherwise registers the alias again.
+    ///
+    /// Other failures to resume, like being rate limited, are returned with the session still
+    /// held by the server, so the same credentials can be tried again. Messages missed while
+    /// disconnected are delivered as events.
+    pub async fn reestablish(&self, credentials: Credentials) -> Result<(), ChatError> {
+        let next_resume_token = random_token();
+        let response = self.hooks.resume_session.call(chat_modrpc::ResumeSessionRequest {
+            alias: credentials.alias.clone(),
+            resume_token: credentials.resume_token,
+            next_resume_digest: chat_common::resume_digest(&next_resume_token),
+        })
+        .await;
+        let success = match response {
+            Ok(success) => success,
+            // The server has let go of our session - try to claim the alias again.
+            Err(chat_modrpc::ResumeSessionError::InvalidSession) => {
+                return self.register(&credentials.alias).await;
+            }
+            Err(error) => return Err(self.shared.report(ChatError::ResumeSession(error)).await),
+        };
+
+        self.shared.registered(Credentials {
+            alias: credentials.alias.clone(),
+            resume_token: next_resume_token,
+        });
+        if success.truncated {
+            self.shared.emit(ChatEvent::MissedMessagesTruncated).await;
+        }
+        for message in success.missed_messages {
+            let is_own = message.alias == credentials.alias;
+            self.shared.emit(ChatEvent::Message(Message {
+                alias: message.alias,
+                content: message.content,
+                is_own,
+                outgoing_id: None,
+                timestamp_ms: Some(message.timestamp_ms),
+            }))
+            .await;
+        }
+        Ok(())
+    }
+
+    /// Send a message to the chat, returning the ID the server gave it. Most clients queue
+    /// messages in an [`Outbox`] and [`flush`](Self::flush) it instead.
+    ///
+    /// The message is delivered as an event exactly once, whether or not this future is dropped
+    /// before the response arrives, as long as the request made it to the server. That includes
+    /// a retry the server recognized as a duplicate. Errors are only returned, not reported as
+    /// events.
+    pub async fn send(&self, message: OutgoingMessage) -> Result<u64, ChatError> {
+        let pending = self.shared.sent_messages.track(message.id.clone(), Message {
+            alias: self.alias().unwrap_or_default(),
+            content: message.content.clone(),
+            is_own: true,
+            outgoing_id: Some(message.id.clone()),
+            timestamp_ms: None,
+        });
+
+        let response = self.hooks.send_message.call(chat_modrpc::SendMessageRequest {
+            content: message.content,
+            idempotency_key: message.id,
+        })
+        .await;
+        let success = response.map_err(ChatError::SendMessage)?;
+
+        if let Some(message) = pending.deliver() {
+            self.shared.emit(ChatEvent::Message(message)).await;
+        }
+        Ok(success.message_id)
+    }
+
+    /// Search the messages posted to the chat, on servers with the
+    /// [`SEARCH_MESSAGES`](chat_common::capabilities::SEARCH_MESSAGES) capability. Errors are only
+    /// returned, not reported as events.
+    pub async fn search(
+        &self,
+        request: chat_modrpc::SearchMessagesRequest,
+    ) -> Result<chat_modrpc::SearchMessagesSuccess, ChatError> {
+        self.hooks.search_messages.call(request).await.map_err(ChatError::SearchMessages)
+    }
+
+    /// Remove users from the directory as the server reports them leaving. Never returns, so run
+    /// it alongside the rest of the work on this connection and drop it with the connection.
+    ///
+    /// Departures are only reported as [`ChatEvent::UserLeft`] while this is being polled.
+    pub async fn track_departures(&self) {
+        let Some(mut departures) = self.departures.take() else {
+            // Another call is already tracking them.
+            return std::future::pending().await;
+        };
+        loop {
+            let Ok(user) = departures.next().await else {
+                // Failed to decode the departure
+                continue;
+            };
+            self.shared.user_left(user).await;
+        }
+    }
+
+    /// Send the messages queued in `outbox` in order, removing each once the server has
+    /// accepted it.
+    ///
+    /// Messages the server rejects are dropped from the outbox and reported with
+    /// [`ChatEvent::SendFailed`]. If we aren't registered or are being rate limited, stops and
+    /// returns the error, leaving the rest of the outbox queued.
+    ///
+    /// Does nothing if this session is already flushing, since that flush picks up messages
+    /// queued in the meantime.
+    pub async fn flush(&self, outbox: &Outbox) -> Result<(), ChatError> {
+        if self.flushing.replace(true) {
+            return Ok(());
+        }
+        let _flushing = ResetOnDrop(&self.flushing);
+
+        while let Some(message) = outbox.front() {
+            if self.alias().is_none() {
+                // The server would only turn the message away.
+                return Err(ChatError::SendMessage(chat_modrpc::SendMessageError::NotRegistered));
+            }
+            let id = message.id.clone();
+            match self.send(message).await {
+                Ok(_) => outbox.remove(&id),
+                Err(error) => {
+                    let retry_later = matches!(
+                        error,
+                        ChatError::SendMessage(
+                            chat_modrpc::SendMessageError::NotRegistered
+                            | chat_modrpc::SendMessageError::RateLimited { .. }
+                        )
+                    );
+                    if retry_later {
+                        return Err(error);
+                    }
+                    outbox.remove(&id);
+                    self.shared.emit(ChatEvent::SendFailed { outgoing_id: id, error }).await;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Clears a flag when dropped, even if the future that set it is cancelled
+struct ResetOnDrop<'a>(&'a Cell<bool>);
+
+impl Drop for ResetOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
+}
+
+impl Shared {
+    async fn emit(&self, event: ChatEvent) {
+        // Nobody to tell if the receiver is gone.
+        let _ = self.events.send(event).await;
+    }
+
+    async fn report(&self, error: ChatError) -> ChatError {
+        self.emit(ChatEvent::Error(error)).await;
+        error
+    }
+
+    fn registered(&self, credentials: Credentials) {
+        self.users.borrow_mut().insert(self.local_endpoint.endpoint, &credentials.alias);
+        *self.credentials.borrow_mut() = Some(credentials);
+    }
+
+    async fn user_joined(&self, endpoint: u64, alias: &str) {
+        let old_alias = self.users.borrow().alias(endpoint).map(str::to_string);
+        let joined = self.users.borrow_mut().insert(endpoint, alias);
+        if !joined {
+            return;
+        }
+        let event = match old_alias {
+            Some(old_alias) => ChatEvent::UserRenamed {
+                endpoint,
+                old_alias,
+                new_alias: alias.to_string(),
+            },
+            None => ChatEvent::UserJoined(chat_modrpc::RegisteredUser {
+                endpoint,
+                alias: alias.to_string(),
+            }),
+        };
+        self.emit(event).await;
+    }
+
+    async fn user_left(&self, user: chat_modrpc::RegisteredUser) {
+        let left = self.users.borrow_mut().remove(user.endpoint).is_some();
+        if left {
+            self.emit(ChatEvent::UserLeft(user)).await;
+        }
+    }
+}
+
+/// Keep the user directory up to date and relay messages.
+///
+/// We see the broadcast copies of our own requests too. Those are matched up with the requests
+/// as soon as they're seen, so that whichever of the caller and the subscription sees the
+/// response first handles it.
+fn subscribe(hooks: &chat_modrpc::ChatClientHooks, shared: &Rc<Shared>) {
+    hooks.register.subscribe({
+        let shared = shared.clone();
+        async move |source, request, response_waiter| {
+            let Ok(alias) = request.alias() else {
+                return;
+            };
+            let is_own = source == shared.local_endpoint;
+            let own_request = is_own.then(|| shared.registrations.claim(alias)).flatten();
+
+            let Ok(response) = response_waiter.wait().await else {
+                // Failed to decode response
+                return;
+            };
+            if response.is_err() {
+                return;
+            }
+            if !is_own {
+                shared.user_joined(source.endpoint, alias).await;
+            } else if let Some(own_request) = own_request {
+                if let Some(credentials) = own_request.deliver() {
+                    shared.registered(credentials);
+                }
+            } else {
+                // Registered through the hooks directly rather than this session.
+                shared.users.borrow_mut().insert(source.endpoint, alias);
+            }
+        }
+    });
+
+    hooks.resume_session.subscribe({
+        let shared = shared.clone();
+        async move |source, request, response_waiter| {
+            if source == shared.local_endpoint {
+                // Handled by `ChatSession::reestablish`
+                return;
+            }
+            let Ok(response) = response_waiter.wait().await else {
+                // Failed to decode response
+                return;
+            };
+            if response.is_ok() {
+                let Ok(alias) = request.alias() else {
+                    return;
+                };
+                shared.user_joined(source.endpoint, alias).await;
+            }
+        }
+    });
+
+    hooks.send_message.subscribe({
+        let shared = shared.clone();
+        async move |source, request, response_waiter| {
+            let (Ok(content), Ok(idempotency_key)) = (request.content(), request.idempotency_key())
+            else {
+                return;
+            };
+            let is_own = source == shared.local_endpoint;
+            let own_request = is_own.then(|| shared.sent_messages.claim(idempotency_key)).flatten();
+
+            let Ok(response) = response_waiter.wait().await else {
+                // Failed to decode response
+                return;
+            };
+            let Ok(success) = response else {
+                return;
+            };
+            let message = match own_request {
+                Some(own_request) => {
+                    let Some(message) = own_request.deliver() else {
+                        // The caller got to it first.
+                        return;
+                    };
+                    message
+                }
+                // A retry of a message that was already posted
+                None if success.duplicate => return,
+                None => {
+                    let alias = shared.users.borrow()
+                        .alias(source.endpoint)
+                        .unwrap_or("<unknown>")
+                        .to_string();
+                    Message {
+                        alias,
+                        content: content.to_string(),
+                        is_own,
+                        // Our own message whose send was abandoned before the response. It may
+                        // still be queued, so pass on its ID for it to be marked delivered
+                        // rather than listed again.
+                        outgoing_id: is_own.then(|| idempotency_key.to_string()),
+                        timestamp_ms: None,
+                    }
+                }
+            };
+            shared.emit(ChatEvent::Message(message)).await;
+        }
+    });
+}
+
+/// Random hex string, used for resume tokens and message IDs.
+pub(crate) fn random_token() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).expect("generate random token");
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credentials_debug_redacts_resume_token() {
+        let credentials = Credentials {
+            alias: "alice".to_string(),
+            resume_token: "0123456789abcdef".to_string(),
+        };
+
+        let debug = format!("{credentials:?}");
+        assert!(debug.contains("alice"), "{debug}");
+        assert!(!debug.contains("0123456789abcdef"), "{debug}");
+    }
+}
@@ -0,0 +1,91 @@
+use core::fmt;
+
+/// Maximum length of an alias, in characters.
+pub const MAX_ALIAS_LEN: usize = 32;
+
+/// Aliases clients use to label messages that don't come from another user, so nobody can
+/// register them. Compared case-insensitively.
+pub const RESERVED_ALIASES: &[&str] = &["System", "You"];
+
+/// Why an alias was rejected by [`validate_alias`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AliasError {
+    Empty,
+    TooLong,
+    /// The alias contains this character, which isn't a letter, digit, `_`, `-` or `.`.
+    InvalidChar(char),
+    /// The alias is one of the [`RESERVED_ALIASES`].
+    Reserved,
+}
+
+impl fmt::Display for AliasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "the alias is empty"),
+            Self::TooLong => write!(f, "the alias is longer than {MAX_ALIAS_LEN} characters"),
+            Self::InvalidChar(' ') => write!(f, "the alias can't contain spaces"),
+            Self::InvalidChar(c) => write!(
+                f,
+                "the alias can't contain {c:?}, only letters, digits, '_', '-' and '.'",
+            ),
+            Self::Reserved => write!(f, "the alias is reserved"),
+        }
+    }
+}
+
+/// Check an alias against the rules the server enforces on registration, so clients can reject
+/// it before sending the request.
+///
+/// An alias is 1 to [`MAX_ALIAS_LEN`] characters, each a letter, digit, `_`, `-` or `.`, and
+/// isn't one of the [`RESERVED_ALIASES`].
+pub fn validate_alias(alias: &str) -> Result<(), AliasError> {
+    if alias.is_empty() {
+        return Err(AliasError::Empty);
+    }
+    if alias.chars().count() > MAX_ALIAS_LEN {
+        return Err(AliasError::TooLong);
+    }
+    if let Some(c) = alias.chars().find(|&c| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.')))
+    {
+        return Err(AliasError::InvalidChar(c));
+    }
+    if RESERVED_ALIASES.iter().any(|reserved| reserved.eq_ignore_ascii_case(alias)) {
+        return Err(AliasError::Reserved);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_valid_aliases() {
+        assert_eq!(validate_alias("alice"), Ok(()));
+        assert_eq!(validate_alias("bob_the-builder.2"), Ok(()));
+        assert_eq!(validate_alias("zoë"), Ok(()));
+        assert_eq!(validate_alias(&"a".repeat(MAX_ALIAS_LEN)), Ok(()));
+        assert_eq!(validate_alias("Systems"), Ok(()));
+    }
+
+    #[test]
+    fn rejects_empty_and_long_aliases() {
+        assert_eq!(validate_alias(""), Err(AliasError::Empty));
+        assert_eq!(validate_alias(&"a".repeat(MAX_ALIAS_LEN + 1)), Err(AliasError::TooLong));
+        // Length is counted in characters, not bytes.
+        assert_eq!(validate_alias(&"ë".repeat(MAX_ALIAS_LEN)), Ok(()));
+    }
+
+    #[test]
+    fn rejects_invalid_chars() {
+        assert_eq!(validate_alias("has space"), Err(AliasError::InvalidChar(' ')));
+        assert_eq!(validate_alias("a@b"), Err(AliasError::InvalidChar('@')));
+    }
+
+    #[test]
+    fn rejects_reserved_aliases_in_any_case() {
+        for alias in ["System", "system", "SYSTEM", "You", "you", "yOU"] {
+            assert_eq!(validate_alias(alias), Err(AliasError::Reserved), "{alias}");
+        }
+    }
+}
@@ -0,0 +1,12 @@
+//! Hand-written helpers shared by the chat server and clients, on top of the generated
+//! `chat_modrpc` protocol crate.
+
+pub use alias::*;
+pub use message::*;
+pub use server_info::*;
+pub use session::*;
+
+mod alias;
+mod message;
+mod server_info;
+mod session;
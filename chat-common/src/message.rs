@@ -0,0 +1,3 @@
+/// Prefix marking a message as an action, posted with `/me` and shown as
+/// "* alias does something".
+pub const ACTION_PREFIX: &str = "/me ";
@@ -0,0 +1,25 @@
+use chat_modrpc::ServerInfo;
+
+/// Version of the chat protocol implemented by `chat_modrpc`, advertised to clients in
+/// `ServerInfo::protocol_version`.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Names of the optional features a server can advertise in `ServerInfo::capabilities`.
+pub mod capabilities {
+    /// Aliases of disconnected users are held for them and `resume_session` is supported.
+    pub const RESUME_SESSION: &str = "resume_session";
+    /// Requests may be rejected with a `RateLimited` error.
+    pub const RATE_LIMIT: &str = "rate_limit";
+    /// The server sends on the `user_left` stream when a registered user disconnects.
+    pub const USER_LEFT: &str = "user_left";
+    /// Retrying a `send_message` request with the same idempotency key doesn't post the message
+    /// again.
+    pub const IDEMPOTENT_SEND: &str = "idempotent_send";
+    /// `search_messages` is supported.
+    pub const SEARCH_MESSAGES: &str = "search_messages";
+}
+
+/// Whether the server advertises `capability`, one of the [`capabilities`].
+pub fn has_capability(server_info: &ServerInfo, capability: &str) -> bool {
+    server_info.capabilities.iter().any(|c| c == capability)
+}
@@ -0,0 +1,12 @@
+use sha2::{Digest, Sha256};
+
+/// Hex-encoded SHA-256 digest of a resume token.
+///
+/// Clients commit to a secret resume token by sending its digest when they register or resume a
+/// session, and later reveal the token itself to resume the session after reconnecting.
+pub fn resume_digest(resume_token: &str) -> String {
+    Sha256::digest(resume_token.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
@@ -1,10 +1,13 @@
-use crate::proto::{RegisterError, RegisterRequest, RegisterSuccess, SendMessageError, SendMessageRequest, SendMessageSuccess};
+use crate::proto::{RegisterError, RegisterRequest, RegisterSuccess, RegisteredUser, ResumeSessionError, ResumeSessionRequest, ResumeSessionSuccess, SearchMessagesError, SearchMessagesRequest, SearchMessagesSuccess, SendMessageError, SendMessageRequest, SendMessageSuccess};
 use modrpc::{InterfaceBuilder, InterfaceSchema};
-use std_modrpc::RequestInterface;
+use std_modrpc::{RequestInterface, StreamInterface};
 
 pub struct ChatInterface {
     pub register: RequestInterface<RegisterRequest, Result<RegisterSuccess, RegisterError>>,
     pub send_message: RequestInterface<SendMessageRequest, Result<SendMessageSuccess, SendMessageError>>,
+    pub resume_session: RequestInterface<ResumeSessionRequest, Result<ResumeSessionSuccess, ResumeSessionError>>,
+    pub search_messages: RequestInterface<SearchMessagesRequest, Result<SearchMessagesSuccess, SearchMessagesError>>,
+    pub user_left: StreamInterface<RegisteredUser>,
 }
 
 impl InterfaceSchema for ChatInterface {
@@ -12,6 +15,9 @@ impl InterfaceSchema for ChatInterface {
         Self {
             register: RequestInterface::new(ib),
             send_message: RequestInterface::new(ib),
+            resume_session: RequestInterface::new(ib),
+            search_messages: RequestInterface::new(ib),
+            user_left: StreamInterface::new(ib),
         }
     }
 }
@@ -1,6 +1,243 @@
 use core::convert::TryFrom;
 use mproto::{BaseLen, Compatible, Decode, DecodeCursor, DecodeError, DecodeResult, Encode, EncodeCursor, Lazy, Owned, max};
 
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct ServerInfo {
+    pub protocol_version: u32,
+    pub name: String,
+    pub motd: String,
+    pub max_message_len: u64,
+    pub max_users: u64,
+    pub capabilities: Vec<String>,
+}
+
+pub struct ServerInfoLazy<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+}
+
+pub struct ServerInfoGen<
+    Name: Encode + Compatible<String>,
+    Motd: Encode + Compatible<String>,
+    Capabilities: Encode + Compatible<Vec<String>>,
+> {
+    pub protocol_version: u32,
+    pub name: Name,
+    pub motd: Motd,
+    pub max_message_len: u64,
+    pub max_users: u64,
+    pub capabilities: Capabilities,
+}
+
+impl<
+    Name: Encode + Compatible<String>,
+    Motd: Encode + Compatible<String>,
+    Capabilities: Encode + Compatible<Vec<String>>
+> Compatible<ServerInfo> for ServerInfoGen<Name, Motd, Capabilities> { }
+impl<
+    Name: Encode + Compatible<String>,
+    Motd: Encode + Compatible<String>,
+    Capabilities: Encode + Compatible<Vec<String>>
+> Compatible<ServerInfoGen<Name, Motd, Capabilities>> for ServerInfo { }
+
+impl<
+    Name: Encode + Compatible<String>,
+    Motd: Encode + Compatible<String>,
+    Capabilities: Encode + Compatible<Vec<String>>,
+> BaseLen for ServerInfoGen<Name, Motd, Capabilities> {
+    const BASE_LEN: usize = 4 + Name::BASE_LEN + Motd::BASE_LEN + 8 + 8 + Capabilities::BASE_LEN;
+}
+
+impl<
+    Name: Encode + Compatible<String>,
+    Motd: Encode + Compatible<String>,
+    Capabilities: Encode + Compatible<Vec<String>>,
+> Encode for ServerInfoGen<Name, Motd, Capabilities> {
+    fn scratch_len(&self) -> usize {
+        self.protocol_version.scratch_len() + self.name.scratch_len() + self.motd.scratch_len() + self.max_message_len.scratch_len() + self.max_users.scratch_len() + self.capabilities.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        self.protocol_version.encode(cursor);
+        self.name.encode(cursor);
+        self.motd.encode(cursor);
+        self.max_message_len.encode(cursor);
+        self.max_users.encode(cursor);
+        self.capabilities.encode(cursor);
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Owned for ServerInfo {
+    type Lazy<'a> = ServerInfoLazy<'a>;
+
+    fn lazy_to_owned(lazy: Self::Lazy<'_>) -> DecodeResult<Self> {
+        TryFrom::try_from(lazy)
+    }
+}
+
+impl<'a> Lazy<'a> for ServerInfoLazy<'a> {
+    type Owned = ServerInfo;
+}
+
+impl<'a> Compatible<ServerInfoLazy<'a>> for ServerInfoLazy<'a> { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Compatible<ServerInfoLazy<'a>> for ServerInfo { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Compatible<ServerInfo> for ServerInfo { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Compatible<ServerInfo> for ServerInfoLazy<'a> { }
+
+impl<'a> ServerInfoLazy<'a> {
+
+    pub fn protocol_version(&self) -> DecodeResult<u32> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0))
+    }
+
+    pub fn name(&self) -> DecodeResult<&'a str> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 4))
+    }
+
+    pub fn motd(&self) -> DecodeResult<&'a str> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 12))
+    }
+
+    pub fn max_message_len(&self) -> DecodeResult<u64> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 20))
+    }
+
+    pub fn max_users(&self) -> DecodeResult<u64> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 28))
+    }
+
+    pub fn capabilities(&self) -> DecodeResult<mproto::ListLazy<'a, String>> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 36))
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl BaseLen for ServerInfo {
+    const BASE_LEN: usize = 44;
+}
+
+impl Encode for ServerInfo {
+    fn scratch_len(&self) -> usize {
+        self.protocol_version.scratch_len() + self.name.scratch_len() + self.motd.scratch_len() + self.max_message_len.scratch_len() + self.max_users.scratch_len() + self.capabilities.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        self.protocol_version.encode(cursor);
+        self.name.encode(cursor);
+        self.motd.encode(cursor);
+        self.max_message_len.encode(cursor);
+        self.max_users.encode(cursor);
+        self.capabilities.encode(cursor);
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Decode<'a> for ServerInfo {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let protocol_version = Decode::decode(cursor)?;
+        let name = Decode::decode(cursor)?;
+        let motd = Decode::decode(cursor)?;
+        let max_message_len = Decode::decode(cursor)?;
+        let max_users = Decode::decode(cursor)?;
+        let capabilities = Decode::decode(cursor)?;
+
+        Ok(ServerInfo {
+            protocol_version,
+            name,
+            motd,
+            max_message_len,
+            max_users,
+            capabilities,
+        })
+    }
+}
+
+impl<'a> BaseLen for ServerInfoLazy<'a> {
+    const BASE_LEN: usize = 44;
+}
+
+impl<'a> Encode for ServerInfoLazy<'a> {
+    fn scratch_len(&self) -> usize {
+        let protocol_version: u32 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let name: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 4)).unwrap();
+        let motd: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 12)).unwrap();
+        let max_message_len: u64 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 20)).unwrap();
+        let max_users: u64 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 28)).unwrap();
+        let capabilities: mproto::ListLazy<'a, String> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 36)).unwrap();
+        protocol_version.scratch_len() + name.scratch_len() + motd.scratch_len() + max_message_len.scratch_len() + max_users.scratch_len() + capabilities.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        let protocol_version: u32 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let name: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 4)).unwrap();
+        let motd: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 12)).unwrap();
+        let max_message_len: u64 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 20)).unwrap();
+        let max_users: u64 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 28)).unwrap();
+        let capabilities: mproto::ListLazy<'a, String> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 36)).unwrap();
+        protocol_version.encode(cursor);
+        name.encode(cursor);
+        motd.encode(cursor);
+        max_message_len.encode(cursor);
+        max_users.encode(cursor);
+        capabilities.encode(cursor);
+    }
+}
+
+impl<'a> Decode<'a> for ServerInfoLazy<'a> {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let offset = cursor.offset();
+        cursor.advance(Self::BASE_LEN);
+        Ok(ServerInfoLazy {
+            buffer: cursor.buffer(),
+            offset,
+        })
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> TryFrom<ServerInfoLazy<'a>> for ServerInfo {
+    type Error = DecodeError;
+
+    fn try_from(other: ServerInfoLazy<'a>) -> Result<Self, Self::Error> {
+        let cursor = DecodeCursor::at_offset(other.buffer, other.offset);
+        Decode::decode(&cursor)
+    }
+}
+
+impl<'a> Copy for ServerInfoLazy<'a> { }
+
+impl<'a> Clone for ServerInfoLazy<'a> {
+    fn clone(&self) -> Self {
+        Self {
+            buffer: self.buffer,
+            offset: self.offset,
+        }
+    }
+}
+
+impl<'a> core::fmt::Debug for ServerInfoLazy<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ServerInfoLazy")
+            .finish()
+    }
+}
+
+impl<'a> PartialEq for ServerInfoLazy<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.protocol_version().unwrap() == other.protocol_version().unwrap()
+            && self.name().unwrap() == other.name().unwrap()
+            && self.motd().unwrap() == other.motd().unwrap()
+            && self.max_message_len().unwrap() == other.max_message_len().unwrap()
+            && self.max_users().unwrap() == other.max_users().unwrap()
+            && self.capabilities().unwrap() == other.capabilities().unwrap()
+    }
+}
+
 #[cfg(any(feature = "std", feature = "alloc"))]
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
 pub struct RegisteredUser {
@@ -172,10 +409,215 @@ impl<'a> PartialEq for RegisteredUserLazy<'a> {
     }
 }
 
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct ChatMessage {
+    pub id: u64,
+    pub timestamp_ms: u64,
+    pub alias: String,
+    pub content: String,
+}
+
+pub struct ChatMessageLazy<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+}
+
+pub struct ChatMessageGen<
+    Alias: Encode + Compatible<String>,
+    Content: Encode + Compatible<String>,
+> {
+    pub id: u64,
+    pub timestamp_ms: u64,
+    pub alias: Alias,
+    pub content: Content,
+}
+
+impl<
+    Alias: Encode + Compatible<String>,
+    Content: Encode + Compatible<String>
+> Compatible<ChatMessage> for ChatMessageGen<Alias, Content> { }
+impl<
+    Alias: Encode + Compatible<String>,
+    Content: Encode + Compatible<String>
+> Compatible<ChatMessageGen<Alias, Content>> for ChatMessage { }
+
+impl<
+    Alias: Encode + Compatible<String>,
+    Content: Encode + Compatible<String>,
+> BaseLen for ChatMessageGen<Alias, Content> {
+    const BASE_LEN: usize = 8 + 8 + Alias::BASE_LEN + Content::BASE_LEN;
+}
+
+impl<
+    Alias: Encode + Compatible<String>,
+    Content: Encode + Compatible<String>,
+> Encode for ChatMessageGen<Alias, Content> {
+    fn scratch_len(&self) -> usize {
+        self.id.scratch_len() + self.timestamp_ms.scratch_len() + self.alias.scratch_len() + self.content.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        self.id.encode(cursor);
+        self.timestamp_ms.encode(cursor);
+        self.alias.encode(cursor);
+        self.content.encode(cursor);
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Owned for ChatMessage {
+    type Lazy<'a> = ChatMessageLazy<'a>;
+
+    fn lazy_to_owned(lazy: Self::Lazy<'_>) -> DecodeResult<Self> {
+        TryFrom::try_from(lazy)
+    }
+}
+
+impl<'a> Lazy<'a> for ChatMessageLazy<'a> {
+    type Owned = ChatMessage;
+}
+
+impl<'a> Compatible<ChatMessageLazy<'a>> for ChatMessageLazy<'a> { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Compatible<ChatMessageLazy<'a>> for ChatMessage { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Compatible<ChatMessage> for ChatMessage { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Compatible<ChatMessage> for ChatMessageLazy<'a> { }
+
+impl<'a> ChatMessageLazy<'a> {
+
+    pub fn id(&self) -> DecodeResult<u64> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0))
+    }
+
+    pub fn timestamp_ms(&self) -> DecodeResult<u64> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8))
+    }
+
+    pub fn alias(&self) -> DecodeResult<&'a str> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 16))
+    }
+
+    pub fn content(&self) -> DecodeResult<&'a str> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 24))
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl BaseLen for ChatMessage {
+    const BASE_LEN: usize = 32;
+}
+
+impl Encode for ChatMessage {
+    fn scratch_len(&self) -> usize {
+        self.id.scratch_len() + self.timestamp_ms.scratch_len() + self.alias.scratch_len() + self.content.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        self.id.encode(cursor);
+        self.timestamp_ms.encode(cursor);
+        self.alias.encode(cursor);
+        self.content.encode(cursor);
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Decode<'a> for ChatMessage {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let id = Decode::decode(cursor)?;
+        let timestamp_ms = Decode::decode(cursor)?;
+        let alias = Decode::decode(cursor)?;
+        let content = Decode::decode(cursor)?;
+
+        Ok(ChatMessage {
+            id,
+            timestamp_ms,
+            alias,
+            content,
+        })
+    }
+}
+
+impl<'a> BaseLen for ChatMessageLazy<'a> {
+    const BASE_LEN: usize = 32;
+}
+
+impl<'a> Encode for ChatMessageLazy<'a> {
+    fn scratch_len(&self) -> usize {
+        let id: u64 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let timestamp_ms: u64 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
+        let alias: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 16)).unwrap();
+        let content: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 24)).unwrap();
+        id.scratch_len() + timestamp_ms.scratch_len() + alias.scratch_len() + content.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        let id: u64 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let timestamp_ms: u64 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
+        let alias: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 16)).unwrap();
+        let content: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 24)).unwrap();
+        id.encode(cursor);
+        timestamp_ms.encode(cursor);
+        alias.encode(cursor);
+        content.encode(cursor);
+    }
+}
+
+impl<'a> Decode<'a> for ChatMessageLazy<'a> {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let offset = cursor.offset();
+        cursor.advance(Self::BASE_LEN);
+        Ok(ChatMessageLazy {
+            buffer: cursor.buffer(),
+            offset,
+        })
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> TryFrom<ChatMessageLazy<'a>> for ChatMessage {
+    type Error = DecodeError;
+
+    fn try_from(other: ChatMessageLazy<'a>) -> Result<Self, Self::Error> {
+        let cursor = DecodeCursor::at_offset(other.buffer, other.offset);
+        Decode::decode(&cursor)
+    }
+}
+
+impl<'a> Copy for ChatMessageLazy<'a> { }
+
+impl<'a> Clone for ChatMessageLazy<'a> {
+    fn clone(&self) -> Self {
+        Self {
+            buffer: self.buffer,
+            offset: self.offset,
+        }
+    }
+}
+
+impl<'a> core::fmt::Debug for ChatMessageLazy<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ChatMessageLazy")
+            .finish()
+    }
+}
+
+impl<'a> PartialEq for ChatMessageLazy<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id().unwrap() == other.id().unwrap()
+            && self.timestamp_ms().unwrap() == other.timestamp_ms().unwrap()
+            && self.alias().unwrap() == other.alias().unwrap()
+            && self.content().unwrap() == other.content().unwrap()
+    }
+}
+
 #[cfg(any(feature = "std", feature = "alloc"))]
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
 pub struct RegisterRequest {
     pub alias: String,
+    pub resume_digest: Option<String>,
 }
 
 pub struct RegisterRequestLazy<'a> {
@@ -185,32 +627,39 @@ pub struct RegisterRequestLazy<'a> {
 
 pub struct RegisterRequestGen<
     Alias: Encode + Compatible<String>,
+    ResumeDigest: Encode + Compatible<Option<String>>,
 > {
     pub alias: Alias,
+    pub resume_digest: ResumeDigest,
 }
 
 impl<
-    Alias: Encode + Compatible<String>
-> Compatible<RegisterRequest> for RegisterRequestGen<Alias> { }
+    Alias: Encode + Compatible<String>,
+    ResumeDigest: Encode + Compatible<Option<String>>
+> Compatible<RegisterRequest> for RegisterRequestGen<Alias, ResumeDigest> { }
 impl<
-    Alias: Encode + Compatible<String>
-> Compatible<RegisterRequestGen<Alias>> for RegisterRequest { }
+    Alias: Encode + Compatible<String>,
+    ResumeDigest: Encode + Compatible<Option<String>>
+> Compatible<RegisterRequestGen<Alias, ResumeDigest>> for RegisterRequest { }
 
 impl<
     Alias: Encode + Compatible<String>,
-> BaseLen for RegisterRequestGen<Alias> {
-    const BASE_LEN: usize = Alias::BASE_LEN;
+    ResumeDigest: Encode + Compatible<Option<String>>,
+> BaseLen for RegisterRequestGen<Alias, ResumeDigest> {
+    const BASE_LEN: usize = Alias::BASE_LEN + ResumeDigest::BASE_LEN;
 }
 
 impl<
     Alias: Encode + Compatible<String>,
-> Encode for RegisterRequestGen<Alias> {
+    ResumeDigest: Encode + Compatible<Option<String>>,
+> Encode for RegisterRequestGen<Alias, ResumeDigest> {
     fn scratch_len(&self) -> usize {
-        self.alias.scratch_len()
+        self.alias.scratch_len() + self.resume_digest.scratch_len()
     }
 
     fn encode(&self, cursor: &mut EncodeCursor) {
         self.alias.encode(cursor);
+        self.resume_digest.encode(cursor);
     }
 }
 
@@ -240,20 +689,25 @@ impl<'a> RegisterRequestLazy<'a> {
     pub fn alias(&self) -> DecodeResult<&'a str> {
         Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0))
     }
+
+    pub fn resume_digest(&self) -> DecodeResult<Option<&'a str>> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8))
+    }
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
 impl BaseLen for RegisterRequest {
-    const BASE_LEN: usize = 8;
+    const BASE_LEN: usize = 17;
 }
 
 impl Encode for RegisterRequest {
     fn scratch_len(&self) -> usize {
-        self.alias.scratch_len()
+        self.alias.scratch_len() + self.resume_digest.scratch_len()
     }
 
     fn encode(&self, cursor: &mut EncodeCursor) {
         self.alias.encode(cursor);
+        self.resume_digest.encode(cursor);
     }
 }
 
@@ -261,26 +715,31 @@ impl Encode for RegisterRequest {
 impl<'a> Decode<'a> for RegisterRequest {
     fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
         let alias = Decode::decode(cursor)?;
+        let resume_digest = Decode::decode(cursor)?;
 
         Ok(RegisterRequest {
             alias,
+            resume_digest,
         })
     }
 }
 
 impl<'a> BaseLen for RegisterRequestLazy<'a> {
-    const BASE_LEN: usize = 8;
+    const BASE_LEN: usize = 17;
 }
 
 impl<'a> Encode for RegisterRequestLazy<'a> {
     fn scratch_len(&self) -> usize {
         let alias: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
-        alias.scratch_len()
+        let resume_digest: Option<&'a str> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
+        alias.scratch_len() + resume_digest.scratch_len()
     }
 
     fn encode(&self, cursor: &mut EncodeCursor) {
         let alias: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let resume_digest: Option<&'a str> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
         alias.encode(cursor);
+        resume_digest.encode(cursor);
     }
 }
 
@@ -326,6 +785,7 @@ impl<'a> core::fmt::Debug for RegisterRequestLazy<'a> {
 impl<'a> PartialEq for RegisterRequestLazy<'a> {
     fn eq(&self, other: &Self) -> bool {
         self.alias().unwrap() == other.alias().unwrap()
+            && self.resume_digest().unwrap() == other.resume_digest().unwrap()
     }
 }
 
@@ -453,6 +913,8 @@ pub enum RegisterError {
     Internal,
     UserAlreadyExists,
     ChatFull,
+    RateLimited { retry_after_ms: u32 },
+    InvalidAlias,
 }
 
 #[derive(Clone)]
@@ -460,6 +922,8 @@ pub enum RegisterErrorLazy {
     Internal,
     UserAlreadyExists,
     ChatFull,
+    RateLimited { retry_after_ms: u32 },
+    InvalidAlias,
 }
 
 impl Compatible<RegisterErrorLazy> for RegisterErrorLazy { }
@@ -480,7 +944,7 @@ impl<'a> Lazy<'a> for RegisterErrorLazy {
 }
 
 impl BaseLen for RegisterError {
-    const BASE_LEN: usize = 1 + max(max(max(0, 0), 0), 0);
+    const BASE_LEN: usize = 1 + max(max(max(max(max(0, 0), 0), 0), 4), 0);
 }
 
 impl Encode for RegisterError {
@@ -489,6 +953,8 @@ impl Encode for RegisterError {
             RegisterError::Internal => 0,
             RegisterError::UserAlreadyExists => 0,
             RegisterError::ChatFull => 0,
+            RegisterError::RateLimited { retry_after_ms } => retry_after_ms.scratch_len(),
+            RegisterError::InvalidAlias => 0,
         }
     }
 
@@ -506,7 +972,16 @@ impl Encode for RegisterError {
                 cursor.base(1)[0] = 2;
                 cursor.base(Self::BASE_LEN - 1).fill(0);
             }
-        }
+            RegisterError::RateLimited { retry_after_ms } => {
+                cursor.base(1)[0] = 3;
+                retry_after_ms.encode(cursor);
+                cursor.base(Self::BASE_LEN - 1 - 4).fill(0);
+            }
+            RegisterError::InvalidAlias => {
+                cursor.base(1)[0] = 4;
+                cursor.base(Self::BASE_LEN - 1).fill(0);
+            }
+        }
     }
 }
 
@@ -526,13 +1001,22 @@ impl<'a> Decode<'a> for RegisterError {
                 cursor.advance(Self::BASE_LEN - 1);
                 Ok(RegisterError::ChatFull)
             }
+            3 => {
+                let retry_after_ms = Decode::decode(cursor)?;
+                cursor.advance(Self::BASE_LEN - 1 - 4);
+                Ok(RegisterError::RateLimited { retry_after_ms })
+            }
+            4 => {
+                cursor.advance(Self::BASE_LEN - 1);
+                Ok(RegisterError::InvalidAlias)
+            }
             _ => { Err(DecodeError) }
         }
     }
 }
 
 impl BaseLen for RegisterErrorLazy {
-    const BASE_LEN: usize = 1 + max(max(max(0, 0), 0), 0);
+    const BASE_LEN: usize = 1 + max(max(max(max(max(0, 0), 0), 0), 4), 0);
 }
 
 impl Encode for RegisterErrorLazy {
@@ -541,6 +1025,8 @@ impl Encode for RegisterErrorLazy {
             RegisterErrorLazy::Internal => 0,
             RegisterErrorLazy::UserAlreadyExists => 0,
             RegisterErrorLazy::ChatFull => 0,
+            RegisterErrorLazy::RateLimited { retry_after_ms } => retry_after_ms.scratch_len(),
+            RegisterErrorLazy::InvalidAlias => 0,
         }
     }
 
@@ -558,6 +1044,15 @@ impl Encode for RegisterErrorLazy {
                 cursor.base(1)[0] = 2;
                 cursor.base(Self::BASE_LEN - 1).fill(0);
             }
+            RegisterErrorLazy::RateLimited { retry_after_ms } => {
+                cursor.base(1)[0] = 3;
+                retry_after_ms.encode(cursor);
+                cursor.base(Self::BASE_LEN - 1 - 4).fill(0);
+            }
+            RegisterErrorLazy::InvalidAlias => {
+                cursor.base(1)[0] = 4;
+                cursor.base(Self::BASE_LEN - 1).fill(0);
+            }
         }
     }
 }
@@ -578,161 +1073,1526 @@ impl<'a> Decode<'a> for RegisterErrorLazy {
                 cursor.advance(Self::BASE_LEN - 1);
                 Ok(RegisterErrorLazy::ChatFull)
             }
+            3 => {
+                let retry_after_ms = Decode::decode(cursor)?;
+                cursor.advance(Self::BASE_LEN - 1 - 4);
+                Ok(RegisterErrorLazy::RateLimited { retry_after_ms })
+            }
+            4 => {
+                cursor.advance(Self::BASE_LEN - 1);
+                Ok(RegisterErrorLazy::InvalidAlias)
+            }
             _ => { Err(DecodeError) }
         }
     }
 }
 
-impl TryFrom<RegisterErrorLazy> for RegisterError {
+impl TryFrom<RegisterErrorLazy> for RegisterError {
+    type Error = DecodeError;
+
+    fn try_from(other: RegisterErrorLazy) -> Result<Self, Self::Error> {
+        match other {
+            RegisterErrorLazy::Internal => Ok(RegisterError::Internal),
+            RegisterErrorLazy::UserAlreadyExists => Ok(RegisterError::UserAlreadyExists),
+            RegisterErrorLazy::ChatFull => Ok(RegisterError::ChatFull),
+            RegisterErrorLazy::RateLimited { retry_after_ms } => Ok(RegisterError::RateLimited { retry_after_ms }),
+            RegisterErrorLazy::InvalidAlias => Ok(RegisterError::InvalidAlias),
+        }
+    }
+}
+
+impl Copy for RegisterErrorLazy { }
+
+impl core::fmt::Debug for RegisterErrorLazy {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RegisterErrorLazy")
+            .finish()
+    }
+}
+
+impl PartialEq for RegisterErrorLazy {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (RegisterErrorLazy::Internal, RegisterErrorLazy::Internal) => true,
+            (RegisterErrorLazy::UserAlreadyExists, RegisterErrorLazy::UserAlreadyExists) => true,
+            (RegisterErrorLazy::ChatFull, RegisterErrorLazy::ChatFull) => true,
+            (RegisterErrorLazy::RateLimited { retry_after_ms: a_retry_after_ms }, RegisterErrorLazy::RateLimited { retry_after_ms: b_retry_after_ms }) => a_retry_after_ms == b_retry_after_ms,
+            (RegisterErrorLazy::InvalidAlias, RegisterErrorLazy::InvalidAlias) => true,
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct SendMessageRequest {
+    pub content: String,
+    pub idempotency_key: String,
+}
+
+pub struct SendMessageRequestLazy<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+}
+
+pub struct SendMessageRequestGen<
+    Content: Encode + Compatible<String>,
+    IdempotencyKey: Encode + Compatible<String>,
+> {
+    pub content: Content,
+    pub idempotency_key: IdempotencyKey,
+}
+
+impl<
+    Content: Encode + Compatible<String>,
+    IdempotencyKey: Encode + Compatible<String>
+> Compatible<SendMessageRequest> for SendMessageRequestGen<Content, IdempotencyKey> { }
+impl<
+    Content: Encode + Compatible<String>,
+    IdempotencyKey: Encode + Compatible<String>
+> Compatible<SendMessageRequestGen<Content, IdempotencyKey>> for SendMessageRequest { }
+
+impl<
+    Content: Encode + Compatible<String>,
+    IdempotencyKey: Encode + Compatible<String>,
+> BaseLen for SendMessageRequestGen<Content, IdempotencyKey> {
+    const BASE_LEN: usize = Content::BASE_LEN + IdempotencyKey::BASE_LEN;
+}
+
+impl<
+    Content: Encode + Compatible<String>,
+    IdempotencyKey: Encode + Compatible<String>,
+> Encode for SendMessageRequestGen<Content, IdempotencyKey> {
+    fn scratch_len(&self) -> usize {
+        self.content.scratch_len() + self.idempotency_key.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        self.content.encode(cursor);
+        self.idempotency_key.encode(cursor);
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Owned for SendMessageRequest {
+    type Lazy<'a> = SendMessageRequestLazy<'a>;
+
+    fn lazy_to_owned(lazy: Self::Lazy<'_>) -> DecodeResult<Self> {
+        TryFrom::try_from(lazy)
+    }
+}
+
+impl<'a> Lazy<'a> for SendMessageRequestLazy<'a> {
+    type Owned = SendMessageRequest;
+}
+
+impl<'a> Compatible<SendMessageRequestLazy<'a>> for SendMessageRequestLazy<'a> { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Compatible<SendMessageRequestLazy<'a>> for SendMessageRequest { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Compatible<SendMessageRequest> for SendMessageRequest { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Compatible<SendMessageRequest> for SendMessageRequestLazy<'a> { }
+
+impl<'a> SendMessageRequestLazy<'a> {
+
+    pub fn content(&self) -> DecodeResult<&'a str> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0))
+    }
+
+    pub fn idempotency_key(&self) -> DecodeResult<&'a str> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8))
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl BaseLen for SendMessageRequest {
+    const BASE_LEN: usize = 16;
+}
+
+impl Encode for SendMessageRequest {
+    fn scratch_len(&self) -> usize {
+        self.content.scratch_len() + self.idempotency_key.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        self.content.encode(cursor);
+        self.idempotency_key.encode(cursor);
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Decode<'a> for SendMessageRequest {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let content = Decode::decode(cursor)?;
+        let idempotency_key = Decode::decode(cursor)?;
+
+        Ok(SendMessageRequest {
+            content,
+            idempotency_key,
+        })
+    }
+}
+
+impl<'a> BaseLen for SendMessageRequestLazy<'a> {
+    const BASE_LEN: usize = 16;
+}
+
+impl<'a> Encode for SendMessageRequestLazy<'a> {
+    fn scratch_len(&self) -> usize {
+        let content: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let idempotency_key: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
+        content.scratch_len() + idempotency_key.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        let content: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let idempotency_key: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
+        content.encode(cursor);
+        idempotency_key.encode(cursor);
+    }
+}
+
+impl<'a> Decode<'a> for SendMessageRequestLazy<'a> {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let offset = cursor.offset();
+        cursor.advance(Self::BASE_LEN);
+        Ok(SendMessageRequestLazy {
+            buffer: cursor.buffer(),
+            offset,
+        })
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> TryFrom<SendMessageRequestLazy<'a>> for SendMessageRequest {
+    type Error = DecodeError;
+
+    fn try_from(other: SendMessageRequestLazy<'a>) -> Result<Self, Self::Error> {
+        let cursor = DecodeCursor::at_offset(other.buffer, other.offset);
+        Decode::decode(&cursor)
+    }
+}
+
+impl<'a> Copy for SendMessageRequestLazy<'a> { }
+
+impl<'a> Clone for SendMessageRequestLazy<'a> {
+    fn clone(&self) -> Self {
+        Self {
+            buffer: self.buffer,
+            offset: self.offset,
+        }
+    }
+}
+
+impl<'a> core::fmt::Debug for SendMessageRequestLazy<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SendMessageRequestLazy")
+            .finish()
+    }
+}
+
+impl<'a> PartialEq for SendMessageRequestLazy<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.content().unwrap() == other.content().unwrap()
+            && self.idempotency_key().unwrap() == other.idempotency_key().unwrap()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct SendMessageSuccess {
+    pub message_id: u64,
+    pub duplicate: bool,
+}
+
+pub struct SendMessageSuccessLazy<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+}
+
+pub struct SendMessageSuccessGen {
+    pub message_id: u64,
+    pub duplicate: bool,
+}
+
+impl Compatible<SendMessageSuccess> for SendMessageSuccessGen { }
+impl Compatible<SendMessageSuccessGen> for SendMessageSuccess { }
+
+impl BaseLen for SendMessageSuccessGen {
+    const BASE_LEN: usize = 8 + 1;
+}
+
+impl Encode for SendMessageSuccessGen {
+    fn scratch_len(&self) -> usize {
+        self.message_id.scratch_len() + self.duplicate.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        self.message_id.encode(cursor);
+        self.duplicate.encode(cursor);
+    }
+}
+
+impl Owned for SendMessageSuccess {
+    type Lazy<'a> = SendMessageSuccessLazy<'a>;
+
+    fn lazy_to_owned(lazy: Self::Lazy<'_>) -> DecodeResult<Self> {
+        TryFrom::try_from(lazy)
+    }
+}
+
+impl<'a> Lazy<'a> for SendMessageSuccessLazy<'a> {
+    type Owned = SendMessageSuccess;
+}
+
+impl<'a> Compatible<SendMessageSuccessLazy<'a>> for SendMessageSuccessLazy<'a> { }
+impl<'a> Compatible<SendMessageSuccessLazy<'a>> for SendMessageSuccess { }
+impl Compatible<SendMessageSuccess> for SendMessageSuccess { }
+impl<'a> Compatible<SendMessageSuccess> for SendMessageSuccessLazy<'a> { }
+
+impl<'a> SendMessageSuccessLazy<'a> {
+
+    pub fn message_id(&self) -> DecodeResult<u64> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0))
+    }
+
+    pub fn duplicate(&self) -> DecodeResult<bool> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8))
+    }
+}
+
+impl BaseLen for SendMessageSuccess {
+    const BASE_LEN: usize = 9;
+}
+
+impl Encode for SendMessageSuccess {
+    fn scratch_len(&self) -> usize {
+        self.message_id.scratch_len() + self.duplicate.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        self.message_id.encode(cursor);
+        self.duplicate.encode(cursor);
+    }
+}
+
+impl<'a> Decode<'a> for SendMessageSuccess {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let message_id = Decode::decode(cursor)?;
+        let duplicate = Decode::decode(cursor)?;
+
+        Ok(SendMessageSuccess {
+            message_id,
+            duplicate,
+        })
+    }
+}
+
+impl<'a> BaseLen for SendMessageSuccessLazy<'a> {
+    const BASE_LEN: usize = 9;
+}
+
+impl<'a> Encode for SendMessageSuccessLazy<'a> {
+    fn scratch_len(&self) -> usize {
+        let message_id: u64 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let duplicate: bool = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
+        message_id.scratch_len() + duplicate.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        let message_id: u64 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let duplicate: bool = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
+        message_id.encode(cursor);
+        duplicate.encode(cursor);
+    }
+}
+
+impl<'a> Decode<'a> for SendMessageSuccessLazy<'a> {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let offset = cursor.offset();
+        cursor.advance(Self::BASE_LEN);
+        Ok(SendMessageSuccessLazy {
+            buffer: cursor.buffer(),
+            offset,
+        })
+    }
+}
+
+impl<'a> TryFrom<SendMessageSuccessLazy<'a>> for SendMessageSuccess {
+    type Error = DecodeError;
+
+    fn try_from(other: SendMessageSuccessLazy<'a>) -> Result<Self, Self::Error> {
+        let cursor = DecodeCursor::at_offset(other.buffer, other.offset);
+        Decode::decode(&cursor)
+    }
+}
+
+impl<'a> Copy for SendMessageSuccessLazy<'a> { }
+
+impl<'a> Clone for SendMessageSuccessLazy<'a> {
+    fn clone(&self) -> Self {
+        Self {
+            buffer: self.buffer,
+            offset: self.offset,
+        }
+    }
+}
+
+impl<'a> core::fmt::Debug for SendMessageSuccessLazy<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SendMessageSuccessLazy")
+            .finish()
+    }
+}
+
+impl<'a> PartialEq for SendMessageSuccessLazy<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.message_id().unwrap() == other.message_id().unwrap()
+            && self.duplicate().unwrap() == other.duplicate().unwrap()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub enum SendMessageError {
+    Internal,
+    NotRegistered,
+    MessageTooLong,
+    RateLimited { retry_after_ms: u32 },
+}
+
+#[derive(Clone)]
+pub enum SendMessageErrorLazy {
+    Internal,
+    NotRegistered,
+    MessageTooLong,
+    RateLimited { retry_after_ms: u32 },
+}
+
+impl Compatible<SendMessageErrorLazy> for SendMessageErrorLazy { }
+impl Compatible<SendMessageErrorLazy> for SendMessageError { }
+impl Compatible<SendMessageError> for SendMessageErrorLazy { }
+impl Compatible<SendMessageError> for SendMessageError { }
+
+impl Owned for SendMessageError {
+    type Lazy<'a> = SendMessageErrorLazy;
+
+    fn lazy_to_owned(lazy: Self::Lazy<'_>) -> DecodeResult<Self> {
+        TryFrom::try_from(lazy)
+    }
+}
+
+impl<'a> Lazy<'a> for SendMessageErrorLazy {
+    type Owned = SendMessageError;
+}
+
+impl BaseLen for SendMessageError {
+    const BASE_LEN: usize = 1 + max(max(max(max(0, 0), 0), 0), 4);
+}
+
+impl Encode for SendMessageError {
+    fn scratch_len(&self) -> usize {
+        match self {
+            SendMessageError::Internal => 0,
+            SendMessageError::NotRegistered => 0,
+            SendMessageError::MessageTooLong => 0,
+            SendMessageError::RateLimited { retry_after_ms } => retry_after_ms.scratch_len(),
+        }
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        match self {
+            SendMessageError::Internal => {
+                cursor.base(1)[0] = 0;
+                cursor.base(Self::BASE_LEN - 1).fill(0);
+            }
+            SendMessageError::NotRegistered => {
+                cursor.base(1)[0] = 1;
+                cursor.base(Self::BASE_LEN - 1).fill(0);
+            }
+            SendMessageError::MessageTooLong => {
+                cursor.base(1)[0] = 2;
+                cursor.base(Self::BASE_LEN - 1).fill(0);
+            }
+            SendMessageError::RateLimited { retry_after_ms } => {
+                cursor.base(1)[0] = 3;
+                retry_after_ms.encode(cursor);
+                cursor.base(Self::BASE_LEN - 1 - 4).fill(0);
+            }
+        }
+    }
+}
+
+impl<'a> Decode<'a> for SendMessageError {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let variant = cursor.base(1)[0];
+        match variant {
+            0 => {
+                cursor.advance(Self::BASE_LEN - 1);
+                Ok(SendMessageError::Internal)
+            }
+            1 => {
+                cursor.advance(Self::BASE_LEN - 1);
+                Ok(SendMessageError::NotRegistered)
+            }
+            2 => {
+                cursor.advance(Self::BASE_LEN - 1);
+                Ok(SendMessageError::MessageTooLong)
+            }
+            3 => {
+                let retry_after_ms = Decode::decode(cursor)?;
+                cursor.advance(Self::BASE_LEN - 1 - 4);
+                Ok(SendMessageError::RateLimited { retry_after_ms })
+            }
+            _ => { Err(DecodeError) }
+        }
+    }
+}
+
+impl BaseLen for SendMessageErrorLazy {
+    const BASE_LEN: usize = 1 + max(max(max(max(0, 0), 0), 0), 4);
+}
+
+impl Encode for SendMessageErrorLazy {
+    fn scratch_len(&self) -> usize {
+        match self {
+            SendMessageErrorLazy::Internal => 0,
+            SendMessageErrorLazy::NotRegistered => 0,
+            SendMessageErrorLazy::MessageTooLong => 0,
+            SendMessageErrorLazy::RateLimited { retry_after_ms } => retry_after_ms.scratch_len(),
+        }
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        match self {
+            SendMessageErrorLazy::Internal => {
+                cursor.base(1)[0] = 0;
+                cursor.base(Self::BASE_LEN - 1).fill(0);
+            }
+            SendMessageErrorLazy::NotRegistered => {
+                cursor.base(1)[0] = 1;
+                cursor.base(Self::BASE_LEN - 1).fill(0);
+            }
+            SendMessageErrorLazy::MessageTooLong => {
+                cursor.base(1)[0] = 2;
+                cursor.base(Self::BASE_LEN - 1).fill(0);
+            }
+            SendMessageErrorLazy::RateLimited { retry_after_ms } => {
+                cursor.base(1)[0] = 3;
+                retry_after_ms.encode(cursor);
+                cursor.base(Self::BASE_LEN - 1 - 4).fill(0);
+            }
+        }
+    }
+}
+
+impl<'a> Decode<'a> for SendMessageErrorLazy {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let variant = cursor.base(1)[0];
+        match variant {
+            0 => {
+                cursor.advance(Self::BASE_LEN - 1);
+                Ok(SendMessageErrorLazy::Internal)
+            }
+            1 => {
+                cursor.advance(Self::BASE_LEN - 1);
+                Ok(SendMessageErrorLazy::NotRegistered)
+            }
+            2 => {
+                cursor.advance(Self::BASE_LEN - 1);
+                Ok(SendMessageErrorLazy::MessageTooLong)
+            }
+            3 => {
+                let retry_after_ms = Decode::decode(cursor)?;
+                cursor.advance(Self::BASE_LEN - 1 - 4);
+                Ok(SendMessageErrorLazy::RateLimited { retry_after_ms })
+            }
+            _ => { Err(DecodeError) }
+        }
+    }
+}
+
+impl TryFrom<SendMessageErrorLazy> for SendMessageError {
+    type Error = DecodeError;
+
+    fn try_from(other: SendMessageErrorLazy) -> Result<Self, Self::Error> {
+        match other {
+            SendMessageErrorLazy::Internal => Ok(SendMessageError::Internal),
+            SendMessageErrorLazy::NotRegistered => Ok(SendMessageError::NotRegistered),
+            SendMessageErrorLazy::MessageTooLong => Ok(SendMessageError::MessageTooLong),
+            SendMessageErrorLazy::RateLimited { retry_after_ms } => Ok(SendMessageError::RateLimited { retry_after_ms }),
+        }
+    }
+}
+
+impl Copy for SendMessageErrorLazy { }
+
+impl core::fmt::Debug for SendMessageErrorLazy {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SendMessageErrorLazy")
+            .finish()
+    }
+}
+
+impl PartialEq for SendMessageErrorLazy {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (SendMessageErrorLazy::Internal, SendMessageErrorLazy::Internal) => true,
+            (SendMessageErrorLazy::NotRegistered, SendMessageErrorLazy::NotRegistered) => true,
+            (SendMessageErrorLazy::MessageTooLong, SendMessageErrorLazy::MessageTooLong) => true,
+            (SendMessageErrorLazy::RateLimited { retry_after_ms: a_retry_after_ms }, SendMessageErrorLazy::RateLimited { retry_after_ms: b_retry_after_ms }) => a_retry_after_ms == b_retry_after_ms,
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct ResumeSessionRequest {
+    pub alias: String,
+    pub resume_token: String,
+    pub next_resume_digest: String,
+}
+
+pub struct ResumeSessionRequestLazy<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+}
+
+pub struct ResumeSessionRequestGen<
+    Alias: Encode + Compatible<String>,
+    ResumeToken: Encode + Compatible<String>,
+    NextResumeDigest: Encode + Compatible<String>,
+> {
+    pub alias: Alias,
+    pub resume_token: ResumeToken,
+    pub next_resume_digest: NextResumeDigest,
+}
+
+impl<
+    Alias: Encode + Compatible<String>,
+    ResumeToken: Encode + Compatible<String>,
+    NextResumeDigest: Encode + Compatible<String>
+> Compatible<ResumeSessionRequest> for ResumeSessionRequestGen<Alias, ResumeToken, NextResumeDigest> { }
+impl<
+    Alias: Encode + Compatible<String>,
+    ResumeToken: Encode + Compatible<String>,
+    NextResumeDigest: Encode + Compatible<String>
+> Compatible<ResumeSessionRequestGen<Alias, ResumeToken, NextResumeDigest>> for ResumeSessionRequest { }
+
+impl<
+    Alias: Encode + Compatible<String>,
+    ResumeToken: Encode + Compatible<String>,
+    NextResumeDigest: Encode + Compatible<String>,
+> BaseLen for ResumeSessionRequestGen<Alias, ResumeToken, NextResumeDigest> {
+    const BASE_LEN: usize = Alias::BASE_LEN + ResumeToken::BASE_LEN + NextResumeDigest::BASE_LEN;
+}
+
+impl<
+    Alias: Encode + Compatible<String>,
+    ResumeToken: Encode + Compatible<String>,
+    NextResumeDigest: Encode + Compatible<String>,
+> Encode for ResumeSessionRequestGen<Alias, ResumeToken, NextResumeDigest> {
+    fn scratch_len(&self) -> usize {
+        self.alias.scratch_len() + self.resume_token.scratch_len() + self.next_resume_digest.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        self.alias.encode(cursor);
+        self.resume_token.encode(cursor);
+        self.next_resume_digest.encode(cursor);
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Owned for ResumeSessionRequest {
+    type Lazy<'a> = ResumeSessionRequestLazy<'a>;
+
+    fn lazy_to_owned(lazy: Self::Lazy<'_>) -> DecodeResult<Self> {
+        TryFrom::try_from(lazy)
+    }
+}
+
+impl<'a> Lazy<'a> for ResumeSessionRequestLazy<'a> {
+    type Owned = ResumeSessionRequest;
+}
+
+impl<'a> Compatible<ResumeSessionRequestLazy<'a>> for ResumeSessionRequestLazy<'a> { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Compatible<ResumeSessionRequestLazy<'a>> for ResumeSessionRequest { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Compatible<ResumeSessionRequest> for ResumeSessionRequest { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Compatible<ResumeSessionRequest> for ResumeSessionRequestLazy<'a> { }
+
+impl<'a> ResumeSessionRequestLazy<'a> {
+
+    pub fn alias(&self) -> DecodeResult<&'a str> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0))
+    }
+
+    pub fn resume_token(&self) -> DecodeResult<&'a str> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8))
+    }
+
+    pub fn next_resume_digest(&self) -> DecodeResult<&'a str> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 16))
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl BaseLen for ResumeSessionRequest {
+    const BASE_LEN: usize = 24;
+}
+
+impl Encode for ResumeSessionRequest {
+    fn scratch_len(&self) -> usize {
+        self.alias.scratch_len() + self.resume_token.scratch_len() + self.next_resume_digest.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        self.alias.encode(cursor);
+        self.resume_token.encode(cursor);
+        self.next_resume_digest.encode(cursor);
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Decode<'a> for ResumeSessionRequest {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let alias = Decode::decode(cursor)?;
+        let resume_token = Decode::decode(cursor)?;
+        let next_resume_digest = Decode::decode(cursor)?;
+
+        Ok(ResumeSessionRequest {
+            alias,
+            resume_token,
+            next_resume_digest,
+        })
+    }
+}
+
+impl<'a> BaseLen for ResumeSessionRequestLazy<'a> {
+    const BASE_LEN: usize = 24;
+}
+
+impl<'a> Encode for ResumeSessionRequestLazy<'a> {
+    fn scratch_len(&self) -> usize {
+        let alias: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let resume_token: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
+        let next_resume_digest: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 16)).unwrap();
+        alias.scratch_len() + resume_token.scratch_len() + next_resume_digest.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        let alias: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let resume_token: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
+        let next_resume_digest: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 16)).unwrap();
+        alias.encode(cursor);
+        resume_token.encode(cursor);
+        next_resume_digest.encode(cursor);
+    }
+}
+
+impl<'a> Decode<'a> for ResumeSessionRequestLazy<'a> {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let offset = cursor.offset();
+        cursor.advance(Self::BASE_LEN);
+        Ok(ResumeSessionRequestLazy {
+            buffer: cursor.buffer(),
+            offset,
+        })
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> TryFrom<ResumeSessionRequestLazy<'a>> for ResumeSessionRequest {
+    type Error = DecodeError;
+
+    fn try_from(other: ResumeSessionRequestLazy<'a>) -> Result<Self, Self::Error> {
+        let cursor = DecodeCursor::at_offset(other.buffer, other.offset);
+        Decode::decode(&cursor)
+    }
+}
+
+impl<'a> Copy for ResumeSessionRequestLazy<'a> { }
+
+impl<'a> Clone for ResumeSessionRequestLazy<'a> {
+    fn clone(&self) -> Self {
+        Self {
+            buffer: self.buffer,
+            offset: self.offset,
+        }
+    }
+}
+
+impl<'a> core::fmt::Debug for ResumeSessionRequestLazy<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ResumeSessionRequestLazy")
+            .finish()
+    }
+}
+
+impl<'a> PartialEq for ResumeSessionRequestLazy<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.alias().unwrap() == other.alias().unwrap()
+            && self.resume_token().unwrap() == other.resume_token().unwrap()
+            && self.next_resume_digest().unwrap() == other.next_resume_digest().unwrap()
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct ResumeSessionSuccess {
+    pub missed_messages: Vec<ChatMessage>,
+    pub truncated: bool,
+}
+
+pub struct ResumeSessionSuccessLazy<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+}
+
+pub struct ResumeSessionSuccessGen<
+    MissedMessages: Encode + Compatible<Vec<ChatMessage>>,
+> {
+    pub missed_messages: MissedMessages,
+    pub truncated: bool,
+}
+
+impl<
+    MissedMessages: Encode + Compatible<Vec<ChatMessage>>
+> Compatible<ResumeSessionSuccess> for ResumeSessionSuccessGen<MissedMessages> { }
+impl<
+    MissedMessages: Encode + Compatible<Vec<ChatMessage>>
+> Compatible<ResumeSessionSuccessGen<MissedMessages>> for ResumeSessionSuccess { }
+
+impl<
+    MissedMessages: Encode + Compatible<Vec<ChatMessage>>,
+> BaseLen for ResumeSessionSuccessGen<MissedMessages> {
+    const BASE_LEN: usize = 1 + MissedMessages::BASE_LEN;
+}
+
+impl<
+    MissedMessages: Encode + Compatible<Vec<ChatMessage>>,
+> Encode for ResumeSessionSuccessGen<MissedMessages> {
+    fn scratch_len(&self) -> usize {
+        self.missed_messages.scratch_len() + self.truncated.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        self.missed_messages.encode(cursor);
+        self.truncated.encode(cursor);
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Owned for ResumeSessionSuccess {
+    type Lazy<'a> = ResumeSessionSuccessLazy<'a>;
+
+    fn lazy_to_owned(lazy: Self::Lazy<'_>) -> DecodeResult<Self> {
+        TryFrom::try_from(lazy)
+    }
+}
+
+impl<'a> Lazy<'a> for ResumeSessionSuccessLazy<'a> {
+    type Owned = ResumeSessionSuccess;
+}
+
+impl<'a> Compatible<ResumeSessionSuccessLazy<'a>> for ResumeSessionSuccessLazy<'a> { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Compatible<ResumeSessionSuccessLazy<'a>> for ResumeSessionSuccess { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Compatible<ResumeSessionSuccess> for ResumeSessionSuccess { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Compatible<ResumeSessionSuccess> for ResumeSessionSuccessLazy<'a> { }
+
+impl<'a> ResumeSessionSuccessLazy<'a> {
+
+    pub fn missed_messages(&self) -> DecodeResult<mproto::ListLazy<'a, ChatMessage>> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0))
+    }
+
+    pub fn truncated(&self) -> DecodeResult<bool> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8))
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl BaseLen for ResumeSessionSuccess {
+    const BASE_LEN: usize = 9;
+}
+
+impl Encode for ResumeSessionSuccess {
+    fn scratch_len(&self) -> usize {
+        self.missed_messages.scratch_len() + self.truncated.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        self.missed_messages.encode(cursor);
+        self.truncated.encode(cursor);
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Decode<'a> for ResumeSessionSuccess {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let missed_messages = Decode::decode(cursor)?;
+        let truncated = Decode::decode(cursor)?;
+
+        Ok(ResumeSessionSuccess {
+            missed_messages,
+            truncated,
+        })
+    }
+}
+
+impl<'a> BaseLen for ResumeSessionSuccessLazy<'a> {
+    const BASE_LEN: usize = 9;
+}
+
+impl<'a> Encode for ResumeSessionSuccessLazy<'a> {
+    fn scratch_len(&self) -> usize {
+        let missed_messages: mproto::ListLazy<'a, ChatMessage> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let truncated: bool = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
+        missed_messages.scratch_len() + truncated.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        let missed_messages: mproto::ListLazy<'a, ChatMessage> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let truncated: bool = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
+        missed_messages.encode(cursor);
+        truncated.encode(cursor);
+    }
+}
+
+impl<'a> Decode<'a> for ResumeSessionSuccessLazy<'a> {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let offset = cursor.offset();
+        cursor.advance(Self::BASE_LEN);
+        Ok(ResumeSessionSuccessLazy {
+            buffer: cursor.buffer(),
+            offset,
+        })
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> TryFrom<ResumeSessionSuccessLazy<'a>> for ResumeSessionSuccess {
+    type Error = DecodeError;
+
+    fn try_from(other: ResumeSessionSuccessLazy<'a>) -> Result<Self, Self::Error> {
+        let cursor = DecodeCursor::at_offset(other.buffer, other.offset);
+        Decode::decode(&cursor)
+    }
+}
+
+impl<'a> Copy for ResumeSessionSuccessLazy<'a> { }
+
+impl<'a> Clone for ResumeSessionSuccessLazy<'a> {
+    fn clone(&self) -> Self {
+        Self {
+            buffer: self.buffer,
+            offset: self.offset,
+        }
+    }
+}
+
+impl<'a> core::fmt::Debug for ResumeSessionSuccessLazy<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ResumeSessionSuccessLazy")
+            .finish()
+    }
+}
+
+impl<'a> PartialEq for ResumeSessionSuccessLazy<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.missed_messages().unwrap() == other.missed_messages().unwrap()
+            && self.truncated().unwrap() == other.truncated().unwrap()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub enum ResumeSessionError {
+    Internal,
+    InvalidSession,
+    RateLimited { retry_after_ms: u32 },
+}
+
+#[derive(Clone)]
+pub enum ResumeSessionErrorLazy {
+    Internal,
+    InvalidSession,
+    RateLimited { retry_after_ms: u32 },
+}
+
+impl Compatible<ResumeSessionErrorLazy> for ResumeSessionErrorLazy { }
+impl Compatible<ResumeSessionErrorLazy> for ResumeSessionError { }
+impl Compatible<ResumeSessionError> for ResumeSessionErrorLazy { }
+impl Compatible<ResumeSessionError> for ResumeSessionError { }
+
+impl Owned for ResumeSessionError {
+    type Lazy<'a> = ResumeSessionErrorLazy;
+
+    fn lazy_to_owned(lazy: Self::Lazy<'_>) -> DecodeResult<Self> {
+        TryFrom::try_from(lazy)
+    }
+}
+
+impl<'a> Lazy<'a> for ResumeSessionErrorLazy {
+    type Owned = ResumeSessionError;
+}
+
+impl BaseLen for ResumeSessionError {
+    const BASE_LEN: usize = 1 + max(max(max(0, 0), 0), 4);
+}
+
+impl Encode for ResumeSessionError {
+    fn scratch_len(&self) -> usize {
+        match self {
+            ResumeSessionError::Internal => 0,
+            ResumeSessionError::InvalidSession => 0,
+            ResumeSessionError::RateLimited { retry_after_ms } => retry_after_ms.scratch_len(),
+        }
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        match self {
+            ResumeSessionError::Internal => {
+                cursor.base(1)[0] = 0;
+                cursor.base(Self::BASE_LEN - 1).fill(0);
+            }
+            ResumeSessionError::InvalidSession => {
+                cursor.base(1)[0] = 1;
+                cursor.base(Self::BASE_LEN - 1).fill(0);
+            }
+            ResumeSessionError::RateLimited { retry_after_ms } => {
+                cursor.base(1)[0] = 2;
+                retry_after_ms.encode(cursor);
+                cursor.base(Self::BASE_LEN - 1 - 4).fill(0);
+            }
+        }
+    }
+}
+
+impl<'a> Decode<'a> for ResumeSessionError {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let variant = cursor.base(1)[0];
+        match variant {
+            0 => {
+                cursor.advance(Self::BASE_LEN - 1);
+                Ok(ResumeSessionError::Internal)
+            }
+            1 => {
+                cursor.advance(Self::BASE_LEN - 1);
+                Ok(ResumeSessionError::InvalidSession)
+            }
+            2 => {
+                let retry_after_ms = Decode::decode(cursor)?;
+                cursor.advance(Self::BASE_LEN - 1 - 4);
+                Ok(ResumeSessionError::RateLimited { retry_after_ms })
+            }
+            _ => { Err(DecodeError) }
+        }
+    }
+}
+
+impl BaseLen for ResumeSessionErrorLazy {
+    const BASE_LEN: usize = 1 + max(max(max(0, 0), 0), 4);
+}
+
+impl Encode for ResumeSessionErrorLazy {
+    fn scratch_len(&self) -> usize {
+        match self {
+            ResumeSessionErrorLazy::Internal => 0,
+            ResumeSessionErrorLazy::InvalidSession => 0,
+            ResumeSessionErrorLazy::RateLimited { retry_after_ms } => retry_after_ms.scratch_len(),
+        }
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        match self {
+            ResumeSessionErrorLazy::Internal => {
+                cursor.base(1)[0] = 0;
+                cursor.base(Self::BASE_LEN - 1).fill(0);
+            }
+            ResumeSessionErrorLazy::InvalidSession => {
+                cursor.base(1)[0] = 1;
+                cursor.base(Self::BASE_LEN - 1).fill(0);
+            }
+            ResumeSessionErrorLazy::RateLimited { retry_after_ms } => {
+                cursor.base(1)[0] = 2;
+                retry_after_ms.encode(cursor);
+                cursor.base(Self::BASE_LEN - 1 - 4).fill(0);
+            }
+        }
+    }
+}
+
+impl<'a> Decode<'a> for ResumeSessionErrorLazy {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let variant = cursor.base(1)[0];
+        match variant {
+            0 => {
+                cursor.advance(Self::BASE_LEN - 1);
+                Ok(ResumeSessionErrorLazy::Internal)
+            }
+            1 => {
+                cursor.advance(Self::BASE_LEN - 1);
+                Ok(ResumeSessionErrorLazy::InvalidSession)
+            }
+            2 => {
+                let retry_after_ms = Decode::decode(cursor)?;
+                cursor.advance(Self::BASE_LEN - 1 - 4);
+                Ok(ResumeSessionErrorLazy::RateLimited { retry_after_ms })
+            }
+            _ => { Err(DecodeError) }
+        }
+    }
+}
+
+impl TryFrom<ResumeSessionErrorLazy> for ResumeSessionError {
+    type Error = DecodeError;
+
+    fn try_from(other: ResumeSessionErrorLazy) -> Result<Self, Self::Error> {
+        match other {
+            ResumeSessionErrorLazy::Internal => Ok(ResumeSessionError::Internal),
+            ResumeSessionErrorLazy::InvalidSession => Ok(ResumeSessionError::InvalidSession),
+            ResumeSessionErrorLazy::RateLimited { retry_after_ms } => Ok(ResumeSessionError::RateLimited { retry_after_ms }),
+        }
+    }
+}
+
+impl Copy for ResumeSessionErrorLazy { }
+
+impl core::fmt::Debug for ResumeSessionErrorLazy {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ResumeSessionErrorLazy")
+            .finish()
+    }
+}
+
+impl PartialEq for ResumeSessionErrorLazy {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ResumeSessionErrorLazy::Internal, ResumeSessionErrorLazy::Internal) => true,
+            (ResumeSessionErrorLazy::InvalidSession, ResumeSessionErrorLazy::InvalidSession) => true,
+            (ResumeSessionErrorLazy::RateLimited { retry_after_ms: a_retry_after_ms }, ResumeSessionErrorLazy::RateLimited { retry_after_ms: b_retry_after_ms }) => a_retry_after_ms == b_retry_after_ms,
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct SearchMessagesRequest {
+    pub query: String,
+    pub alias: Option<String>,
+    pub after_ms: Option<u64>,
+    pub before_ms: Option<u64>,
+    pub before_id: Option<u64>,
+    pub limit: u32,
+}
+
+pub struct SearchMessagesRequestLazy<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+}
+
+pub struct SearchMessagesRequestGen<
+    Query: Encode + Compatible<String>,
+    Alias: Encode + Compatible<Option<String>>,
+    AfterMs: Encode + Compatible<Option<u64>>,
+    BeforeMs: Encode + Compatible<Option<u64>>,
+    BeforeId: Encode + Compatible<Option<u64>>,
+> {
+    pub query: Query,
+    pub alias: Alias,
+    pub after_ms: AfterMs,
+    pub before_ms: BeforeMs,
+    pub before_id: BeforeId,
+    pub limit: u32,
+}
+
+impl<
+    Query: Encode + Compatible<String>,
+    Alias: Encode + Compatible<Option<String>>,
+    AfterMs: Encode + Compatible<Option<u64>>,
+    BeforeMs: Encode + Compatible<Option<u64>>,
+    BeforeId: Encode + Compatible<Option<u64>>
+> Compatible<SearchMessagesRequest> for SearchMessagesRequestGen<Query, Alias, AfterMs, BeforeMs, BeforeId> { }
+impl<
+    Query: Encode + Compatible<String>,
+    Alias: Encode + Compatible<Option<String>>,
+    AfterMs: Encode + Compatible<Option<u64>>,
+    BeforeMs: Encode + Compatible<Option<u64>>,
+    BeforeId: Encode + Compatible<Option<u64>>
+> Compatible<SearchMessagesRequestGen<Query, Alias, AfterMs, BeforeMs, BeforeId>> for SearchMessagesRequest { }
+
+impl<
+    Query: Encode + Compatible<String>,
+    Alias: Encode + Compatible<Option<String>>,
+    AfterMs: Encode + Compatible<Option<u64>>,
+    BeforeMs: Encode + Compatible<Option<u64>>,
+    BeforeId: Encode + Compatible<Option<u64>>,
+> BaseLen for SearchMessagesRequestGen<Query, Alias, AfterMs, BeforeMs, BeforeId> {
+    const BASE_LEN: usize = Query::BASE_LEN + Alias::BASE_LEN + AfterMs::BASE_LEN + BeforeMs::BASE_LEN + BeforeId::BASE_LEN + 4;
+}
+
+impl<
+    Query: Encode + Compatible<String>,
+    Alias: Encode + Compatible<Option<String>>,
+    AfterMs: Encode + Compatible<Option<u64>>,
+    BeforeMs: Encode + Compatible<Option<u64>>,
+    BeforeId: Encode + Compatible<Option<u64>>,
+> Encode for SearchMessagesRequestGen<Query, Alias, AfterMs, BeforeMs, BeforeId> {
+    fn scratch_len(&self) -> usize {
+        self.query.scratch_len() + self.alias.scratch_len() + self.after_ms.scratch_len() + self.before_ms.scratch_len() + self.before_id.scratch_len() + self.limit.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        self.query.encode(cursor);
+        self.alias.encode(cursor);
+        self.after_ms.encode(cursor);
+        self.before_ms.encode(cursor);
+        self.before_id.encode(cursor);
+        self.limit.encode(cursor);
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Owned for SearchMessagesRequest {
+    type Lazy<'a> = SearchMessagesRequestLazy<'a>;
+
+    fn lazy_to_owned(lazy: Self::Lazy<'_>) -> DecodeResult<Self> {
+        TryFrom::try_from(lazy)
+    }
+}
+
+impl<'a> Lazy<'a> for SearchMessagesRequestLazy<'a> {
+    type Owned = SearchMessagesRequest;
+}
+
+impl<'a> Compatible<SearchMessagesRequestLazy<'a>> for SearchMessagesRequestLazy<'a> { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Compatible<SearchMessagesRequestLazy<'a>> for SearchMessagesRequest { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Compatible<SearchMessagesRequest> for SearchMessagesRequest { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Compatible<SearchMessagesRequest> for SearchMessagesRequestLazy<'a> { }
+
+impl<'a> SearchMessagesRequestLazy<'a> {
+
+    pub fn query(&self) -> DecodeResult<&'a str> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0))
+    }
+
+    pub fn alias(&self) -> DecodeResult<Option<&'a str>> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8))
+    }
+
+    pub fn after_ms(&self) -> DecodeResult<Option<u64>> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 17))
+    }
+
+    pub fn before_ms(&self) -> DecodeResult<Option<u64>> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 26))
+    }
+
+    pub fn before_id(&self) -> DecodeResult<Option<u64>> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 35))
+    }
+
+    pub fn limit(&self) -> DecodeResult<u32> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 44))
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl BaseLen for SearchMessagesRequest {
+    const BASE_LEN: usize = 48;
+}
+
+impl Encode for SearchMessagesRequest {
+    fn scratch_len(&self) -> usize {
+        self.query.scratch_len() + self.alias.scratch_len() + self.after_ms.scratch_len() + self.before_ms.scratch_len() + self.before_id.scratch_len() + self.limit.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        self.query.encode(cursor);
+        self.alias.encode(cursor);
+        self.after_ms.encode(cursor);
+        self.before_ms.encode(cursor);
+        self.before_id.encode(cursor);
+        self.limit.encode(cursor);
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Decode<'a> for SearchMessagesRequest {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let query = Decode::decode(cursor)?;
+        let alias = Decode::decode(cursor)?;
+        let after_ms = Decode::decode(cursor)?;
+        let before_ms = Decode::decode(cursor)?;
+        let before_id = Decode::decode(cursor)?;
+        let limit = Decode::decode(cursor)?;
+
+        Ok(SearchMessagesRequest {
+            query,
+            alias,
+            after_ms,
+            before_ms,
+            before_id,
+            limit,
+        })
+    }
+}
+
+impl<'a> BaseLen for SearchMessagesRequestLazy<'a> {
+    const BASE_LEN: usize = 48;
+}
+
+impl<'a> Encode for SearchMessagesRequestLazy<'a> {
+    fn scratch_len(&self) -> usize {
+        let query: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let alias: Option<&'a str> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
+        let after_ms: Option<u64> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 17)).unwrap();
+        let before_ms: Option<u64> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 26)).unwrap();
+        let before_id: Option<u64> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 35)).unwrap();
+        let limit: u32 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 44)).unwrap();
+        query.scratch_len() + alias.scratch_len() + after_ms.scratch_len() + before_ms.scratch_len() + before_id.scratch_len() + limit.scratch_len()
+    }
+
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        let query: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let alias: Option<&'a str> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
+        let after_ms: Option<u64> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 17)).unwrap();
+        let before_ms: Option<u64> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 26)).unwrap();
+        let before_id: Option<u64> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 35)).unwrap();
+        let limit: u32 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 44)).unwrap();
+        query.encode(cursor);
+        alias.encode(cursor);
+        after_ms.encode(cursor);
+        before_ms.encode(cursor);
+        before_id.encode(cursor);
+        limit.encode(cursor);
+    }
+}
+
+impl<'a> Decode<'a> for SearchMessagesRequestLazy<'a> {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let offset = cursor.offset();
+        cursor.advance(Self::BASE_LEN);
+        Ok(SearchMessagesRequestLazy {
+            buffer: cursor.buffer(),
+            offset,
+        })
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> TryFrom<SearchMessagesRequestLazy<'a>> for SearchMessagesRequest {
     type Error = DecodeError;
 
-    fn try_from(other: RegisterErrorLazy) -> Result<Self, Self::Error> {
-        match other {
-            RegisterErrorLazy::Internal => Ok(RegisterError::Internal),
-            RegisterErrorLazy::UserAlreadyExists => Ok(RegisterError::UserAlreadyExists),
-            RegisterErrorLazy::ChatFull => Ok(RegisterError::ChatFull),
-        }
+    fn try_from(other: SearchMessagesRequestLazy<'a>) -> Result<Self, Self::Error> {
+        let cursor = DecodeCursor::at_offset(other.buffer, other.offset);
+        Decode::decode(&cursor)
     }
 }
 
-impl Copy for RegisterErrorLazy { }
+impl<'a> Copy for SearchMessagesRequestLazy<'a> { }
 
-impl core::fmt::Debug for RegisterErrorLazy {
+impl<'a> Clone for SearchMessagesRequestLazy<'a> {
+    fn clone(&self) -> Self {
+        Self {
+            buffer: self.buffer,
+            offset: self.offset,
+        }
+    }
+}
+
+impl<'a> core::fmt::Debug for SearchMessagesRequestLazy<'a> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.debug_struct("RegisterErrorLazy")
+        f.debug_struct("SearchMessagesRequestLazy")
             .finish()
     }
 }
 
-impl PartialEq for RegisterErrorLazy {
+impl<'a> PartialEq for SearchMessagesRequestLazy<'a> {
     fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (RegisterErrorLazy::Internal, RegisterErrorLazy::Internal) => true,
-            (RegisterErrorLazy::UserAlreadyExists, RegisterErrorLazy::UserAlreadyExists) => true,
-            (RegisterErrorLazy::ChatFull, RegisterErrorLazy::ChatFull) => true,
-            #[allow(unreachable_patterns)]
-            _ => false,
-        }
+        self.query().unwrap() == other.query().unwrap()
+            && self.alias().unwrap() == other.alias().unwrap()
+            && self.after_ms().unwrap() == other.after_ms().unwrap()
+            && self.before_ms().unwrap() == other.before_ms().unwrap()
+            && self.before_id().unwrap() == other.before_id().unwrap()
+            && self.limit().unwrap() == other.limit().unwrap()
     }
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
-pub struct SendMessageRequest {
-    pub content: String,
+pub struct SearchResult {
+    pub message_id: u64,
+    pub timestamp_ms: u64,
+    pub alias: String,
+    pub snippet: String,
 }
 
-pub struct SendMessageRequestLazy<'a> {
+pub struct SearchResultLazy<'a> {
     buffer: &'a [u8],
     offset: usize,
 }
 
-pub struct SendMessageRequestGen<
-    Content: Encode + Compatible<String>,
+pub struct SearchResultGen<
+    Alias: Encode + Compatible<String>,
+    Snippet: Encode + Compatible<String>,
 > {
-    pub content: Content,
+    pub message_id: u64,
+    pub timestamp_ms: u64,
+    pub alias: Alias,
+    pub snippet: Snippet,
 }
 
 impl<
-    Content: Encode + Compatible<String>
-> Compatible<SendMessageRequest> for SendMessageRequestGen<Content> { }
+    Alias: Encode + Compatible<String>,
+    Snippet: Encode + Compatible<String>
+> Compatible<SearchResult> for SearchResultGen<Alias, Snippet> { }
 impl<
-    Content: Encode + Compatible<String>
-> Compatible<SendMessageRequestGen<Content>> for SendMessageRequest { }
+    Alias: Encode + Compatible<String>,
+    Snippet: Encode + Compatible<String>
+> Compatible<SearchResultGen<Alias, Snippet>> for SearchResult { }
 
 impl<
-    Content: Encode + Compatible<String>,
-> BaseLen for SendMessageRequestGen<Content> {
-    const BASE_LEN: usize = Content::BASE_LEN;
+    Alias: Encode + Compatible<String>,
+    Snippet: Encode + Compatible<String>,
+> BaseLen for SearchResultGen<Alias, Snippet> {
+    const BASE_LEN: usize = 8 + 8 + Alias::BASE_LEN + Snippet::BASE_LEN;
 }
 
 impl<
-    Content: Encode + Compatible<String>,
-> Encode for SendMessageRequestGen<Content> {
+    Alias: Encode + Compatible<String>,
+    Snippet: Encode + Compatible<String>,
+> Encode for SearchResultGen<Alias, Snippet> {
     fn scratch_len(&self) -> usize {
-        self.content.scratch_len()
+        self.message_id.scratch_len() + self.timestamp_ms.scratch_len() + self.alias.scratch_len() + self.snippet.scratch_len()
     }
 
     fn encode(&self, cursor: &mut EncodeCursor) {
-        self.content.encode(cursor);
+        self.message_id.encode(cursor);
+        self.timestamp_ms.encode(cursor);
+        self.alias.encode(cursor);
+        self.snippet.encode(cursor);
     }
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
-impl Owned for SendMessageRequest {
-    type Lazy<'a> = SendMessageRequestLazy<'a>;
+impl Owned for SearchResult {
+    type Lazy<'a> = SearchResultLazy<'a>;
 
     fn lazy_to_owned(lazy: Self::Lazy<'_>) -> DecodeResult<Self> {
         TryFrom::try_from(lazy)
     }
 }
 
-impl<'a> Lazy<'a> for SendMessageRequestLazy<'a> {
-    type Owned = SendMessageRequest;
+impl<'a> Lazy<'a> for SearchResultLazy<'a> {
+    type Owned = SearchResult;
 }
 
-impl<'a> Compatible<SendMessageRequestLazy<'a>> for SendMessageRequestLazy<'a> { }
+impl<'a> Compatible<SearchResultLazy<'a>> for SearchResultLazy<'a> { }
 #[cfg(any(feature = "std", feature = "alloc"))]
-impl<'a> Compatible<SendMessageRequestLazy<'a>> for SendMessageRequest { }
+impl<'a> Compatible<SearchResultLazy<'a>> for SearchResult { }
 #[cfg(any(feature = "std", feature = "alloc"))]
-impl Compatible<SendMessageRequest> for SendMessageRequest { }
+impl Compatible<SearchResult> for SearchResult { }
 #[cfg(any(feature = "std", feature = "alloc"))]
-impl<'a> Compatible<SendMessageRequest> for SendMessageRequestLazy<'a> { }
+impl<'a> Compatible<SearchResult> for SearchResultLazy<'a> { }
 
-impl<'a> SendMessageRequestLazy<'a> {
+impl<'a> SearchResultLazy<'a> {
 
-    pub fn content(&self) -> DecodeResult<&'a str> {
+    pub fn message_id(&self) -> DecodeResult<u64> {
         Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0))
     }
+
+    pub fn timestamp_ms(&self) -> DecodeResult<u64> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8))
+    }
+
+    pub fn alias(&self) -> DecodeResult<&'a str> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 16))
+    }
+
+    pub fn snippet(&self) -> DecodeResult<&'a str> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 24))
+    }
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
-impl BaseLen for SendMessageRequest {
-    const BASE_LEN: usize = 8;
+impl BaseLen for SearchResult {
+    const BASE_LEN: usize = 32;
 }
 
-impl Encode for SendMessageRequest {
+impl Encode for SearchResult {
     fn scratch_len(&self) -> usize {
-        self.content.scratch_len()
+        self.message_id.scratch_len() + self.timestamp_ms.scratch_len() + self.alias.scratch_len() + self.snippet.scratch_len()
     }
 
     fn encode(&self, cursor: &mut EncodeCursor) {
-        self.content.encode(cursor);
+        self.message_id.encode(cursor);
+        self.timestamp_ms.encode(cursor);
+        self.alias.encode(cursor);
+        self.snippet.encode(cursor);
     }
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
-impl<'a> Decode<'a> for SendMessageRequest {
+impl<'a> Decode<'a> for SearchResult {
     fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
-        let content = Decode::decode(cursor)?;
+        let message_id = Decode::decode(cursor)?;
+        let timestamp_ms = Decode::decode(cursor)?;
+        let alias = Decode::decode(cursor)?;
+        let snippet = Decode::decode(cursor)?;
 
-        Ok(SendMessageRequest {
-            content,
+        Ok(SearchResult {
+            message_id,
+            timestamp_ms,
+            alias,
+            snippet,
         })
     }
 }
 
-impl<'a> BaseLen for SendMessageRequestLazy<'a> {
-    const BASE_LEN: usize = 8;
+impl<'a> BaseLen for SearchResultLazy<'a> {
+    const BASE_LEN: usize = 32;
 }
 
-impl<'a> Encode for SendMessageRequestLazy<'a> {
+impl<'a> Encode for SearchResultLazy<'a> {
     fn scratch_len(&self) -> usize {
-        let content: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
-        content.scratch_len()
+        let message_id: u64 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let timestamp_ms: u64 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
+        let alias: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 16)).unwrap();
+        let snippet: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 24)).unwrap();
+        message_id.scratch_len() + timestamp_ms.scratch_len() + alias.scratch_len() + snippet.scratch_len()
     }
 
     fn encode(&self, cursor: &mut EncodeCursor) {
-        let content: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
-        content.encode(cursor);
+        let message_id: u64 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let timestamp_ms: u64 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
+        let alias: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 16)).unwrap();
+        let snippet: &'a str = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 24)).unwrap();
+        message_id.encode(cursor);
+        timestamp_ms.encode(cursor);
+        alias.encode(cursor);
+        snippet.encode(cursor);
     }
 }
 
-impl<'a> Decode<'a> for SendMessageRequestLazy<'a> {
+impl<'a> Decode<'a> for SearchResultLazy<'a> {
     fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
         let offset = cursor.offset();
         cursor.advance(Self::BASE_LEN);
-        Ok(SendMessageRequestLazy {
+        Ok(SearchResultLazy {
             buffer: cursor.buffer(),
             offset,
         })
@@ -740,18 +2600,18 @@ impl<'a> Decode<'a> for SendMessageRequestLazy<'a> {
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
-impl<'a> TryFrom<SendMessageRequestLazy<'a>> for SendMessageRequest {
+impl<'a> TryFrom<SearchResultLazy<'a>> for SearchResult {
     type Error = DecodeError;
 
-    fn try_from(other: SendMessageRequestLazy<'a>) -> Result<Self, Self::Error> {
+    fn try_from(other: SearchResultLazy<'a>) -> Result<Self, Self::Error> {
         let cursor = DecodeCursor::at_offset(other.buffer, other.offset);
         Decode::decode(&cursor)
     }
 }
 
-impl<'a> Copy for SendMessageRequestLazy<'a> { }
+impl<'a> Copy for SearchResultLazy<'a> { }
 
-impl<'a> Clone for SendMessageRequestLazy<'a> {
+impl<'a> Clone for SearchResultLazy<'a> {
     fn clone(&self) -> Self {
         Self {
             buffer: self.buffer,
@@ -760,117 +2620,176 @@ impl<'a> Clone for SendMessageRequestLazy<'a> {
     }
 }
 
-impl<'a> core::fmt::Debug for SendMessageRequestLazy<'a> {
+impl<'a> core::fmt::Debug for SearchResultLazy<'a> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.debug_struct("SendMessageRequestLazy")
+        f.debug_struct("SearchResultLazy")
             .finish()
     }
 }
 
-impl<'a> PartialEq for SendMessageRequestLazy<'a> {
+impl<'a> PartialEq for SearchResultLazy<'a> {
     fn eq(&self, other: &Self) -> bool {
-        self.content().unwrap() == other.content().unwrap()
+        self.message_id().unwrap() == other.message_id().unwrap()
+            && self.timestamp_ms().unwrap() == other.timestamp_ms().unwrap()
+            && self.alias().unwrap() == other.alias().unwrap()
+            && self.snippet().unwrap() == other.snippet().unwrap()
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash, Default)]
-pub struct SendMessageSuccess {}
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct SearchMessagesSuccess {
+    pub results: Vec<SearchResult>,
+    pub next_before_id: Option<u64>,
+}
 
-pub struct SendMessageSuccessLazy<'a> {
+pub struct SearchMessagesSuccessLazy<'a> {
     buffer: &'a [u8],
     offset: usize,
 }
 
-pub struct SendMessageSuccessGen<> {}
+pub struct SearchMessagesSuccessGen<
+    Results: Encode + Compatible<Vec<SearchResult>>,
+    NextBeforeId: Encode + Compatible<Option<u64>>,
+> {
+    pub results: Results,
+    pub next_before_id: NextBeforeId,
+}
 
-impl<> Compatible<SendMessageSuccess> for SendMessageSuccessGen<> { }
-impl<> Compatible<SendMessageSuccessGen<>> for SendMessageSuccess { }
+impl<
+    Results: Encode + Compatible<Vec<SearchResult>>,
+    NextBeforeId: Encode + Compatible<Option<u64>>
+> Compatible<SearchMessagesSuccess> for SearchMessagesSuccessGen<Results, NextBeforeId> { }
+impl<
+    Results: Encode + Compatible<Vec<SearchResult>>,
+    NextBeforeId: Encode + Compatible<Option<u64>>
+> Compatible<SearchMessagesSuccessGen<Results, NextBeforeId>> for SearchMessagesSuccess { }
 
-impl<> BaseLen for SendMessageSuccessGen<> {
-    const BASE_LEN: usize = 0;
+impl<
+    Results: Encode + Compatible<Vec<SearchResult>>,
+    NextBeforeId: Encode + Compatible<Option<u64>>,
+> BaseLen for SearchMessagesSuccessGen<Results, NextBeforeId> {
+    const BASE_LEN: usize = Results::BASE_LEN + NextBeforeId::BASE_LEN;
 }
 
-impl<> Encode for SendMessageSuccessGen<> {
+impl<
+    Results: Encode + Compatible<Vec<SearchResult>>,
+    NextBeforeId: Encode + Compatible<Option<u64>>,
+> Encode for SearchMessagesSuccessGen<Results, NextBeforeId> {
     fn scratch_len(&self) -> usize {
-        0
+        self.results.scratch_len() + self.next_before_id.scratch_len()
     }
 
-    fn encode(&self, _: &mut EncodeCursor) {}
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        self.results.encode(cursor);
+        self.next_before_id.encode(cursor);
+    }
 }
 
-impl Owned for SendMessageSuccess {
-    type Lazy<'a> = SendMessageSuccessLazy<'a>;
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Owned for SearchMessagesSuccess {
+    type Lazy<'a> = SearchMessagesSuccessLazy<'a>;
 
     fn lazy_to_owned(lazy: Self::Lazy<'_>) -> DecodeResult<Self> {
         TryFrom::try_from(lazy)
     }
 }
 
-impl<'a> Lazy<'a> for SendMessageSuccessLazy<'a> {
-    type Owned = SendMessageSuccess;
+impl<'a> Lazy<'a> for SearchMessagesSuccessLazy<'a> {
+    type Owned = SearchMessagesSuccess;
 }
 
-impl<'a> Compatible<SendMessageSuccessLazy<'a>> for SendMessageSuccessLazy<'a> { }
-impl<'a> Compatible<SendMessageSuccessLazy<'a>> for SendMessageSuccess { }
-impl Compatible<SendMessageSuccess> for SendMessageSuccess { }
-impl<'a> Compatible<SendMessageSuccess> for SendMessageSuccessLazy<'a> { }
+impl<'a> Compatible<SearchMessagesSuccessLazy<'a>> for SearchMessagesSuccessLazy<'a> { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Compatible<SearchMessagesSuccessLazy<'a>> for SearchMessagesSuccess { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Compatible<SearchMessagesSuccess> for SearchMessagesSuccess { }
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Compatible<SearchMessagesSuccess> for SearchMessagesSuccessLazy<'a> { }
 
-impl<'a> SendMessageSuccessLazy<'a> {}
+impl<'a> SearchMessagesSuccessLazy<'a> {
 
-impl BaseLen for SendMessageSuccess {
-    const BASE_LEN: usize = 0;
+    pub fn results(&self) -> DecodeResult<mproto::ListLazy<'a, SearchResult>> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0))
+    }
+
+    pub fn next_before_id(&self) -> DecodeResult<Option<u64>> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8))
+    }
 }
 
-impl Encode for SendMessageSuccess {
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl BaseLen for SearchMessagesSuccess {
+    const BASE_LEN: usize = 17;
+}
+
+impl Encode for SearchMessagesSuccess {
     fn scratch_len(&self) -> usize {
-        0
+        self.results.scratch_len() + self.next_before_id.scratch_len()
     }
 
-    fn encode(&self, _: &mut EncodeCursor) {}
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        self.results.encode(cursor);
+        self.next_before_id.encode(cursor);
+    }
 }
 
-impl<'a> Decode<'a> for SendMessageSuccess {
-    fn decode(_: &DecodeCursor<'a>) -> DecodeResult<Self> {
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Decode<'a> for SearchMessagesSuccess {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let results = Decode::decode(cursor)?;
+        let next_before_id = Decode::decode(cursor)?;
 
-        Ok(SendMessageSuccess {})
+        Ok(SearchMessagesSuccess {
+            results,
+            next_before_id,
+        })
     }
 }
 
-impl<'a> BaseLen for SendMessageSuccessLazy<'a> {
-    const BASE_LEN: usize = 0;
+impl<'a> BaseLen for SearchMessagesSuccessLazy<'a> {
+    const BASE_LEN: usize = 17;
 }
 
-impl<'a> Encode for SendMessageSuccessLazy<'a> {
+impl<'a> Encode for SearchMessagesSuccessLazy<'a> {
     fn scratch_len(&self) -> usize {
-        0
+        let results: mproto::ListLazy<'a, SearchResult> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let next_before_id: Option<u64> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
+        results.scratch_len() + next_before_id.scratch_len()
     }
 
-    fn encode(&self, _: &mut EncodeCursor) {}
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        let results: mproto::ListLazy<'a, SearchResult> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let next_before_id: Option<u64> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
+        results.encode(cursor);
+        next_before_id.encode(cursor);
+    }
 }
 
-impl<'a> Decode<'a> for SendMessageSuccessLazy<'a> {
+impl<'a> Decode<'a> for SearchMessagesSuccessLazy<'a> {
     fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
         let offset = cursor.offset();
         cursor.advance(Self::BASE_LEN);
-        Ok(SendMessageSuccessLazy {
+        Ok(SearchMessagesSuccessLazy {
             buffer: cursor.buffer(),
             offset,
         })
     }
 }
 
-impl<'a> TryFrom<SendMessageSuccessLazy<'a>> for SendMessageSuccess {
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> TryFrom<SearchMessagesSuccessLazy<'a>> for SearchMessagesSuccess {
     type Error = DecodeError;
 
-    fn try_from(other: SendMessageSuccessLazy<'a>) -> Result<Self, Self::Error> {
+    fn try_from(other: SearchMessagesSuccessLazy<'a>) -> Result<Self, Self::Error> {
         let cursor = DecodeCursor::at_offset(other.buffer, other.offset);
         Decode::decode(&cursor)
     }
 }
 
-impl<'a> Copy for SendMessageSuccessLazy<'a> { }
+impl<'a> Copy for SearchMessagesSuccessLazy<'a> { }
 
-impl<'a> Clone for SendMessageSuccessLazy<'a> {
+impl<'a> Clone for SearchMessagesSuccessLazy<'a> {
     fn clone(&self) -> Self {
         Self {
             buffer: self.buffer,
@@ -879,181 +2798,208 @@ impl<'a> Clone for SendMessageSuccessLazy<'a> {
     }
 }
 
-impl<'a> core::fmt::Debug for SendMessageSuccessLazy<'a> {
+impl<'a> core::fmt::Debug for SearchMessagesSuccessLazy<'a> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.debug_struct("SendMessageSuccessLazy")
+        f.debug_struct("SearchMessagesSuccessLazy")
             .finish()
     }
 }
 
-impl<'a> PartialEq for SendMessageSuccessLazy<'a> {
-    fn eq(&self, _: &Self) -> bool {
-        true
+impl<'a> PartialEq for SearchMessagesSuccessLazy<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.results().unwrap() == other.results().unwrap()
+            && self.next_before_id().unwrap() == other.next_before_id().unwrap()
     }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
-pub enum SendMessageError {
+pub enum SearchMessagesError {
     Internal,
     NotRegistered,
-    MessageTooLong,
+    EmptyQuery,
+    RateLimited { retry_after_ms: u32 },
 }
 
 #[derive(Clone)]
-pub enum SendMessageErrorLazy {
+pub enum SearchMessagesErrorLazy {
     Internal,
     NotRegistered,
-    MessageTooLong,
+    EmptyQuery,
+    RateLimited { retry_after_ms: u32 },
 }
 
-impl Compatible<SendMessageErrorLazy> for SendMessageErrorLazy { }
-impl Compatible<SendMessageErrorLazy> for SendMessageError { }
-impl Compatible<SendMessageError> for SendMessageErrorLazy { }
-impl Compatible<SendMessageError> for SendMessageError { }
+impl Compatible<SearchMessagesErrorLazy> for SearchMessagesErrorLazy { }
+impl Compatible<SearchMessagesErrorLazy> for SearchMessagesError { }
+impl Compatible<SearchMessagesError> for SearchMessagesErrorLazy { }
+impl Compatible<SearchMessagesError> for SearchMessagesError { }
 
-impl Owned for SendMessageError {
-    type Lazy<'a> = SendMessageErrorLazy;
+impl Owned for SearchMessagesError {
+    type Lazy<'a> = SearchMessagesErrorLazy;
 
     fn lazy_to_owned(lazy: Self::Lazy<'_>) -> DecodeResult<Self> {
         TryFrom::try_from(lazy)
     }
 }
 
-impl<'a> Lazy<'a> for SendMessageErrorLazy {
-    type Owned = SendMessageError;
+impl<'a> Lazy<'a> for SearchMessagesErrorLazy {
+    type Owned = SearchMessagesError;
 }
 
-impl BaseLen for SendMessageError {
-    const BASE_LEN: usize = 1 + max(max(max(0, 0), 0), 0);
+impl BaseLen for SearchMessagesError {
+    const BASE_LEN: usize = 1 + max(max(max(max(0, 0), 0), 0), 4);
 }
 
-impl Encode for SendMessageError {
+impl Encode for SearchMessagesError {
     fn scratch_len(&self) -> usize {
         match self {
-            SendMessageError::Internal => 0,
-            SendMessageError::NotRegistered => 0,
-            SendMessageError::MessageTooLong => 0,
+            SearchMessagesError::Internal => 0,
+            SearchMessagesError::NotRegistered => 0,
+            SearchMessagesError::EmptyQuery => 0,
+            SearchMessagesError::RateLimited { retry_after_ms } => retry_after_ms.scratch_len(),
         }
     }
 
     fn encode(&self, cursor: &mut EncodeCursor) {
         match self {
-            SendMessageError::Internal => {
+            SearchMessagesError::Internal => {
                 cursor.base(1)[0] = 0;
                 cursor.base(Self::BASE_LEN - 1).fill(0);
             }
-            SendMessageError::NotRegistered => {
+            SearchMessagesError::NotRegistered => {
                 cursor.base(1)[0] = 1;
                 cursor.base(Self::BASE_LEN - 1).fill(0);
             }
-            SendMessageError::MessageTooLong => {
+            SearchMessagesError::EmptyQuery => {
                 cursor.base(1)[0] = 2;
                 cursor.base(Self::BASE_LEN - 1).fill(0);
             }
+            SearchMessagesError::RateLimited { retry_after_ms } => {
+                cursor.base(1)[0] = 3;
+                retry_after_ms.encode(cursor);
+                cursor.base(Self::BASE_LEN - 1 - 4).fill(0);
+            }
         }
     }
 }
 
-impl<'a> Decode<'a> for SendMessageError {
+impl<'a> Decode<'a> for SearchMessagesError {
     fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
         let variant = cursor.base(1)[0];
         match variant {
             0 => {
                 cursor.advance(Self::BASE_LEN - 1);
-                Ok(SendMessageError::Internal)
+                Ok(SearchMessagesError::Internal)
             }
             1 => {
                 cursor.advance(Self::BASE_LEN - 1);
-                Ok(SendMessageError::NotRegistered)
+                Ok(SearchMessagesError::NotRegistered)
             }
             2 => {
                 cursor.advance(Self::BASE_LEN - 1);
-                Ok(SendMessageError::MessageTooLong)
+                Ok(SearchMessagesError::EmptyQuery)
+            }
+            3 => {
+                let retry_after_ms = Decode::decode(cursor)?;
+                cursor.advance(Self::BASE_LEN - 1 - 4);
+                Ok(SearchMessagesError::RateLimited { retry_after_ms })
             }
             _ => { Err(DecodeError) }
         }
     }
 }
 
-impl BaseLen for SendMessageErrorLazy {
-    const BASE_LEN: usize = 1 + max(max(max(0, 0), 0), 0);
+impl BaseLen for SearchMessagesErrorLazy {
+    const BASE_LEN: usize = 1 + max(max(max(max(0, 0), 0), 0), 4);
 }
 
-impl Encode for SendMessageErrorLazy {
+impl Encode for SearchMessagesErrorLazy {
     fn scratch_len(&self) -> usize {
         match self {
-            SendMessageErrorLazy::Internal => 0,
-            SendMessageErrorLazy::NotRegistered => 0,
-            SendMessageErrorLazy::MessageTooLong => 0,
+            SearchMessagesErrorLazy::Internal => 0,
+            SearchMessagesErrorLazy::NotRegistered => 0,
+            SearchMessagesErrorLazy::EmptyQuery => 0,
+            SearchMessagesErrorLazy::RateLimited { retry_after_ms } => retry_after_ms.scratch_len(),
         }
     }
 
     fn encode(&self, cursor: &mut EncodeCursor) {
         match self {
-            SendMessageErrorLazy::Internal => {
+            SearchMessagesErrorLazy::Internal => {
                 cursor.base(1)[0] = 0;
                 cursor.base(Self::BASE_LEN - 1).fill(0);
             }
-            SendMessageErrorLazy::NotRegistered => {
+            SearchMessagesErrorLazy::NotRegistered => {
                 cursor.base(1)[0] = 1;
                 cursor.base(Self::BASE_LEN - 1).fill(0);
             }
-            SendMessageErrorLazy::MessageTooLong => {
+            SearchMessagesErrorLazy::EmptyQuery => {
                 cursor.base(1)[0] = 2;
                 cursor.base(Self::BASE_LEN - 1).fill(0);
             }
+            SearchMessagesErrorLazy::RateLimited { retry_after_ms } => {
+                cursor.base(1)[0] = 3;
+                retry_after_ms.encode(cursor);
+                cursor.base(Self::BASE_LEN - 1 - 4).fill(0);
+            }
         }
     }
 }
 
-impl<'a> Decode<'a> for SendMessageErrorLazy {
+impl<'a> Decode<'a> for SearchMessagesErrorLazy {
     fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
         let variant = cursor.base(1)[0];
         match variant {
             0 => {
                 cursor.advance(Self::BASE_LEN - 1);
-                Ok(SendMessageErrorLazy::Internal)
+                Ok(SearchMessagesErrorLazy::Internal)
             }
             1 => {
                 cursor.advance(Self::BASE_LEN - 1);
-                Ok(SendMessageErrorLazy::NotRegistered)
+                Ok(SearchMessagesErrorLazy::NotRegistered)
             }
             2 => {
                 cursor.advance(Self::BASE_LEN - 1);
-                Ok(SendMessageErrorLazy::MessageTooLong)
+                Ok(SearchMessagesErrorLazy::EmptyQuery)
+            }
+            3 => {
+                let retry_after_ms = Decode::decode(cursor)?;
+                cursor.advance(Self::BASE_LEN - 1 - 4);
+                Ok(SearchMessagesErrorLazy::RateLimited { retry_after_ms })
             }
             _ => { Err(DecodeError) }
         }
     }
 }
 
-impl TryFrom<SendMessageErrorLazy> for SendMessageError {
+impl TryFrom<SearchMessagesErrorLazy> for SearchMessagesError {
     type Error = DecodeError;
 
-    fn try_from(other: SendMessageErrorLazy) -> Result<Self, Self::Error> {
+    fn try_from(other: SearchMessagesErrorLazy) -> Result<Self, Self::Error> {
         match other {
-            SendMessageErrorLazy::Internal => Ok(SendMessageError::Internal),
-            SendMessageErrorLazy::NotRegistered => Ok(SendMessageError::NotRegistered),
-            SendMessageErrorLazy::MessageTooLong => Ok(SendMessageError::MessageTooLong),
+            SearchMessagesErrorLazy::Internal => Ok(SearchMessagesError::Internal),
+            SearchMessagesErrorLazy::NotRegistered => Ok(SearchMessagesError::NotRegistered),
+            SearchMessagesErrorLazy::EmptyQuery => Ok(SearchMessagesError::EmptyQuery),
+            SearchMessagesErrorLazy::RateLimited { retry_after_ms } => Ok(SearchMessagesError::RateLimited { retry_after_ms }),
         }
     }
 }
 
-impl Copy for SendMessageErrorLazy { }
+impl Copy for SearchMessagesErrorLazy { }
 
-impl core::fmt::Debug for SendMessageErrorLazy {
+impl core::fmt::Debug for SearchMessagesErrorLazy {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.debug_struct("SendMessageErrorLazy")
+        f.debug_struct("SearchMessagesErrorLazy")
             .finish()
     }
 }
 
-impl PartialEq for SendMessageErrorLazy {
+impl PartialEq for SearchMessagesErrorLazy {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (SendMessageErrorLazy::Internal, SendMessageErrorLazy::Internal) => true,
-            (SendMessageErrorLazy::NotRegistered, SendMessageErrorLazy::NotRegistered) => true,
-            (SendMessageErrorLazy::MessageTooLong, SendMessageErrorLazy::MessageTooLong) => true,
+            (SearchMessagesErrorLazy::Internal, SearchMessagesErrorLazy::Internal) => true,
+            (SearchMessagesErrorLazy::NotRegistered, SearchMessagesErrorLazy::NotRegistered) => true,
+            (SearchMessagesErrorLazy::EmptyQuery, SearchMessagesErrorLazy::EmptyQuery) => true,
+            (SearchMessagesErrorLazy::RateLimited { retry_after_ms: a_retry_after_ms }, SearchMessagesErrorLazy::RateLimited { retry_after_ms: b_retry_after_ms }) => a_retry_after_ms == b_retry_after_ms,
             #[allow(unreachable_patterns)]
             _ => false,
         }
@@ -1064,6 +3010,7 @@ impl PartialEq for SendMessageErrorLazy {
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
 pub struct ChatInitState {
     pub users: Vec<RegisteredUser>,
+    pub server: ServerInfo,
 }
 
 pub struct ChatInitStateLazy<'a> {
@@ -1073,32 +3020,39 @@ pub struct ChatInitStateLazy<'a> {
 
 pub struct ChatInitStateGen<
     Users: Encode + Compatible<Vec<RegisteredUser>>,
+    Server: Encode + Compatible<ServerInfo>,
 > {
     pub users: Users,
+    pub server: Server,
 }
 
 impl<
-    Users: Encode + Compatible<Vec<RegisteredUser>>
-> Compatible<ChatInitState> for ChatInitStateGen<Users> { }
+    Users: Encode + Compatible<Vec<RegisteredUser>>,
+    Server: Encode + Compatible<ServerInfo>
+> Compatible<ChatInitState> for ChatInitStateGen<Users, Server> { }
 impl<
-    Users: Encode + Compatible<Vec<RegisteredUser>>
-> Compatible<ChatInitStateGen<Users>> for ChatInitState { }
+    Users: Encode + Compatible<Vec<RegisteredUser>>,
+    Server: Encode + Compatible<ServerInfo>
+> Compatible<ChatInitStateGen<Users, Server>> for ChatInitState { }
 
 impl<
     Users: Encode + Compatible<Vec<RegisteredUser>>,
-> BaseLen for ChatInitStateGen<Users> {
-    const BASE_LEN: usize = Users::BASE_LEN;
+    Server: Encode + Compatible<ServerInfo>,
+> BaseLen for ChatInitStateGen<Users, Server> {
+    const BASE_LEN: usize = Users::BASE_LEN + Server::BASE_LEN;
 }
 
 impl<
     Users: Encode + Compatible<Vec<RegisteredUser>>,
-> Encode for ChatInitStateGen<Users> {
+    Server: Encode + Compatible<ServerInfo>,
+> Encode for ChatInitStateGen<Users, Server> {
     fn scratch_len(&self) -> usize {
-        self.users.scratch_len()
+        self.users.scratch_len() + self.server.scratch_len()
     }
 
     fn encode(&self, cursor: &mut EncodeCursor) {
         self.users.encode(cursor);
+        self.server.encode(cursor);
     }
 }
 
@@ -1128,20 +3082,25 @@ impl<'a> ChatInitStateLazy<'a> {
     pub fn users(&self) -> DecodeResult<mproto::ListLazy<'a, RegisteredUser>> {
         Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0))
     }
+
+    pub fn server(&self) -> DecodeResult<ServerInfoLazy<'a>> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8))
+    }
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
 impl BaseLen for ChatInitState {
-    const BASE_LEN: usize = 8;
+    const BASE_LEN: usize = 52;
 }
 
 impl Encode for ChatInitState {
     fn scratch_len(&self) -> usize {
-        self.users.scratch_len()
+        self.users.scratch_len() + self.server.scratch_len()
     }
 
     fn encode(&self, cursor: &mut EncodeCursor) {
         self.users.encode(cursor);
+        self.server.encode(cursor);
     }
 }
 
@@ -1149,26 +3108,31 @@ impl Encode for ChatInitState {
 impl<'a> Decode<'a> for ChatInitState {
     fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
         let users = Decode::decode(cursor)?;
+        let server = Decode::decode(cursor)?;
 
         Ok(ChatInitState {
             users,
+            server,
         })
     }
 }
 
 impl<'a> BaseLen for ChatInitStateLazy<'a> {
-    const BASE_LEN: usize = 8;
+    const BASE_LEN: usize = 52;
 }
 
 impl<'a> Encode for ChatInitStateLazy<'a> {
     fn scratch_len(&self) -> usize {
         let users: mproto::ListLazy<'a, RegisteredUser> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
-        users.scratch_len()
+        let server: ServerInfoLazy<'a> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
+        users.scratch_len() + server.scratch_len()
     }
 
     fn encode(&self, cursor: &mut EncodeCursor) {
         let users: mproto::ListLazy<'a, RegisteredUser> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let server: ServerInfoLazy<'a> = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
         users.encode(cursor);
+        server.encode(cursor);
     }
 }
 
@@ -1214,6 +3178,7 @@ impl<'a> core::fmt::Debug for ChatInitStateLazy<'a> {
 impl<'a> PartialEq for ChatInitStateLazy<'a> {
     fn eq(&self, other: &Self) -> bool {
         self.users().unwrap() == other.users().unwrap()
+            && self.server().unwrap() == other.server().unwrap()
     }
 }
 
@@ -1336,29 +3301,38 @@ impl<'a> PartialEq for ChatClientConfigLazy<'a> {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash, Default)]
-pub struct ChatServerConfig {}
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub struct ChatServerConfig {
+    pub max_users: u64,
+    pub max_message_len: u64,
+}
 
 pub struct ChatServerConfigLazy<'a> {
     buffer: &'a [u8],
     offset: usize,
 }
 
-pub struct ChatServerConfigGen<> {}
+pub struct ChatServerConfigGen {
+    pub max_users: u64,
+    pub max_message_len: u64,
+}
 
-impl<> Compatible<ChatServerConfig> for ChatServerConfigGen<> { }
-impl<> Compatible<ChatServerConfigGen<>> for ChatServerConfig { }
+impl Compatible<ChatServerConfig> for ChatServerConfigGen { }
+impl Compatible<ChatServerConfigGen> for ChatServerConfig { }
 
-impl<> BaseLen for ChatServerConfigGen<> {
-    const BASE_LEN: usize = 0;
+impl BaseLen for ChatServerConfigGen {
+    const BASE_LEN: usize = 8 + 8;
 }
 
-impl<> Encode for ChatServerConfigGen<> {
+impl Encode for ChatServerConfigGen {
     fn scratch_len(&self) -> usize {
-        0
+        self.max_users.scratch_len() + self.max_message_len.scratch_len()
     }
 
-    fn encode(&self, _: &mut EncodeCursor) {}
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        self.max_users.encode(cursor);
+        self.max_message_len.encode(cursor);
+    }
 }
 
 impl Owned for ChatServerConfig {
@@ -1378,37 +3352,61 @@ impl<'a> Compatible<ChatServerConfigLazy<'a>> for ChatServerConfig { }
 impl Compatible<ChatServerConfig> for ChatServerConfig { }
 impl<'a> Compatible<ChatServerConfig> for ChatServerConfigLazy<'a> { }
 
-impl<'a> ChatServerConfigLazy<'a> {}
+impl<'a> ChatServerConfigLazy<'a> {
+
+    pub fn max_users(&self) -> DecodeResult<u64> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0))
+    }
+
+    pub fn max_message_len(&self) -> DecodeResult<u64> {
+        Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8))
+    }
+}
 
 impl BaseLen for ChatServerConfig {
-    const BASE_LEN: usize = 0;
+    const BASE_LEN: usize = 16;
 }
 
 impl Encode for ChatServerConfig {
     fn scratch_len(&self) -> usize {
-        0
+        self.max_users.scratch_len() + self.max_message_len.scratch_len()
     }
 
-    fn encode(&self, _: &mut EncodeCursor) {}
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        self.max_users.encode(cursor);
+        self.max_message_len.encode(cursor);
+    }
 }
 
 impl<'a> Decode<'a> for ChatServerConfig {
-    fn decode(_: &DecodeCursor<'a>) -> DecodeResult<Self> {
+    fn decode(cursor: &DecodeCursor<'a>) -> DecodeResult<Self> {
+        let max_users = Decode::decode(cursor)?;
+        let max_message_len = Decode::decode(cursor)?;
 
-        Ok(ChatServerConfig {})
+        Ok(ChatServerConfig {
+            max_users,
+            max_message_len,
+        })
     }
 }
 
 impl<'a> BaseLen for ChatServerConfigLazy<'a> {
-    const BASE_LEN: usize = 0;
+    const BASE_LEN: usize = 16;
 }
 
 impl<'a> Encode for ChatServerConfigLazy<'a> {
     fn scratch_len(&self) -> usize {
-        0
+        let max_users: u64 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let max_message_len: u64 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
+        max_users.scratch_len() + max_message_len.scratch_len()
     }
 
-    fn encode(&self, _: &mut EncodeCursor) {}
+    fn encode(&self, cursor: &mut EncodeCursor) {
+        let max_users: u64 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 0)).unwrap();
+        let max_message_len: u64 = Decode::decode(&DecodeCursor::at_offset(self.buffer, self.offset + 8)).unwrap();
+        max_users.encode(cursor);
+        max_message_len.encode(cursor);
+    }
 }
 
 impl<'a> Decode<'a> for ChatServerConfigLazy<'a> {
@@ -1450,7 +3448,8 @@ impl<'a> core::fmt::Debug for ChatServerConfigLazy<'a> {
 }
 
 impl<'a> PartialEq for ChatServerConfigLazy<'a> {
-    fn eq(&self, _: &Self) -> bool {
-        true
+    fn eq(&self, other: &Self) -> bool {
+        self.max_users().unwrap() == other.max_users().unwrap()
+            && self.max_message_len().unwrap() == other.max_message_len().unwrap()
     }
 }
@@ -1,13 +1,16 @@
 #![allow(unused_variables)]
 
 use crate::interface::ChatInterface;
-use crate::proto::{ChatClientConfig, ChatInitState, RegisterError, RegisterRequest, RegisterSuccess, SendMessageError, SendMessageRequest, SendMessageSuccess};
+use crate::proto::{ChatClientConfig, ChatInitState, RegisterError, RegisterRequest, RegisterSuccess, RegisteredUser, ResumeSessionError, ResumeSessionRequest, ResumeSessionSuccess, SearchMessagesError, SearchMessagesRequest, SearchMessagesSuccess, SendMessageError, SendMessageRequest, SendMessageSuccess};
 use modrpc::{InterfaceRole, RoleSetup};
-use std_modrpc::{RequestClient, RequestClientBuilder, RequestClientConfig, RequestClientRole, RequestInitState};
+use std_modrpc::{RequestClient, RequestClientBuilder, RequestClientConfig, RequestClientRole, RequestInitState, StreamInitState, StreamReceiver, StreamReceiverBuilder, StreamReceiverConfig, StreamReceiverRole};
 
 pub struct ChatClientHooks {
     pub register: RequestClient<RegisterRequest, Result<RegisterSuccess, RegisterError>>,
     pub send_message: RequestClient<SendMessageRequest, Result<SendMessageSuccess, SendMessageError>>,
+    pub resume_session: RequestClient<ResumeSessionRequest, Result<ResumeSessionSuccess, ResumeSessionError>>,
+    pub search_messages: RequestClient<SearchMessagesRequest, Result<SearchMessagesSuccess, SearchMessagesError>>,
+    pub user_left: StreamReceiver<RegisteredUser>,
 }
 
 pub struct ChatClientStubs {}
@@ -57,12 +60,60 @@ impl InterfaceRole for ChatClientRole {
         let send_message = send_message_builder.create_handle(setup);
         send_message_builder.build(setup);
         setup.pop_object_path();
+        setup.push_object_path("resume_session");
+        let (resume_session_stubs, resume_session_hooks) =
+            RequestClientRole::setup_worker(
+                &i.resume_session, setup, &RequestClientConfig { }, &RequestInitState { },
+            );
+        let resume_session_builder = RequestClientBuilder::new(
+            "chat_client.resume_session",
+            resume_session_hooks,
+            resume_session_stubs,
+            &RequestClientConfig { },
+            RequestInitState { }.clone(),
+        );
+        let resume_session = resume_session_builder.create_handle(setup);
+        resume_session_builder.build(setup);
+        setup.pop_object_path();
+        setup.push_object_path("search_messages");
+        let (search_messages_stubs, search_messages_hooks) =
+            RequestClientRole::setup_worker(
+                &i.search_messages, setup, &RequestClientConfig { }, &RequestInitState { },
+            );
+        let search_messages_builder = RequestClientBuilder::new(
+            "chat_client.search_messages",
+            search_messages_hooks,
+            search_messages_stubs,
+            &RequestClientConfig { },
+            RequestInitState { }.clone(),
+        );
+        let search_messages = search_messages_builder.create_handle(setup);
+        search_messages_builder.build(setup);
+        setup.pop_object_path();
+        setup.push_object_path("user_left");
+        let (user_left_stubs, user_left_hooks) =
+            StreamReceiverRole::setup_worker(
+                &i.user_left, setup, &StreamReceiverConfig { }, &StreamInitState { },
+            );
+        let user_left_builder = StreamReceiverBuilder::new(
+            "chat_client.user_left",
+            user_left_hooks,
+            user_left_stubs,
+            &StreamReceiverConfig { },
+            StreamInitState { }.clone(),
+        );
+        let user_left = user_left_builder.create_handle(setup);
+        user_left_builder.build(setup);
+        setup.pop_object_path();
 
         (
             Self::Stubs {},
             Self::Hooks {
                 register,
                 send_message,
+                resume_session,
+                search_messages,
+                user_left,
             },
         )
     }
@@ -73,6 +124,9 @@ impl Clone for ChatClientHooks {
         Self {
             register: self.register.clone(),
             send_message: self.send_message.clone(),
+            resume_session: self.resume_session.clone(),
+            search_messages: self.search_messages.clone(),
+            user_left: self.user_left.clone(),
         }
     }
 }
@@ -1,18 +1,23 @@
 #![allow(unused_variables)]
 
 use crate::interface::ChatInterface;
-use crate::proto::{ChatInitState, ChatServerConfig, RegisterError, RegisterRequest, RegisterSuccess, SendMessageError, SendMessageRequest, SendMessageSuccess};
+use crate::proto::{ChatInitState, ChatServerConfig, RegisterError, RegisterRequest, RegisterSuccess, RegisteredUser, ResumeSessionError, ResumeSessionRequest, ResumeSessionSuccess, SearchMessagesError, SearchMessagesRequest, SearchMessagesSuccess, SendMessageError, SendMessageRequest, SendMessageSuccess};
 use modrpc::{InterfaceRole, RoleSetup};
-use std_modrpc::{RequestInitState, RequestServer, RequestServerBuilder, RequestServerConfig, RequestServerRole};
+use std_modrpc::{RequestInitState, RequestServer, RequestServerBuilder, RequestServerConfig, RequestServerRole, StreamInitState, StreamSender, StreamSenderBuilder, StreamSenderConfig, StreamSenderRole};
 
 pub struct ChatServerHooks {
     pub register: RequestServer<RegisterRequest, Result<RegisterSuccess, RegisterError>>,
     pub send_message: RequestServer<SendMessageRequest, Result<SendMessageSuccess, SendMessageError>>,
+    pub resume_session: RequestServer<ResumeSessionRequest, Result<ResumeSessionSuccess, ResumeSessionError>>,
+    pub search_messages: RequestServer<SearchMessagesRequest, Result<SearchMessagesSuccess, SearchMessagesError>>,
+    pub user_left: StreamSender<RegisteredUser>,
 }
 
 pub struct ChatServerStubs {
     pub register: RequestServerBuilder<RegisterRequest, Result<RegisterSuccess, RegisterError>>,
     pub send_message: RequestServerBuilder<SendMessageRequest, Result<SendMessageSuccess, SendMessageError>>,
+    pub resume_session: RequestServerBuilder<ResumeSessionRequest, Result<ResumeSessionSuccess, ResumeSessionError>>,
+    pub search_messages: RequestServerBuilder<SearchMessagesRequest, Result<SearchMessagesSuccess, SearchMessagesError>>,
 }
 
 pub struct ChatServerRole {}
@@ -58,15 +63,63 @@ impl InterfaceRole for ChatServerRole {
         );
         let send_message = send_message_builder.create_handle(setup);
         setup.pop_object_path();
+        setup.push_object_path("resume_session");
+        let (resume_session_stubs, resume_session_hooks) =
+            RequestServerRole::setup_worker(
+                &i.resume_session, setup, &RequestServerConfig { }, &RequestInitState { },
+            );
+        let resume_session_builder = RequestServerBuilder::new(
+            "chat_server.resume_session",
+            resume_session_hooks,
+            resume_session_stubs,
+            &RequestServerConfig { },
+            RequestInitState { }.clone(),
+        );
+        let resume_session = resume_session_builder.create_handle(setup);
+        setup.pop_object_path();
+        setup.push_object_path("search_messages");
+        let (search_messages_stubs, search_messages_hooks) =
+            RequestServerRole::setup_worker(
+                &i.search_messages, setup, &RequestServerConfig { }, &RequestInitState { },
+            );
+        let search_messages_builder = RequestServerBuilder::new(
+            "chat_server.search_messages",
+            search_messages_hooks,
+            search_messages_stubs,
+            &RequestServerConfig { },
+            RequestInitState { }.clone(),
+        );
+        let search_messages = search_messages_builder.create_handle(setup);
+        setup.pop_object_path();
+        setup.push_object_path("user_left");
+        let (user_left_stubs, user_left_hooks) =
+            StreamSenderRole::setup_worker(
+                &i.user_left, setup, &StreamSenderConfig { }, &StreamInitState { },
+            );
+        let user_left_builder = StreamSenderBuilder::new(
+            "chat_server.user_left",
+            user_left_hooks,
+            user_left_stubs,
+            &StreamSenderConfig { },
+            StreamInitState { }.clone(),
+        );
+        let user_left = user_left_builder.create_handle(setup);
+        user_left_builder.build(setup);
+        setup.pop_object_path();
 
         (
             Self::Stubs {
                 register: register_builder,
                 send_message: send_message_builder,
+                resume_session: resume_session_builder,
+                search_messages: search_messages_builder,
             },
             Self::Hooks {
                 register,
                 send_message,
+                resume_session,
+                search_messages,
+                user_left,
             },
         )
     }
@@ -77,6 +130,9 @@ impl Clone for ChatServerHooks {
         Self {
             register: self.register.clone(),
             send_message: self.send_message.clone(),
+            resume_session: self.resume_session.clone(),
+            search_messages: self.search_messages.clone(),
+            user_left: self.user_left.clone(),
         }
     }
 }
@@ -0,0 +1,93 @@
+//! Measures how contention on the shared `ChatServerState` limits throughput as threads are
+//! added.
+//!
+//! Each thread registers its own set of users and then sends messages as them, calling the same
+//! `ChatServerState` methods the repliers use. It leaves out the modrpc runtime, transports and
+//! encoding, so it shows how far the sharded state scales rather than what a running server
+//! handles. Run with `cargo bench`.
+
+use std::{
+    num::NonZeroUsize,
+    sync::{Arc, Barrier},
+    thread,
+    time::Instant,
+};
+
+use chat_server::{
+    config::{RateLimitFileConfig, RateLimitsConfig, ServerConfig},
+    state::ChatServerState,
+};
+
+const USERS_PER_WORKER: u64 = 64;
+const MESSAGES_PER_USER: u64 = 5_000;
+
+fn main() {
+    let max_workers = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    let mut worker_counts = vec![1];
+    while worker_counts.last().unwrap() * 2 <= max_workers {
+        worker_counts.push(worker_counts.last().unwrap() * 2);
+    }
+
+    println!("{:>8} {:>14} {:>8}", "workers", "messages/s", "speedup");
+    let mut baseline = None;
+    for workers in worker_counts {
+        let messages_per_sec = run(workers);
+        let baseline = *baseline.get_or_insert(messages_per_sec);
+        println!("{workers:>8} {messages_per_sec:>14.0} {:>7.2}x", messages_per_sec / baseline);
+    }
+}
+
+/// Returns the number of messages per second handled by `workers` threads.
+fn run(workers: usize) -> f64 {
+    let config = ServerConfig {
+        max_users: workers as u64 * USERS_PER_WORKER,
+        rate_limit: RateLimitsConfig {
+            // Measure the server state, not the rate limit policy.
+            send_message: RateLimitFileConfig {
+                burst: u32::MAX,
+                per_second: 1.0,
+                mute_after_violations: 0,
+                mute_duration_secs: 0,
+            },
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let state = Arc::new(ChatServerState::new(&config));
+
+    let start = Arc::new(Barrier::new(workers + 1));
+    let threads: Vec<_> = (0..workers as u64)
+        .map(|worker| {
+            let state = state.clone();
+            let start = start.clone();
+            thread::spawn(move || {
+                let endpoints: Vec<u64> = (0..USERS_PER_WORKER)
+                    .map(|i| worker * USERS_PER_WORKER + i)
+                    .collect();
+                for &endpoint in &endpoints {
+                    state.register(endpoint, &format!("user-{endpoint}"), None)
+                        .expect("register");
+                }
+
+                start.wait();
+                for i in 0..MESSAGES_PER_USER {
+                    // Keys only need to be unique per user.
+                    let idempotency_key = i.to_string();
+                    for &endpoint in &endpoints {
+                        state.send_message(endpoint, "hello, world", &idempotency_key)
+                            .expect("send_message");
+                    }
+                }
+            })
+        })
+        .collect();
+
+    start.wait();
+    let started_at = Instant::now();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    let elapsed = started_at.elapsed();
+
+    (workers as u64 * USERS_PER_WORKER * MESSAGES_PER_USER) as f64 / elapsed.as_secs_f64()
+}
@@ -0,0 +1,438 @@
+use std::{
+    net::SocketAddr,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use clap::Parser;
+use serde::Deserialize;
+
+use crate::rate_limit::{EscalationConfig, RateLimitConfig};
+
+/// Room kept in every buffer for the parts of a request or response other than the chat messages
+/// it carries, and for its framing.
+const PACKET_OVERHEAD: usize = 1024;
+
+/// modrpc chat server
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+    /// Path to a TOML configuration file. Command-line options override values from the file.
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+
+    /// Name of this server, shown to clients when they connect
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Message of the day, shown to clients when they connect
+    #[arg(long)]
+    pub motd: Option<String>,
+
+    /// Address to accept modrpc-over-TCP connections on
+    #[arg(long)]
+    pub tcp_addr: Option<SocketAddr>,
+
+    /// Address to accept modrpc-over-WebSocket connections on
+    #[arg(long)]
+    pub websocket_addr: Option<SocketAddr>,
+
+    /// Number of worker threads handling requests. 0 uses one per CPU.
+    #[arg(long)]
+    pub workers: Option<usize>,
+
+    /// Maximum number of registered users
+    #[arg(long)]
+    pub max_users: Option<u64>,
+
+    /// Maximum length of a chat message in bytes
+    #[arg(long)]
+    pub max_message_len: Option<u64>,
+
+    /// Seconds a disconnected user's alias is held for them to resume their session
+    #[arg(long)]
+    pub session_grace_period_secs: Option<u64>,
+
+    /// Number of recent messages kept to replay to resumed sessions
+    #[arg(long)]
+    pub message_log_len: Option<usize>,
+
+    /// Number of recent messages kept searchable. 0 disables searching.
+    #[arg(long)]
+    pub search_index_len: Option<usize>,
+
+    /// Size in bytes of each transport buffer
+    #[arg(long)]
+    pub buffer_size: Option<usize>,
+
+    /// Number of transport buffers allocated up front
+    #[arg(long)]
+    pub min_buffers: Option<usize>,
+
+    /// Number of transport buffers kept around for reuse
+    #[arg(long)]
+    pub max_buffers: Option<usize>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub name: String,
+    pub motd: String,
+    pub tcp_addr: SocketAddr,
+    pub websocket_addr: SocketAddr,
+    /// Number of worker threads handling requests. 0 uses one per CPU.
+    pub workers: usize,
+    pub max_users: u64,
+    pub max_message_len: u64,
+    pub session_grace_period_secs: u64,
+    pub message_log_len: usize,
+    pub search_index_len: usize,
+    pub buffer_pool: BufferPoolConfig,
+    pub rate_limit: RateLimitsConfig,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct BufferPoolConfig {
+    pub buffer_size: usize,
+    pub min_buffers: usize,
+    pub max_buffers: usize,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitsConfig {
+    pub send_message: RateLimitFileConfig,
+    /// Applies to both `register` and `resume_session` requests. Tracked per connection, so it
+    /// starts over when a client reconnects.
+    pub register: RateLimitFileConfig,
+    pub search_messages: RateLimitFileConfig,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitFileConfig {
+    pub burst: u32,
+    pub per_second: f64,
+    /// Mute a client after this many consecutive rejected requests. 0 disables muting.
+    #[serde(default)]
+    pub mute_after_violations: u32,
+    #[serde(default)]
+    pub mute_duration_secs: u64,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            name: "modrpc chat".into(),
+            motd: String::new(),
+            tcp_addr: "0.0.0.0:9095".parse().unwrap(),
+            websocket_addr: "0.0.0.0:9096".parse().unwrap(),
+            workers: 0,
+            max_users: 1000,
+            max_message_len: 500,
+            session_grace_period_secs: 120,
+            message_log_len: 1000,
+            search_index_len: 100_000,
+            buffer_pool: BufferPoolConfig::default(),
+            rate_limit: RateLimitsConfig::default(),
+        }
+    }
+}
+
+impl Default for BufferPoolConfig {
+    fn default() -> Self {
+        Self {
+            buffer_size: 65536,
+            min_buffers: 4,
+            max_buffers: 4,
+        }
+    }
+}
+
+impl Default for RateLimitsConfig {
+    fn default() -> Self {
+        Self {
+            send_message: RateLimitFileConfig {
+                burst: 10,
+                per_second: 2.0,
+                mute_after_violations: 20,
+                mute_duration_secs: 60,
+            },
+            register: RateLimitFileConfig {
+                burst: 5,
+                per_second: 0.2,
+                mute_after_violations: 10,
+                mute_duration_secs: 300,
+            },
+            search_messages: RateLimitFileConfig {
+                burst: 5,
+                per_second: 0.5,
+                mute_after_violations: 0,
+                mute_duration_secs: 0,
+            },
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Load the config file named on the command line (if any), apply command-line overrides and
+    /// validate the result.
+    pub fn load(cli: &Cli) -> Result<Self, String> {
+        let mut config = match &cli.config {
+            Some(path) => Self::from_file(path)?,
+            None => Self::default(),
+        };
+
+        if let Some(name) = &cli.name {
+            config.name = name.clone();
+        }
+        if let Some(motd) = &cli.motd {
+            config.motd = motd.clone();
+        }
+        if let Some(tcp_addr) = cli.tcp_addr {
+            config.tcp_addr = tcp_addr;
+        }
+        if let Some(websocket_addr) = cli.websocket_addr {
+            config.websocket_addr = websocket_addr;
+        }
+        if let Some(workers) = cli.workers {
+            config.workers = workers;
+        }
+        if let Some(max_users) = cli.max_users {
+            config.max_users = max_users;
+        }
+        if let Some(max_message_len) = cli.max_message_len {
+            config.max_message_len = max_message_len;
+        }
+        if let Some(session_grace_period_secs) = cli.session_grace_period_secs {
+            config.session_grace_period_secs = session_grace_period_secs;
+        }
+        if let Some(message_log_len) = cli.message_log_len {
+            config.message_log_len = message_log_len;
+        }
+        if let Some(search_index_len) = cli.search_index_len {
+            config.search_index_len = search_index_len;
+        }
+        if let Some(buffer_size) = cli.buffer_size {
+            config.buffer_pool.buffer_size = buffer_size;
+        }
+        if let Some(min_buffers) = cli.min_buffers {
+            config.buffer_pool.min_buffers = min_buffers;
+        }
+        if let Some(max_buffers) = cli.max_buffers {
+            config.buffer_pool.max_buffers = max_buffers;
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn from_file(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read config file {}: {e}", path.display()))?;
+        toml::from_str(&contents)
+            .map_err(|e| format!("failed to parse config file {}: {e}", path.display()))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("name must not be empty".into());
+        }
+        if self.tcp_addr == self.websocket_addr {
+            return Err(format!(
+                "tcp_addr and websocket_addr must be different, both are {}", self.tcp_addr,
+            ));
+        }
+        if self.max_users == 0 {
+            return Err("max_users must be at least 1".into());
+        }
+        if self.max_message_len == 0 {
+            return Err("max_message_len must be at least 1".into());
+        }
+        if self.max_message_len as usize + PACKET_OVERHEAD > self.buffer_pool.buffer_size {
+            return Err(format!(
+                "buffer_pool.buffer_size ({}) must be at least max_message_len + {PACKET_OVERHEAD} \
+                 ({})",
+                self.buffer_pool.buffer_size, self.max_message_len as usize + PACKET_OVERHEAD,
+            ));
+        }
+        if self.buffer_pool.max_buffers == 0 {
+            return Err("buffer_pool.max_buffers must be at least 1".into());
+        }
+        if self.buffer_pool.min_buffers > self.buffer_pool.max_buffers {
+            return Err(format!(
+                "buffer_pool.min_buffers ({}) must not exceed buffer_pool.max_buffers ({})",
+                self.buffer_pool.min_buffers, self.buffer_pool.max_buffers,
+            ));
+        }
+        self.rate_limit.send_message.validate("rate_limit.send_message")?;
+        self.rate_limit.register.validate("rate_limit.register")?;
+        self.rate_limit.search_messages.validate("rate_limit.search_messages")?;
+        Ok(())
+    }
+
+    /// Most bytes of encoded messages that fit in a single response.
+    pub fn max_response_messages_len(&self) -> usize {
+        self.buffer_pool.buffer_size.saturating_sub(PACKET_OVERHEAD)
+    }
+
+    pub fn worker_count(&self) -> usize {
+        if self.workers > 0 {
+            return self.workers;
+        }
+        std::thread::available_parallelism().map_or(1, NonZeroUsize::get)
+    }
+
+    pub fn session_grace_period(&self) -> Duration {
+        Duration::from_secs(self.session_grace_period_secs)
+    }
+
+    pub fn server_info(&self) -> chat_modrpc::ServerInfo {
+        let mut capabilities = vec![
+            chat_common::capabilities::RESUME_SESSION.into(),
+            chat_common::capabilities::RATE_LIMIT.into(),
+            chat_common::capabilities::USER_LEFT.into(),
+            chat_common::capabilities::IDEMPOTENT_SEND.into(),
+        ];
+        if self.search_index_len > 0 {
+            capabilities.push(chat_common::capabilities::SEARCH_MESSAGES.into());
+        }
+        chat_modrpc::ServerInfo {
+            protocol_version: chat_common::PROTOCOL_VERSION,
+            name: self.name.clone(),
+            motd: self.motd.clone(),
+            max_message_len: self.max_message_len,
+            max_users: self.max_users,
+            capabilities,
+        }
+    }
+
+    pub fn chat_server_config(&self) -> chat_modrpc::ChatServerConfig {
+        chat_modrpc::ChatServerConfig {
+            max_users: self.max_users,
+            max_message_len: self.max_message_len,
+        }
+    }
+}
+
+impl RateLimitFileConfig {
+    fn validate(&self, name: &str) -> Result<(), String> {
+        if self.burst == 0 {
+            return Err(format!("{name}.burst must be at least 1"));
+        }
+        if !(self.per_second.is_finite() && self.per_second > 0.0) {
+            return Err(format!("{name}.per_second must be a positive number"));
+        }
+        if self.mute_after_violations > 0 && self.mute_duration_secs == 0 {
+            return Err(format!(
+                "{name}.mute_duration_secs must be at least 1 when mute_after_violations is set",
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn rate_limit_config(&self) -> RateLimitConfig {
+        RateLimitConfig {
+            burst: self.burst,
+            per_second: self.per_second,
+            escalation: (self.mute_after_violations > 0).then(|| EscalationConfig {
+                violations: self.mute_after_violations,
+                mute_duration: Duration::from_secs(self.mute_duration_secs),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(configure: impl FnOnce(&mut ServerConfig)) -> Result<(), String> {
+        let mut config = ServerConfig::default();
+        configure(&mut config);
+        config.validate()
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        assert_eq!(validate(|_| {}), Ok(()));
+    }
+
+    #[track_caller]
+    fn assert_rejected(configure: impl FnOnce(&mut ServerConfig), expected: &str) {
+        let error = validate(configure).unwrap_err();
+        assert!(error.starts_with(expected), "{error:?} should start with {expected:?}");
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        assert_rejected(|c| c.name = " ".into(), "name must not be empty");
+        assert_rejected(
+            |c| c.websocket_addr = c.tcp_addr,
+            "tcp_addr and websocket_addr must be different",
+        );
+        assert_rejected(|c| c.max_users = 0, "max_users must be at least 1");
+        assert_rejected(|c| c.max_message_len = 0, "max_message_len must be at least 1");
+        assert_rejected(
+            |c| c.buffer_pool.max_buffers = 0,
+            "buffer_pool.max_buffers must be at least 1",
+        );
+        assert_rejected(
+            |c| c.buffer_pool.min_buffers = 5,
+            "buffer_pool.min_buffers (5) must not exceed buffer_pool.max_buffers (4)",
+        );
+    }
+
+    #[test]
+    fn buffers_must_fit_the_longest_message() {
+        let fits = |c: &mut ServerConfig| {
+            c.max_message_len = 1000;
+            c.buffer_pool.buffer_size = 1000 + PACKET_OVERHEAD;
+        };
+        assert_eq!(validate(fits), Ok(()));
+
+        let error = validate(|c| {
+            fits(c);
+            c.max_message_len += 1;
+        })
+        .unwrap_err();
+        assert_eq!(
+            error,
+            format!(
+                "buffer_pool.buffer_size ({}) must be at least max_message_len + {PACKET_OVERHEAD} \
+                 ({})",
+                1000 + PACKET_OVERHEAD,
+                1001 + PACKET_OVERHEAD,
+            ),
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_rate_limits() {
+        let error = validate(|c| c.rate_limit.register.burst = 0).unwrap_err();
+        assert_eq!(error, "rate_limit.register.burst must be at least 1");
+
+        let error = validate(|c| c.rate_limit.send_message.per_second = f64::NAN).unwrap_err();
+        assert_eq!(error, "rate_limit.send_message.per_second must be a positive number");
+
+        let error = validate(|c| {
+            c.rate_limit.search_messages.mute_after_violations = 3;
+            c.rate_limit.search_messages.mute_duration_secs = 0;
+        })
+        .unwrap_err();
+        assert_eq!(
+            error,
+            "rate_limit.search_messages.mute_duration_secs must be at least 1 when \
+             mute_after_violations is set",
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_fields_in_the_file() {
+        assert!(toml::from_str::<ServerConfig>("max_users = 10").is_ok());
+        assert!(toml::from_str::<ServerConfig>("max_user = 10").is_err());
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::{HashMap, VecDeque, hash_map::Entry};
+
+/// Number of idempotency keys remembered per alias. Clients only retry messages they haven't
+/// seen a response for, which are always among their latest.
+const WINDOW_LEN: usize = 256;
+
+/// Idempotency keys of an alias's most recent messages, mapped to the IDs the messages were
+/// logged under.
+#[derive(Default)]
+pub struct RecentSends {
+    message_ids: HashMap<String, u64>,
+    // Keys oldest first, to know which to forget
+    keys: VecDeque<String>,
+}
+
+impl RecentSends {
+    /// ID of the message previously sent with `key`, if it's recent enough to be remembered.
+    pub fn get(&self, key: &str) -> Option<u64> {
+        self.message_ids.get(key).copied()
+    }
+
+    /// Remember that `key` was sent as `message_id`. A key that's already remembered keeps its
+    /// original message ID.
+    pub fn insert(&mut self, key: &str, message_id: u64) {
+        let Entry::Vacant(entry) = self.message_ids.entry(key.to_owned()) else {
+            return;
+        };
+        entry.insert(message_id);
+        self.keys.push_back(key.to_owned());
+        if self.keys.len() > WINDOW_LEN
+            && let Some(oldest) = self.keys.pop_front()
+        {
+            self.message_ids.remove(&oldest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remembers_the_first_message_id_for_a_key() {
+        let mut sends = RecentSends::default();
+        assert_eq!(sends.get("a"), None);
+
+        sends.insert("a", 1);
+        sends.insert("b", 2);
+        sends.insert("a", 3);
+        assert_eq!(sends.get("a"), Some(1));
+        assert_eq!(sends.get("b"), Some(2));
+    }
+
+    #[test]
+    fn forgets_the_oldest_keys_past_the_window() {
+        let mut sends = RecentSends::default();
+        for i in 0..=WINDOW_LEN as u64 {
+            sends.insert(&i.to_string(), i);
+        }
+        assert_eq!(sends.get("0"), None);
+        assert_eq!(sends.get("1"), Some(1));
+        assert_eq!(sends.get(&WINDOW_LEN.to_string()), Some(WINDOW_LEN as u64));
+    }
+}
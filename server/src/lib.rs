@@ -0,0 +1,8 @@
+pub mod config;
+pub mod rate_limit;
+pub mod search;
+pub mod state;
+
+mod idempotency;
+mod message_log;
+mod sharded;
@@ -1,93 +1,111 @@
-use std::{
-    rc::Rc,
-    cell::RefCell,
-    collections::HashMap,
-};
+use std::{cell::OnceCell, rc::Rc, sync::Arc};
 
+use clap::Parser;
 use modrpc_executor::ModrpcExecutor;
 
-const MAX_USERS: usize = 1000;
-const MAX_MESSAGE_LEN: usize = 500;
+use chat_server::{
+    config::{Cli, ServerConfig},
+    search::SearchFilter,
+    state::ChatServerState,
+};
 
 struct ChatHubDelegate {
-    state: Rc<ChatServerState>,
+    state: Arc<ChatServerState>,
+    // Used to tell clients about disconnected users. The hub calls this delegate on the local
+    // worker, so it uses the sender of the role started there. Sending through one sender keeps
+    // the stream's sequence numbers in order.
+    user_left: Rc<OnceCell<std_modrpc::StreamSender<chat_modrpc::RegisteredUser>>>,
 }
 
 impl modrpc_hub::AppHubDelegate for ChatHubDelegate {
-    // :( one day maybe
-    // type Init<'a> = impl mproto::Compatible<chat_modrpc::ChatInitState> + 'a;
-    type Init<'a> = chat_modrpc::ChatInitStateGen<
-        mproto::ListGen<std::collections::hash_map::Values<'a, u64, chat_modrpc::RegisteredUser>>,
-    >;
+    type Init<'a> = chat_modrpc::ChatInitState;
 
     async fn client_handshake(
         &self,
         _: modrpc::EndpointAddr,
         handshake_fn: impl for<'a> AsyncFnOnce(Self::Init<'a>) -> std::io::Result<()>,
     ) -> std::io::Result<()> {
-        let registered_users = self.state.registered_users.borrow();
-        let init_state: Self::Init<'_> = chat_modrpc::ChatInitStateGen {
-            users: mproto::ListGen(registered_users.values()),
+        // The user directory is sharded across workers, so send a snapshot of it.
+        let init_state = chat_modrpc::ChatInitState {
+            users: self.state.registered_users(),
+            server: self.state.server_info().clone(),
         };
         handshake_fn(init_state).await
     }
 
     async fn client_disconnected(&self, endpoint_addr: modrpc::EndpointAddr) {
-        if let Some(user) =
-            self.state.registered_users.borrow_mut().remove(&endpoint_addr.endpoint)
-        {
-            log::info!("User disconnected: [endpoint={}] {}", endpoint_addr.endpoint, user.alias);
-            self.state.registered_aliases.borrow_mut().remove(&user.alias);
-        } else {
-            log::info!("Unregistered client disconnected: [endpoint={}]", endpoint_addr.endpoint);
+        let Some(user) = self.state.client_disconnected(endpoint_addr.endpoint) else {
+            return;
+        };
+        if let Some(user_left) = self.user_left.get() {
+            user_left.send(user).await;
         }
     }
 }
 
-struct ChatServerState {
-    // Map endpoint ID to its registered user object
-    registered_users: RefCell<HashMap<u64, chat_modrpc::RegisteredUser>>,
-    // Map user alias to registered endpoint ID
-    registered_aliases: RefCell<HashMap<String, u64>>,
-}
-
 fn main() {
     env_logger::init();
 
-    let state = Rc::new(ChatServerState {
-        registered_users: RefCell::new(HashMap::new()),
-        registered_aliases: RefCell::new(HashMap::new()),
-    });
+    let cli = Cli::parse();
+    let config = match ServerConfig::load(&cli) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let state = Arc::new(ChatServerState::new(&config));
+    let user_left = Rc::new(OnceCell::new());
 
     let mut ex = modrpc_executor::TokioExecutor::new();
     let _guard = ex.tokio_runtime().enter();
 
-    let buffer_pool = modrpc::HeapBufferPool::new(65536, 4, 4);
-    let (rt, rt_shutdown) = modrpc::RuntimeHandle::single_threaded(&mut ex);
+    let buffer_pool = modrpc::HeapBufferPool::new(
+        config.buffer_pool.buffer_size,
+        config.buffer_pool.min_buffers,
+        config.buffer_pool.max_buffers,
+    );
+    // This thread is a worker too, so the group gets one thread less than the configured count.
+    let mut rt_builder = modrpc::RuntimeBuilder::new_with_local(ex.spawner());
+    let extra_workers = u16::try_from(config.worker_count() - 1).unwrap_or(u16::MAX);
+    let workers = rt_builder.new_worker_group(extra_workers);
+    let (rt, rt_shutdown) = rt_builder.start::<modrpc_executor::TokioExecutor>();
 
     ex.run_until(async move {
         let start_role = modrpc_hub::AppHubBuilder::new(
             buffer_pool.clone(),
             rt.clone(),
         )
-        .with_tcp("0.0.0.0:9095".parse().unwrap())
-        .with_websocket("0.0.0.0:9096".parse().unwrap())
+        .with_tcp(config.tcp_addr)
+        .with_websocket(config.websocket_addr)
         .build::<chat_modrpc::ChatServerRole, _>(
             ChatHubDelegate {
                 state: state.clone(),
+                user_left: user_left.clone(),
             },
-            chat_modrpc::ChatServerConfig { },
+            config.chat_server_config(),
             chat_modrpc::ChatInitState {
                 users: vec![],
+                server: config.server_info(),
             },
         )
         .await;
 
         let shutdown_signal = start_role.role_shutdown_signal.clone();
-        let _chat_server = start_role.local({
-            let state = state.clone();
-            move |cx| build_chat_server(cx, state.clone())
-        });
+        // Every worker gets its own repliers, all sharing the same state. Requests are balanced
+        // across the workers running the role.
+        let chat_server = start_role
+            .on_worker_group(workers, {
+                let state = state.clone();
+                move |cx| build_chat_server(cx, state)
+            })
+            .await
+            .local({
+                let state = state.clone();
+                move |cx| build_chat_server(cx, state)
+            });
+        let _ = user_left.set(chat_server.user_left);
 
         shutdown_signal.wait().await;
 
@@ -97,71 +115,73 @@ fn main() {
 
 fn build_chat_server(
     cx: modrpc::RoleWorkerContext<chat_modrpc::ChatServerRole>,
-    state: Rc<ChatServerState>,
+    state: Arc<ChatServerState>,
 ) {
     cx.stubs.register.build_replier(cx.setup, {
         let state = state.clone();
         async move |mut cx, request| {
-            if state.registered_aliases.borrow().len() >= MAX_USERS {
-                cx.reply.send_err(chat_modrpc::RegisterError::ChatFull).await;
-                return;
-            }
-
-            let Ok(alias) = request.alias() else {
+            let (Ok(alias), Ok(resume_digest)) = (request.alias(), request.resume_digest()) else {
                 cx.reply.send_err(chat_modrpc::RegisterError::Internal).await;
                 return;
             };
 
-            log::info!("Registration request - endpoint={} alias={}", cx.source.endpoint, alias);
-
-            if let Some(&existing_endpoint_with_alias) =
-                state.registered_aliases.borrow().get(alias)
-            {
-                if existing_endpoint_with_alias == cx.source.endpoint {
-                    // This endpoint is already registered as this user - let registration be
-                    // idempotent.
-                    cx.reply.send_ok(chat_modrpc::RegisterSuccess {}).await;
-                    return;
-                } else {
-                    // Some other endpoint has already claimed this alias.
-                    cx.reply.send_err(chat_modrpc::RegisterError::UserAlreadyExists).await;
-                    return;
-                }
+            match state.register(cx.source.endpoint, alias, resume_digest) {
+                Ok(success) => { cx.reply.send_ok(success).await; }
+                Err(error) => { cx.reply.send_err(error).await; }
             }
-
-            state.registered_users.borrow_mut()
-                .insert(cx.source.endpoint, chat_modrpc::RegisteredUser {
-                    endpoint: cx.source.endpoint,
-                    alias: alias.to_string(),
-                });
-            state.registered_aliases.borrow_mut().insert(alias.to_owned(), cx.source.endpoint);
-
-            cx.reply.send_ok(chat_modrpc::RegisterSuccess {}).await;
         }
     });
     cx.stubs.send_message.build_replier(cx.setup, {
         let state = state.clone();
         async move |mut cx, request| {
-            let Ok(content) = request.content() else {
+            let (Ok(content), Ok(idempotency_key)) = (request.content(), request.idempotency_key())
+            else {
                 cx.reply.send_err(chat_modrpc::SendMessageError::Internal).await;
                 return;
             };
 
-            if content.len() > MAX_MESSAGE_LEN {
-                cx.reply.send_err(chat_modrpc::SendMessageError::MessageTooLong).await;
-                return;
+            match state.send_message(cx.source.endpoint, content, idempotency_key) {
+                Ok(success) => { cx.reply.send_ok(success).await; }
+                Err(error) => { cx.reply.send_err(error).await; }
             }
+        }
+    });
+    cx.stubs.resume_session.build_replier(cx.setup, {
+        let state = state.clone();
+        async move |mut cx, request| {
+            let (Ok(alias), Ok(resume_token), Ok(next_resume_digest)) =
+                (request.alias(), request.resume_token(), request.next_resume_digest())
+            else {
+                cx.reply.send_err(chat_modrpc::ResumeSessionError::Internal).await;
+                return;
+            };
 
-            let registered_users = state.registered_users.borrow();
-            let Some(user) = registered_users.get(&cx.source.endpoint) else {
-                cx.reply.send_err(chat_modrpc::SendMessageError::NotRegistered).await;
+            match state.resume_session(cx.source.endpoint, alias, resume_token, next_resume_digest) {
+                Ok(success) => { cx.reply.send_ok(success).await; }
+                Err(error) => { cx.reply.send_err(error).await; }
+            }
+        }
+    });
+    cx.stubs.search_messages.build_replier(cx.setup, {
+        let state = state.clone();
+        async move |mut cx, request| {
+            let (Ok(query), Ok(alias), Ok(after_ms), Ok(before_ms), Ok(before_id), Ok(limit)) = (
+                request.query(),
+                request.alias(),
+                request.after_ms(),
+                request.before_ms(),
+                request.before_id(),
+                request.limit(),
+            ) else {
+                cx.reply.send_err(chat_modrpc::SearchMessagesError::Internal).await;
                 return;
             };
-            log::info!("[endpoint={}] {}: {content}", cx.source.endpoint, user.alias);
-            drop(registered_users);
 
-            cx.reply.send_ok(chat_modrpc::SendMessageSuccess {}).await;
+            let filter = SearchFilter { alias, after_ms, before_ms, before_id };
+            match state.search_messages(cx.source.endpoint, query, &filter, limit) {
+                Ok(success) => { cx.reply.send_ok(success).await; }
+                Err(error) => { cx.reply.send_err(error).await; }
+            }
         }
     });
 }
-
@@ -0,0 +1,133 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::SystemTime,
+};
+
+use crate::sharded::lock;
+
+/// Most recent chat messages, kept around to replay to resumed sessions.
+///
+/// Messages are spread across shards round-robin by ID, so concurrent senders rarely contend for
+/// the same lock while the log as a whole still holds an unbroken run of the latest messages.
+pub struct MessageLog {
+    shards: Box<[Mutex<VecDeque<chat_modrpc::ChatMessage>>]>,
+    // Number of messages kept per shard
+    shard_len: usize,
+    next_id: AtomicU64,
+}
+
+impl MessageLog {
+    pub fn new(len: usize, shard_count: usize) -> Self {
+        assert!(shard_count > 0, "shard_count must be at least 1");
+        Self {
+            shards: (0..shard_count).map(|_| Mutex::new(VecDeque::new())).collect(),
+            shard_len: len.div_ceil(shard_count).max(1),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// ID the next logged message will be given.
+    pub fn next_id(&self) -> u64 {
+        self.next_id.load(Ordering::Acquire)
+    }
+
+    /// Append a message to the log, returning its ID and timestamp.
+    pub fn push(&self, alias: String, content: String) -> (u64, u64) {
+        let id = self.next_id.fetch_add(1, Ordering::AcqRel);
+
+        let timestamp_ms = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |t| t.as_millis() as u64);
+
+        let mut shard = lock(&self.shards[id as usize % self.shards.len()]);
+        if shard.len() >= self.shard_len {
+            shard.pop_front();
+        }
+        shard.push_back(chat_modrpc::ChatMessage {
+            id,
+            timestamp_ms,
+            alias,
+            content,
+        });
+
+        (id, timestamp_ms)
+    }
+
+    /// Logged messages with an ID of at least `first_id`, oldest first, and whether older ones
+    /// were left out to keep the messages within `max_encoded_len` bytes once encoded.
+    pub fn since(
+        &self,
+        first_id: u64,
+        max_encoded_len: usize,
+    ) -> (Vec<chat_modrpc::ChatMessage>, bool) {
+        let mut messages = Vec::new();
+        for shard in self.shards.iter() {
+            messages.extend(lock(shard).iter().filter(|message| message.id >= first_id).cloned());
+        }
+        messages.sort_by_key(|message| message.id);
+
+        // Keep as many of the newest messages as fit.
+        let mut encoded_len = 0;
+        let kept = messages.iter()
+            .rev()
+            .take_while(|message| {
+                encoded_len += mproto::encoded_len(*message);
+                encoded_len <= max_encoded_len
+            })
+            .count();
+        let truncated = kept < messages.len();
+        messages.drain(..messages.len() - kept);
+        (messages, truncated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(messages: &[chat_modrpc::ChatMessage]) -> Vec<u64> {
+        messages.iter().map(|message| message.id).collect()
+    }
+
+    #[test]
+    fn keeps_the_latest_messages_in_order() {
+        let log = MessageLog::new(4, 2);
+        for i in 0..6 {
+            let (id, _) = log.push("alice".to_string(), format!("message {i}"));
+            assert_eq!(id, i);
+        }
+        assert_eq!(log.next_id(), 6);
+
+        let (messages, truncated) = log.since(0, usize::MAX);
+        assert!(!truncated);
+        assert_eq!(ids(&messages), [2, 3, 4, 5]);
+        assert_eq!(messages[0].content, "message 2");
+        assert_eq!(ids(&log.since(4, usize::MAX).0), [4, 5]);
+        assert!(log.since(6, usize::MAX).0.is_empty());
+    }
+
+    #[test]
+    fn since_keeps_the_newest_messages_that_fit() {
+        let log = MessageLog::new(10, 4);
+        for i in 0..10 {
+            log.push("alice".to_string(), format!("message {i}"));
+        }
+        let message_len = mproto::encoded_len(&log.since(0, usize::MAX).0[0]);
+
+        let (messages, truncated) = log.since(2, usize::MAX);
+        assert!(!truncated);
+        assert_eq!(ids(&messages), (2..10).collect::<Vec<_>>());
+
+        let (messages, truncated) = log.since(2, message_len * 3 + 1);
+        assert!(truncated);
+        assert_eq!(ids(&messages), [7, 8, 9]);
+
+        let (messages, truncated) = log.since(2, 0);
+        assert!(truncated);
+        assert!(messages.is_empty());
+    }
+}
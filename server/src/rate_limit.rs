@@ -0,0 +1,262 @@
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    fmt,
+    hash::Hash,
+    time::{Duration, Instant},
+};
+
+/// How often a limiter drops the entries of clients it no longer needs to remember.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Token bucket parameters for one kind of request.
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimitConfig {
+    /// Number of requests a client can make back-to-back.
+    pub burst: u32,
+    /// Number of requests per second a client can sustain.
+    pub per_second: f64,
+    /// Mute clients that keep exceeding their limit.
+    pub escalation: Option<EscalationConfig>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct EscalationConfig {
+    /// Number of consecutive rejected requests after which a client is muted.
+    pub violations: u32,
+    /// How long a muted client has all of its requests rejected.
+    pub mute_duration: Duration,
+}
+
+/// Token bucket rate limiter with a bucket per client, keyed by `K`.
+///
+/// A client's bucket is kept after it disconnects, so reconnecting doesn't get around its limit or
+/// mute. It's only forgotten once it has refilled and any mute is over, at which point it's no
+/// different from a new one.
+pub struct RateLimiter<K> {
+    config: RateLimitConfig,
+    limits: HashMap<K, ClientLimit>,
+    next_sweep: Instant,
+}
+
+struct ClientLimit {
+    tokens: f64,
+    last_refill: Instant,
+    // Requests rejected since the last one that was let through
+    violations: u32,
+    muted_until: Option<Instant>,
+}
+
+impl ClientLimit {
+    /// Whether the bucket would be full by `now` with no mute in force.
+    fn is_idle(&self, config: &RateLimitConfig, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.muted_until.is_none_or(|muted_until| muted_until <= now)
+            && self.tokens + elapsed * config.per_second >= config.burst as f64
+    }
+}
+
+impl<K: Hash + Eq> RateLimiter<K> {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            limits: HashMap::new(),
+            next_sweep: Instant::now() + SWEEP_INTERVAL,
+        }
+    }
+
+    /// Take a token for a request from `key`. If the client is over its limit, returns how long it
+    /// should wait before trying again.
+    pub fn check<Q>(&mut self, key: &Q) -> Result<(), Duration>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = K> + fmt::Display + ?Sized,
+    {
+        self.check_at(key, Instant::now())
+    }
+
+    fn check_at<Q>(&mut self, key: &Q, now: Instant) -> Result<(), Duration>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = K> + fmt::Display + ?Sized,
+    {
+        if now >= self.next_sweep {
+            self.sweep(now);
+        }
+        let config = self.config;
+        if !self.limits.contains_key(key) {
+            self.limits.insert(key.to_owned(), ClientLimit {
+                tokens: config.burst as f64,
+                last_refill: now,
+                violations: 0,
+                muted_until: None,
+            });
+        }
+        let limit = self.limits.get_mut(key).expect("limit was just inserted");
+
+        if let Some(muted_until) = limit.muted_until {
+            if now < muted_until {
+                return Err(muted_until - now);
+            }
+            limit.muted_until = None;
+            limit.violations = 0;
+        }
+
+        let elapsed = now.duration_since(limit.last_refill).as_secs_f64();
+        limit.tokens = f64::min(limit.tokens + elapsed * config.per_second, config.burst as f64);
+        limit.last_refill = now;
+
+        if limit.tokens >= 1.0 {
+            limit.tokens -= 1.0;
+            limit.violations = 0;
+            return Ok(());
+        }
+
+        limit.violations += 1;
+        if let Some(escalation) = config.escalation
+            && limit.violations >= escalation.violations
+        {
+            log::warn!(
+                "Muting {key} for {:?} after {} rate limit violations",
+                escalation.mute_duration, limit.violations,
+            );
+            limit.muted_until = Some(now + escalation.mute_duration);
+            return Err(escalation.mute_duration);
+        }
+
+        Err(Duration::from_secs_f64((1.0 - limit.tokens) / config.per_second))
+    }
+
+    /// Forget the clients that are back to a full bucket and aren't muted.
+    fn sweep(&mut self, now: Instant) {
+        let config = self.config;
+        self.limits.retain(|_, limit| !limit.is_idle(&config, now));
+        self.next_sweep = now + SWEEP_INTERVAL;
+    }
+}
+
+/// Convert a retry-after duration to its wire representation, rounding up so clients don't retry
+/// a hair too early.
+pub fn retry_after_ms(retry_after: Duration) -> u32 {
+    let millis = retry_after.as_micros().div_ceil(1000);
+    u32::try_from(millis).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(escalation: Option<EscalationConfig>) -> RateLimitConfig {
+        RateLimitConfig { burst: 3, per_second: 2.0, escalation }
+    }
+
+    fn secs(secs: f64) -> Duration {
+        Duration::from_secs_f64(secs)
+    }
+
+    #[test]
+    fn allows_a_burst_then_rejects() {
+        let mut limiter = RateLimiter::new(config(None));
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            assert_eq!(limiter.check_at(&1, now), Ok(()));
+        }
+        // One token comes back every half second.
+        assert_eq!(limiter.check_at(&1, now), Err(secs(0.5)));
+        // Other clients have buckets of their own.
+        assert_eq!(limiter.check_at(&2, now), Ok(()));
+    }
+
+    #[test]
+    fn refills_over_time_up_to_the_burst() {
+        let mut limiter = RateLimiter::new(config(None));
+        let now = Instant::now();
+        for _ in 0..3 {
+            limiter.check_at(&1, now).unwrap();
+        }
+
+        let later = now + secs(0.5);
+        assert_eq!(limiter.check_at(&1, later), Ok(()));
+        assert!(limiter.check_at(&1, later).is_err());
+
+        // However long the wait, only `burst` requests go through back-to-back.
+        let much_later = later + secs(60.0);
+        for _ in 0..3 {
+            assert_eq!(limiter.check_at(&1, much_later), Ok(()));
+        }
+        assert!(limiter.check_at(&1, much_later).is_err());
+    }
+
+    #[test]
+    fn mutes_after_consecutive_violations() {
+        let escalation = EscalationConfig { violations: 2, mute_duration: secs(30.0) };
+        let mut limiter = RateLimiter::new(config(Some(escalation)));
+        let now = Instant::now();
+        for _ in 0..3 {
+            limiter.check_at(&1, now).unwrap();
+        }
+
+        assert_eq!(limiter.check_at(&1, now), Err(secs(0.5)));
+        assert_eq!(limiter.check_at(&1, now), Err(secs(30.0)));
+        // Refilled tokens don't help while muted.
+        assert_eq!(limiter.check_at(&1, now + secs(10.0)), Err(secs(20.0)));
+
+        let unmuted = now + secs(30.0);
+        assert_eq!(limiter.check_at(&1, unmuted), Ok(()));
+        // The violation count starts over.
+        limiter.check_at(&1, unmuted).unwrap();
+        limiter.check_at(&1, unmuted).unwrap();
+        assert_eq!(limiter.check_at(&1, unmuted), Err(secs(0.5)));
+    }
+
+    #[test]
+    fn a_request_let_through_resets_the_violations() {
+        let escalation = EscalationConfig { violations: 2, mute_duration: secs(30.0) };
+        let mut limiter = RateLimiter::new(config(Some(escalation)));
+        let now = Instant::now();
+        for _ in 0..3 {
+            limiter.check_at(&1, now).unwrap();
+        }
+
+        assert!(limiter.check_at(&1, now).is_err());
+        let later = now + secs(0.5);
+        assert_eq!(limiter.check_at(&1, later), Ok(()));
+        assert_eq!(limiter.check_at(&1, later), Err(secs(0.5)));
+    }
+
+    #[test]
+    fn sweeps_only_clients_back_to_a_fresh_bucket() {
+        let escalation = EscalationConfig { violations: 1, mute_duration: secs(300.0) };
+        let mut limiter = RateLimiter::new(config(Some(escalation)));
+        let now = Instant::now();
+        limiter.check_at("idle", now).unwrap();
+        for _ in 0..4 {
+            let _ = limiter.check_at("muted", now);
+        }
+
+        limiter.sweep(now + secs(120.0));
+        assert!(!limiter.limits.contains_key("idle"));
+        // A muted client stays muted, however long it's been gone.
+        assert!(limiter.limits.contains_key("muted"));
+        assert_eq!(limiter.check_at("muted", now + secs(120.0)), Err(secs(180.0)));
+    }
+
+    #[test]
+    fn sweeps_as_requests_come_in() {
+        let mut limiter = RateLimiter::new(config(None));
+        let now = Instant::now();
+        limiter.check_at(&1, now).unwrap();
+
+        limiter.check_at(&2, now + SWEEP_INTERVAL).unwrap();
+        assert!(!limiter.limits.contains_key(&1));
+        assert!(limiter.limits.contains_key(&2));
+    }
+
+    #[test]
+    fn retry_after_rounds_up_to_whole_milliseconds() {
+        assert_eq!(retry_after_ms(Duration::from_micros(1)), 1);
+        assert_eq!(retry_after_ms(Duration::from_millis(500)), 500);
+        assert_eq!(retry_after_ms(Duration::from_secs(u64::MAX)), u32::MAX);
+    }
+}
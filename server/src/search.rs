@@ -0,0 +1,341 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Mutex,
+};
+
+use crate::sharded::lock;
+
+/// Most results returned for one `search_messages` request.
+pub const MAX_RESULTS: u32 = 50;
+/// Length of a result snippet in characters, not counting ellipses.
+const SNIPPET_LEN: usize = 80;
+/// Characters shown before the first matching word in a snippet.
+const SNIPPET_CONTEXT: usize = 20;
+
+/// Which messages a search is limited to, besides those containing its words.
+#[derive(Default)]
+pub struct SearchFilter<'a> {
+    pub alias: Option<&'a str>,
+    /// Inclusive, in milliseconds since the Unix epoch
+    pub after_ms: Option<u64>,
+    /// Exclusive, in milliseconds since the Unix epoch
+    pub before_ms: Option<u64>,
+    /// Only messages with lower IDs, for paging through results
+    pub before_id: Option<u64>,
+}
+
+/// Inverted index of the most recent chat messages, backing `search_messages`.
+///
+/// Each word maps to the IDs of the messages it appears in, so a search only walks the messages
+/// containing its rarest word. Holds about `capacity` messages, dropping the oldest.
+///
+/// Every send indexes its message, so like the [`MessageLog`](crate::message_log::MessageLog)
+/// the index is spread across shards round-robin by message ID and a send only locks one of them.
+/// A search locks each shard in turn and merges what they find.
+pub struct SearchIndex {
+    shards: Box<[Mutex<Shard>]>,
+    // Number of messages kept per shard
+    shard_len: usize,
+}
+
+#[derive(Default)]
+struct Shard {
+    messages: BTreeMap<u64, IndexedMessage>,
+    // Lowercased word to the IDs of the messages containing it
+    postings: HashMap<String, BTreeSet<u64>>,
+}
+
+struct IndexedMessage {
+    timestamp_ms: u64,
+    alias: String,
+    content: String,
+}
+
+impl SearchIndex {
+    pub fn new(capacity: usize, shard_count: usize) -> Self {
+        assert!(shard_count > 0, "shard_count must be at least 1");
+        Self {
+            shards: (0..shard_count).map(|_| Mutex::new(Shard::default())).collect(),
+            shard_len: capacity.div_ceil(shard_count),
+        }
+    }
+
+    /// Index a message that was just posted.
+    pub fn insert(&self, id: u64, timestamp_ms: u64, alias: &str, content: &str) {
+        if self.shard_len == 0 {
+            return;
+        }
+        let mut shard = lock(&self.shards[id as usize % self.shards.len()]);
+        for (_, word) in words(content) {
+            shard.postings.entry(word.to_lowercase()).or_default().insert(id);
+        }
+        shard.messages.insert(id, IndexedMessage {
+            timestamp_ms,
+            alias: alias.to_owned(),
+            content: content.to_owned(),
+        });
+
+        while shard.messages.len() > self.shard_len {
+            let Some((oldest_id, oldest)) = shard.messages.pop_first() else {
+                break;
+            };
+            shard.remove_postings(oldest_id, &oldest.content);
+        }
+    }
+
+    /// Messages containing every word in `query`, newest first. Returns at most `limit` results,
+    /// capped at [`MAX_RESULTS`], along with where to pick up for the next page.
+    pub fn search(
+        &self,
+        query: &str,
+        filter: &SearchFilter<'_>,
+        limit: u32,
+    ) -> Result<chat_modrpc::SearchMessagesSuccess, chat_modrpc::SearchMessagesError> {
+        let mut query_words: Vec<String> =
+            words(query).map(|(_, word)| word.to_lowercase()).collect();
+        query_words.sort();
+        query_words.dedup();
+        if query_words.is_empty() {
+            return Err(chat_modrpc::SearchMessagesError::EmptyQuery);
+        }
+        let limit = limit.clamp(1, MAX_RESULTS) as usize;
+
+        // Each shard's newest matches, one more than fits on the page to tell if there's another.
+        let mut results = Vec::new();
+        for shard in self.shards.iter() {
+            results.extend(lock(shard).search(&query_words, filter, limit + 1));
+        }
+        results.sort_by_key(|result| std::cmp::Reverse(result.message_id));
+
+        let mut page = chat_modrpc::SearchMessagesSuccess {
+            results,
+            next_before_id: None,
+        };
+        if page.results.len() > limit {
+            page.results.truncate(limit);
+            // There's at least one more, so point the next page past the last result.
+            page.next_before_id = page.results.last().map(|r| r.message_id);
+        }
+        Ok(page)
+    }
+}
+
+impl Shard {
+    /// Up to `limit` of this shard's messages containing every one of `query_words` (sorted and
+    /// lowercased), newest first.
+    fn search(
+        &self,
+        query_words: &[String],
+        filter: &SearchFilter<'_>,
+        limit: usize,
+    ) -> Vec<chat_modrpc::SearchResult> {
+        let mut postings = Vec::with_capacity(query_words.len());
+        for word in query_words {
+            match self.postings.get(word) {
+                Some(ids) => postings.push(ids),
+                // No message has this word, so none has all of them.
+                None => return Vec::new(),
+            }
+        }
+        postings.sort_by_key(|ids| ids.len());
+        let (rarest, others) = postings.split_first().expect("query has at least one word");
+
+        rarest
+            .range(..filter.before_id.unwrap_or(u64::MAX))
+            .rev()
+            .filter(|id| others.iter().all(|ids| ids.contains(id)))
+            .map(|id| (*id, &self.messages[id]))
+            .filter(|(_, message)| {
+                filter.alias.is_none_or(|alias| alias == message.alias)
+                    && filter.after_ms.is_none_or(|after_ms| message.timestamp_ms >= after_ms)
+                    && filter.before_ms.is_none_or(|before_ms| message.timestamp_ms < before_ms)
+            })
+            .take(limit)
+            .map(|(id, message)| chat_modrpc::SearchResult {
+                message_id: id,
+                timestamp_ms: message.timestamp_ms,
+                alias: message.alias.clone(),
+                snippet: snippet(&message.content, query_words),
+            })
+            .collect()
+    }
+
+    fn remove_postings(&mut self, id: u64, content: &str) {
+        for (_, word) in words(content) {
+            let word = word.to_lowercase();
+            if let Some(ids) = self.postings.get_mut(&word) {
+                ids.remove(&id);
+                if ids.is_empty() {
+                    self.postings.remove(&word);
+                }
+            }
+        }
+    }
+}
+
+/// Runs of letters and digits in `text`, with their byte offsets.
+fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut chars = text.char_indices().peekable();
+    std::iter::from_fn(move || {
+        let (start, _) = chars.find(|(_, c)| c.is_alphanumeric())?;
+        let mut end = text.len();
+        while let Some(&(i, c)) = chars.peek() {
+            if !c.is_alphanumeric() {
+                end = i;
+                break;
+            }
+            chars.next();
+        }
+        Some((start, &text[start..end]))
+    })
+}
+
+/// A one-line excerpt of `content` starting a little before the first of `query_words` in it.
+fn snippet(content: &str, query_words: &[String]) -> String {
+    let offset = words(content)
+        .find(|(_, word)| query_words.binary_search(&word.to_lowercase()).is_ok())
+        .map_or(0, |(offset, _)| offset);
+    let start = content[..offset].chars().count().saturating_sub(SNIPPET_CONTEXT);
+    let excerpt: String = content.chars().skip(start).take(SNIPPET_LEN).collect();
+
+    let mut snippet = excerpt.split_whitespace().collect::<Vec<_>>().join(" ");
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    if content.chars().count() > start + SNIPPET_LEN {
+        snippet.push('…');
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(page: &chat_modrpc::SearchMessagesSuccess) -> Vec<u64> {
+        page.results.iter().map(|result| result.message_id).collect()
+    }
+
+    #[test]
+    fn matches_every_word_starting_from_the_rarest() {
+        let index = SearchIndex::new(100, 4);
+        index.insert(0, 0, "alice", "the deploy is done");
+        index.insert(1, 0, "bob", "the server is up");
+        index.insert(2, 0, "alice", "the server deploy failed");
+        index.insert(3, 0, "bob", "The SERVER, the deploy!");
+
+        let page = index.search("the server deploy", &SearchFilter::default(), 10).unwrap();
+        assert_eq!(ids(&page), [3, 2]);
+        assert_eq!(page.next_before_id, None);
+
+        // A word no message has rules out every message, however common the others are.
+        let page = index.search("the nothing", &SearchFilter::default(), 10).unwrap();
+        assert!(page.results.is_empty());
+
+        assert_eq!(
+            index.search(" !? ", &SearchFilter::default(), 10).unwrap_err(),
+            chat_modrpc::SearchMessagesError::EmptyQuery,
+        );
+    }
+
+    #[test]
+    fn applies_filters() {
+        let index = SearchIndex::new(100, 4);
+        index.insert(0, 100, "alice", "hello");
+        index.insert(1, 200, "bob", "hello");
+        index.insert(2, 300, "alice", "hello");
+
+        let search = |filter: SearchFilter<'_>| ids(&index.search("hello", &filter, 10).unwrap());
+        assert_eq!(search(SearchFilter { alias: Some("alice"), ..Default::default() }), [2, 0]);
+        assert_eq!(search(SearchFilter { after_ms: Some(200), ..Default::default() }), [2, 1]);
+        assert_eq!(search(SearchFilter { before_ms: Some(200), ..Default::default() }), [0]);
+        assert_eq!(search(SearchFilter { before_id: Some(2), ..Default::default() }), [1, 0]);
+    }
+
+    #[test]
+    fn drops_the_oldest_messages_at_capacity() {
+        let index = SearchIndex::new(2, 1);
+        index.insert(0, 0, "alice", "hello there");
+        index.insert(1, 0, "alice", "hello again");
+        index.insert(2, 0, "alice", "hello once more");
+
+        let page = index.search("hello", &SearchFilter::default(), 10).unwrap();
+        assert_eq!(ids(&page), [2, 1]);
+        // The dropped message's words go with it.
+        assert!(index.search("there", &SearchFilter::default(), 10).unwrap().results.is_empty());
+    }
+
+    #[test]
+    fn keeps_about_capacity_messages_across_shards() {
+        let index = SearchIndex::new(8, 4);
+        for id in 0..20 {
+            index.insert(id, 0, "alice", "hello");
+        }
+
+        let page = index.search("hello", &SearchFilter::default(), MAX_RESULTS).unwrap();
+        assert_eq!(ids(&page), (12..20).rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn pages_through_results() {
+        let index = SearchIndex::new(100, 4);
+        for id in 0..7 {
+            index.insert(id, 0, "alice", "hello");
+        }
+
+        let mut filter = SearchFilter::default();
+        let mut pages = Vec::new();
+        loop {
+            let page = index.search("hello", &filter, 3).unwrap();
+            pages.push(ids(&page));
+            match page.next_before_id {
+                Some(before_id) => filter.before_id = Some(before_id),
+                None => break,
+            }
+        }
+        assert_eq!(pages, [vec![6, 5, 4], vec![3, 2, 1], vec![0]]);
+
+        // A page that's exactly full has nothing after it.
+        let page = index.search("hello", &SearchFilter::default(), 7).unwrap();
+        assert_eq!(page.results.len(), 7);
+        assert_eq!(page.next_before_id, None);
+    }
+
+    #[test]
+    fn limit_is_clamped() {
+        let index = SearchIndex::new(100, 4);
+        for id in 0..(MAX_RESULTS as u64 + 1) {
+            index.insert(id, 0, "alice", "hello");
+        }
+
+        let page = index.search("hello", &SearchFilter::default(), 0).unwrap();
+        assert_eq!(page.results.len(), 1);
+        let page = index.search("hello", &SearchFilter::default(), u32::MAX).unwrap();
+        assert_eq!(page.results.len(), MAX_RESULTS as usize);
+        assert_eq!(page.next_before_id, Some(1));
+    }
+
+    #[test]
+    fn snippet_starts_a_little_before_the_first_match() {
+        let query = ["deploy".to_string()];
+        assert_eq!(snippet("time to Deploy it", &query), "time to Deploy it");
+
+        let content = format!("{}deploy{}", "a ".repeat(20), " b".repeat(60));
+        let snippet = snippet(&content, &query);
+        assert!(snippet.starts_with("…a a"), "{snippet}");
+        assert!(snippet.ends_with('…'), "{snippet}");
+        assert!(snippet.contains("deploy"), "{snippet}");
+    }
+
+    #[test]
+    fn snippet_collapses_whitespace() {
+        let query = ["hello".to_string()];
+        assert_eq!(snippet("hello\n\n  there\tyou", &query), "hello there you");
+    }
+
+    #[test]
+    fn splits_words_on_anything_but_letters_and_digits() {
+        let words: Vec<_> = words("Hi, zoë! 2nd-try…ok").collect();
+        assert_eq!(words, [(0, "Hi"), (4, "zoë"), (10, "2nd"), (14, "try"), (20, "ok")]);
+    }
+}
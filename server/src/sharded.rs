@@ -0,0 +1,38 @@
+use std::{
+    hash::{BuildHasher, Hash, RandomState},
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+/// A value split into independently locked shards, so workers touching different keys don't
+/// contend for the same lock.
+pub struct Sharded<T> {
+    shards: Box<[Mutex<T>]>,
+    hasher: RandomState,
+}
+
+impl<T> Sharded<T> {
+    pub fn new(shard_count: usize, mut init: impl FnMut() -> T) -> Self {
+        assert!(shard_count > 0, "shard_count must be at least 1");
+        Self {
+            shards: (0..shard_count).map(|_| Mutex::new(init())).collect(),
+            hasher: RandomState::new(),
+        }
+    }
+
+    /// Lock the shard that `key` belongs to.
+    pub fn lock<K: Hash + ?Sized>(&self, key: &K) -> MutexGuard<'_, T> {
+        let index = self.hasher.hash_one(key) as usize % self.shards.len();
+        lock(&self.shards[index])
+    }
+
+    /// Lock each shard in turn, releasing the previous one before taking the next.
+    pub fn lock_each(&self) -> impl Iterator<Item = MutexGuard<'_, T>> {
+        self.shards.iter().map(lock)
+    }
+}
+
+/// Lock a mutex, ignoring poisoning. State updates never leave a shard half-modified across a
+/// panic, so there's nothing to recover.
+pub fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
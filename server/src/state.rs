@@ -0,0 +1,586 @@
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    config::ServerConfig,
+    idempotency::RecentSends,
+    message_log::MessageLog,
+    rate_limit::{self, RateLimiter},
+    search::{SearchFilter, SearchIndex},
+    sharded::Sharded,
+};
+
+/// Number of shards each piece of state is split into. Having several times more shards than
+/// workers keeps the odds of two workers contending for the same lock low.
+const SHARD_COUNT: usize = 64;
+
+/// Chat server state shared by every worker.
+pub struct ChatServerState {
+    config: chat_modrpc::ChatServerConfig,
+    // Advertised to clients when they connect
+    server_info: chat_modrpc::ServerInfo,
+    // How long a disconnected user's alias is held for them to resume their session
+    session_grace_period: Duration,
+    // Most bytes of missed messages replayed to a resumed session
+    max_replay_len: usize,
+    // Map endpoint ID to its registered user object, sharded by endpoint ID
+    registered_users: Sharded<HashMap<u64, chat_modrpc::RegisteredUser>>,
+    // Map user alias to its claim, sharded by alias
+    aliases: Sharded<HashMap<String, AliasClaim>>,
+    // Number of claimed aliases across all shards, including held ones
+    alias_count: AtomicU64,
+    message_log: MessageLog,
+    search_index: SearchIndex,
+    // Keyed by alias, which a user keeps across reconnects
+    send_message_limiter: Sharded<RateLimiter<String>>,
+    // Shared by `register` and `resume_session`. Keyed by endpoint, as there's no alias yet and
+    // modrpc doesn't tell us the client's network address. A client that reconnects gets a new
+    // endpoint and so a fresh limit.
+    register_limiter: Sharded<RateLimiter<u64>>,
+    // Keyed by alias
+    search_messages_limiter: Sharded<RateLimiter<String>>,
+}
+
+struct AliasClaim {
+    // Endpoint the alias is registered to. While the session is held this is the endpoint that
+    // disconnected.
+    endpoint: u64,
+    session: Option<Session>,
+    // Lets retried messages be recognized for as long as the alias stays claimed, including
+    // across a resumed session.
+    recent_sends: RecentSends,
+}
+
+/// A session that can be resumed by revealing the secret behind `resume_digest`.
+///
+/// Requests and responses are multicast to every client, so the server can't hand out a
+/// secret token itself. Instead the client commits to a secret it keeps to itself and reveals
+/// it (along with a commitment to the next one) when it resumes.
+struct Session {
+    // Hex-encoded SHA-256 digest of the client's current resume token
+    resume_digest: String,
+    // Present while the owning client is disconnected
+    held: Option<HeldSession>,
+}
+
+struct HeldSession {
+    expires_at: Instant,
+    // ID of the first message logged after the client disconnected
+    first_missed_message_id: u64,
+}
+
+impl AliasClaim {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.session.as_ref()
+            .and_then(|session| session.held.as_ref())
+            .is_some_and(|held| held.expires_at <= now)
+    }
+}
+
+impl ChatServerState {
+    pub fn new(config: &ServerConfig) -> Self {
+        let send_message_limit = config.rate_limit.send_message.rate_limit_config();
+        let register_limit = config.rate_limit.register.rate_limit_config();
+        let search_messages_limit = config.rate_limit.search_messages.rate_limit_config();
+        Self {
+            config: config.chat_server_config(),
+            server_info: config.server_info(),
+            session_grace_period: config.session_grace_period(),
+            max_replay_len: config.max_response_messages_len(),
+            registered_users: Sharded::new(SHARD_COUNT, HashMap::new),
+            aliases: Sharded::new(SHARD_COUNT, HashMap::new),
+            alias_count: AtomicU64::new(0),
+            message_log: MessageLog::new(config.message_log_len, SHARD_COUNT),
+            search_index: SearchIndex::new(config.search_index_len, SHARD_COUNT),
+            send_message_limiter: Sharded::new(SHARD_COUNT, || RateLimiter::new(send_message_limit)),
+            register_limiter: Sharded::new(SHARD_COUNT, || RateLimiter::new(register_limit)),
+            search_messages_limiter: Sharded::new(SHARD_COUNT, || {
+                RateLimiter::new(search_messages_limit)
+            }),
+        }
+    }
+
+    pub fn server_info(&self) -> &chat_modrpc::ServerInfo {
+        &self.server_info
+    }
+
+    /// Snapshot of the currently registered users.
+    pub fn registered_users(&self) -> Vec<chat_modrpc::RegisteredUser> {
+        let mut users = Vec::new();
+        for shard in self.registered_users.lock_each() {
+            users.extend(shard.values().cloned());
+        }
+        users
+    }
+
+    pub fn register(
+        &self,
+        endpoint: u64,
+        alias: &str,
+        resume_digest: Option<&str>,
+    ) -> Result<chat_modrpc::RegisterSuccess, chat_modrpc::RegisterError> {
+        let allowed = self.register_limiter.lock(&endpoint).check(&endpoint);
+        if let Err(retry_after) = allowed {
+            return Err(chat_modrpc::RegisterError::RateLimited {
+                retry_after_ms: rate_limit::retry_after_ms(retry_after),
+            });
+        }
+
+        if let Err(error) = chat_common::validate_alias(alias) {
+            log::info!("Rejected registration - endpoint={endpoint} alias={alias:?}: {error}");
+            return Err(chat_modrpc::RegisterError::InvalidAlias);
+        }
+
+        // Reserve a slot for the alias up front so concurrent registrations can't overshoot
+        // max_users. It's given back below if the alias turns out to be claimed already.
+        // Renaming frees up the old alias once it's done, so it goes ahead even when full.
+        let renaming = self.registered_users.lock(&endpoint)
+            .get(&endpoint)
+            .is_some_and(|user| user.alias != alias);
+        if renaming {
+            self.alias_count.fetch_add(1, Ordering::AcqRel);
+        } else if !self.reserve_alias_slot() {
+            self.release_expired_sessions();
+            if !self.reserve_alias_slot() {
+                return Err(chat_modrpc::RegisterError::ChatFull);
+            }
+        }
+
+        log::info!("Registration request - endpoint={endpoint} alias={alias}");
+
+        let mut aliases = self.aliases.lock(alias);
+        if aliases.get(alias).is_some_and(|claim| claim.is_expired(Instant::now())) {
+            log::info!("Session expired: {alias}");
+            aliases.remove(alias);
+            self.alias_count.fetch_sub(1, Ordering::AcqRel);
+        }
+
+        if let Some(claim) = aliases.get_mut(alias) {
+            self.alias_count.fetch_sub(1, Ordering::AcqRel);
+            if claim.endpoint == endpoint {
+                // This endpoint is already registered as this user - let registration be
+                // idempotent.
+                if let Some(resume_digest) = resume_digest {
+                    claim.session = Some(Session {
+                        resume_digest: resume_digest.to_owned(),
+                        held: None,
+                    });
+                }
+                return Ok(chat_modrpc::RegisterSuccess {});
+            } else {
+                // Some other endpoint has already claimed this alias.
+                return Err(chat_modrpc::RegisterError::UserAlreadyExists);
+            }
+        }
+
+        aliases.insert(alias.to_owned(), AliasClaim {
+            endpoint,
+            session: resume_digest.map(|resume_digest| Session {
+                resume_digest: resume_digest.to_owned(),
+                held: None,
+            }),
+            recent_sends: RecentSends::default(),
+        });
+        drop(aliases);
+        self.bind_user(endpoint, alias);
+
+        Ok(chat_modrpc::RegisterSuccess {})
+    }
+
+    /// Record that `endpoint` is now registered as `alias`. An endpoint that was registered under
+    /// a different alias gives that one up.
+    ///
+    /// Must not be called with an `aliases` shard locked.
+    fn bind_user(&self, endpoint: u64, alias: &str) {
+        let user = chat_modrpc::RegisteredUser { endpoint, alias: alias.to_owned() };
+        let previous = self.registered_users.lock(&endpoint).insert(endpoint, user);
+        if let Some(previous) = previous && previous.alias != alias {
+            log::info!("User renamed: [endpoint={endpoint}] {} -> {alias}", previous.alias);
+            self.release_alias(endpoint, &previous.alias);
+        }
+    }
+
+    /// Drop `endpoint`'s claim on `alias`, if it still has one.
+    fn release_alias(&self, endpoint: u64, alias: &str) {
+        let mut aliases = self.aliases.lock(alias);
+        if aliases.get(alias).is_some_and(|claim| claim.endpoint == endpoint) {
+            aliases.remove(alias);
+            self.alias_count.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+
+    /// Post a message to the chat, unless one was already posted by the same user with the same
+    /// idempotency key.
+    pub fn send_message(
+        &self,
+        endpoint: u64,
+        content: &str,
+        idempotency_key: &str,
+    ) -> Result<chat_modrpc::SendMessageSuccess, chat_modrpc::SendMessageError> {
+        let Some(alias) = self.registered_users.lock(&endpoint)
+            .get(&endpoint)
+            .map(|user| user.alias.clone())
+        else {
+            return Err(chat_modrpc::SendMessageError::NotRegistered);
+        };
+
+        let allowed = self.send_message_limiter.lock(alias.as_str()).check(alias.as_str());
+        if let Err(retry_after) = allowed {
+            return Err(chat_modrpc::SendMessageError::RateLimited {
+                retry_after_ms: rate_limit::retry_after_ms(retry_after),
+            });
+        }
+
+        if content.len() as u64 > self.config.max_message_len {
+            return Err(chat_modrpc::SendMessageError::MessageTooLong);
+        }
+
+        // Hold the alias lock until the key is recorded so a retry racing the original can't
+        // post the message twice.
+        let mut aliases = self.aliases.lock(&alias);
+        let Some(claim) = aliases.get_mut(&alias).filter(|claim| claim.endpoint == endpoint) else {
+            return Err(chat_modrpc::SendMessageError::NotRegistered);
+        };
+        if let Some(message_id) = claim.recent_sends.get(idempotency_key) {
+            log::info!("[endpoint={endpoint}] {alias}: duplicate of message {message_id}");
+            return Ok(chat_modrpc::SendMessageSuccess { message_id, duplicate: true });
+        }
+
+        log::info!("[endpoint={endpoint}] {alias}: {content}");
+        let (message_id, timestamp_ms) = self.message_log.push(alias.clone(), content.to_owned());
+        claim.recent_sends.insert(idempotency_key, message_id);
+        drop(aliases);
+        self.search_index.insert(message_id, timestamp_ms, &alias, content);
+
+        Ok(chat_modrpc::SendMessageSuccess { message_id, duplicate: false })
+    }
+
+    /// Search the messages posted to the chat. Everyone registered sees every message, so that's
+    /// all it takes to search them.
+    pub fn search_messages(
+        &self,
+        endpoint: u64,
+        query: &str,
+        filter: &SearchFilter<'_>,
+        limit: u32,
+    ) -> Result<chat_modrpc::SearchMessagesSuccess, chat_modrpc::SearchMessagesError> {
+        let Some(alias) = self.registered_users.lock(&endpoint)
+            .get(&endpoint)
+            .map(|user| user.alias.clone())
+        else {
+            return Err(chat_modrpc::SearchMessagesError::NotRegistered);
+        };
+
+        let allowed = self.search_messages_limiter.lock(alias.as_str()).check(alias.as_str());
+        if let Err(retry_after) = allowed {
+            return Err(chat_modrpc::SearchMessagesError::RateLimited {
+                retry_after_ms: rate_limit::retry_after_ms(retry_after),
+            });
+        }
+
+        log::info!("Search request - endpoint={endpoint} query={query:?}");
+        self.search_index.search(query, filter, limit)
+    }
+
+    pub fn resume_session(
+        &self,
+        endpoint: u64,
+        alias: &str,
+        resume_token: &str,
+        next_resume_digest: &str,
+    ) -> Result<chat_modrpc::ResumeSessionSuccess, chat_modrpc::ResumeSessionError> {
+        let allowed = self.register_limiter.lock(&endpoint).check(&endpoint);
+        if let Err(retry_after) = allowed {
+            return Err(chat_modrpc::ResumeSessionError::RateLimited {
+                retry_after_ms: rate_limit::retry_after_ms(retry_after),
+            });
+        }
+
+        log::info!("Resume session request - endpoint={endpoint} alias={alias}");
+
+        let mut aliases = self.aliases.lock(alias);
+        if aliases.get(alias).is_some_and(|claim| claim.is_expired(Instant::now())) {
+            log::info!("Session expired: {alias}");
+            aliases.remove(alias);
+            self.alias_count.fetch_sub(1, Ordering::AcqRel);
+            return Err(chat_modrpc::ResumeSessionError::InvalidSession);
+        }
+
+        let Some(claim) = aliases.get_mut(alias) else {
+            return Err(chat_modrpc::ResumeSessionError::InvalidSession);
+        };
+        let Some(session) = claim.session.as_mut() else {
+            return Err(chat_modrpc::ResumeSessionError::InvalidSession);
+        };
+        let Some(held) = session.held.as_ref() else {
+            // The session is still bound to a connected endpoint.
+            return Err(chat_modrpc::ResumeSessionError::InvalidSession);
+        };
+        if chat_common::resume_digest(resume_token) != session.resume_digest {
+            return Err(chat_modrpc::ResumeSessionError::InvalidSession);
+        }
+
+        let first_missed_message_id = held.first_missed_message_id;
+        session.resume_digest = next_resume_digest.to_owned();
+        session.held = None;
+        claim.endpoint = endpoint;
+        drop(aliases);
+        self.bind_user(endpoint, alias);
+
+        let (missed_messages, truncated) =
+            self.message_log.since(first_missed_message_id, self.max_replay_len);
+        Ok(chat_modrpc::ResumeSessionSuccess { missed_messages, truncated })
+    }
+
+    /// Forget about an endpoint that has disconnected, holding its alias if it has a session to
+    /// resume. Returns the user the endpoint was registered as, if any.
+    ///
+    /// Limits per alias are left alone, so a user can't reset them by reconnecting. The
+    /// `register` limit is per endpoint, and a reconnecting client gets a new one anyway.
+    pub fn client_disconnected(&self, endpoint: u64) -> Option<chat_modrpc::RegisteredUser> {
+        let Some(user) = self.registered_users.lock(&endpoint).remove(&endpoint) else {
+            log::info!("Unregistered client disconnected: [endpoint={endpoint}]");
+            return None;
+        };
+        log::info!("User disconnected: [endpoint={endpoint}] {}", user.alias);
+
+        let first_missed_message_id = self.message_log.next_id();
+        let mut aliases = self.aliases.lock(&user.alias);
+        match aliases.get_mut(&user.alias) {
+            // The alias has already been bound to another endpoint.
+            Some(claim) if claim.endpoint != endpoint => {}
+            Some(AliasClaim { session: Some(session), .. }) => {
+                // Keep the alias claimed so the user can resume their session after reconnecting.
+                session.held = Some(HeldSession {
+                    expires_at: Instant::now() + self.session_grace_period,
+                    first_missed_message_id,
+                });
+            }
+            Some(AliasClaim { session: None, .. }) => {
+                aliases.remove(&user.alias);
+                self.alias_count.fetch_sub(1, Ordering::AcqRel);
+            }
+            None => {}
+        }
+        drop(aliases);
+
+        Some(user)
+    }
+
+    fn reserve_alias_slot(&self) -> bool {
+        let max_users = self.config.max_users;
+        self.alias_count
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                (count < max_users).then_some(count + 1)
+            })
+            .is_ok()
+    }
+
+    /// Release the aliases of held sessions whose grace period has run out.
+    fn release_expired_sessions(&self) {
+        let now = Instant::now();
+        for mut aliases in self.aliases.lock_each() {
+            aliases.retain(|alias, claim| {
+                let expired = claim.is_expired(now);
+                if expired {
+                    log::info!("Session expired: {alias}");
+                    self.alias_count.fetch_sub(1, Ordering::AcqRel);
+                }
+                !expired
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(configure: impl FnOnce(&mut ServerConfig)) -> ChatServerState {
+        let mut config = ServerConfig::default();
+        configure(&mut config);
+        ChatServerState::new(&config)
+    }
+
+    /// Register `alias` on `endpoint` with a session resumable with `resume_token`.
+    fn register(state: &ChatServerState, endpoint: u64, alias: &str, resume_token: &str) {
+        let resume_digest = chat_common::resume_digest(resume_token);
+        state.register(endpoint, alias, Some(&resume_digest)).unwrap();
+    }
+
+    fn resume(
+        state: &ChatServerState,
+        endpoint: u64,
+        alias: &str,
+        resume_token: &str,
+    ) -> Result<chat_modrpc::ResumeSessionSuccess, chat_modrpc::ResumeSessionError> {
+        let next_resume_digest = chat_common::resume_digest("next");
+        state.resume_session(endpoint, alias, resume_token, &next_resume_digest)
+    }
+
+    fn aliases(state: &ChatServerState) -> Vec<String> {
+        let mut aliases: Vec<_> = state.registered_users().into_iter().map(|u| u.alias).collect();
+        aliases.sort();
+        aliases
+    }
+
+    #[test]
+    fn registered_aliases_are_taken() {
+        let state = state(|_| {});
+        register(&state, 1, "alice", "token");
+        // Registering again on the same endpoint is fine.
+        register(&state, 1, "alice", "token");
+        assert_eq!(
+            state.register(2, "alice", None),
+            Err(chat_modrpc::RegisterError::UserAlreadyExists),
+        );
+        assert_eq!(
+            state.register(2, "not an alias", None),
+            Err(chat_modrpc::RegisterError::InvalidAlias),
+        );
+        assert_eq!(aliases(&state), ["alice"]);
+    }
+
+    #[test]
+    fn renaming_frees_the_old_alias() {
+        let state = state(|c| c.max_users = 2);
+        register(&state, 1, "alice", "token");
+        register(&state, 1, "alicia", "token");
+        assert_eq!(aliases(&state), ["alicia"]);
+
+        // The old alias is free, and so is its slot.
+        register(&state, 2, "alice", "token");
+        assert_eq!(aliases(&state), ["alice", "alicia"]);
+        assert_eq!(
+            state.register(3, "bob", None),
+            Err(chat_modrpc::RegisterError::ChatFull),
+        );
+    }
+
+    #[test]
+    fn duplicate_idempotency_key_returns_the_original_message_id() {
+        let state = state(|_| {});
+        register(&state, 1, "alice", "token");
+        register(&state, 2, "bob", "token");
+
+        let first = state.send_message(1, "hello", "key").unwrap();
+        assert!(!first.duplicate);
+        let retry = state.send_message(1, "hello", "key").unwrap();
+        assert_eq!(retry.message_id, first.message_id);
+        assert!(retry.duplicate);
+        // Keys are per alias.
+        let other = state.send_message(2, "hello", "key").unwrap();
+        assert_ne!(other.message_id, first.message_id);
+        assert!(!other.duplicate);
+
+        assert_eq!(state.message_log.since(0, usize::MAX).0.len(), 2);
+    }
+
+    #[test]
+    fn send_message_requires_registration_and_a_short_enough_message() {
+        let state = state(|c| c.max_message_len = 5);
+        assert_eq!(
+            state.send_message(1, "hello", "key"),
+            Err(chat_modrpc::SendMessageError::NotRegistered),
+        );
+        register(&state, 1, "alice", "token");
+        assert_eq!(
+            state.send_message(1, "hello!", "key"),
+            Err(chat_modrpc::SendMessageError::MessageTooLong),
+        );
+    }
+
+    #[test]
+    fn resume_replays_missed_messages() {
+        let state = state(|_| {});
+        register(&state, 1, "alice", "token");
+        register(&state, 2, "bob", "token");
+        state.send_message(2, "before", "1").unwrap();
+
+        assert_eq!(state.client_disconnected(1).map(|user| user.alias).as_deref(), Some("alice"));
+        state.send_message(2, "while away", "2").unwrap();
+        // The alias is held for the session.
+        assert_eq!(
+            state.register(3, "alice", None),
+            Err(chat_modrpc::RegisterError::UserAlreadyExists),
+        );
+
+        let success = resume(&state, 3, "alice", "token").unwrap();
+        let missed: Vec<_> = success.missed_messages.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(missed, ["while away"]);
+        assert!(!success.truncated);
+        assert_eq!(aliases(&state), ["alice", "bob"]);
+        // Resuming uses up the token, and the next one is for the next disconnect.
+        assert_eq!(state.send_message(3, "back", "3").map(|s| s.duplicate), Ok(false));
+        state.client_disconnected(3);
+        assert_eq!(
+            resume(&state, 4, "alice", "token"),
+            Err(chat_modrpc::ResumeSessionError::InvalidSession),
+        );
+        assert!(resume(&state, 4, "alice", "next").is_ok());
+    }
+
+    #[test]
+    fn resume_with_the_wrong_token_fails_and_keeps_the_session() {
+        let state = state(|_| {});
+        register(&state, 1, "alice", "token");
+        state.client_disconnected(1);
+
+        assert_eq!(
+            resume(&state, 2, "alice", "wrong"),
+            Err(chat_modrpc::ResumeSessionError::InvalidSession),
+        );
+        assert_eq!(
+            resume(&state, 2, "bob", "token"),
+            Err(chat_modrpc::ResumeSessionError::InvalidSession),
+        );
+        assert!(resume(&state, 2, "alice", "token").is_ok());
+    }
+
+    #[test]
+    fn resume_fails_while_the_session_is_still_connected() {
+        let state = state(|_| {});
+        register(&state, 1, "alice", "token");
+        assert_eq!(
+            resume(&state, 2, "alice", "token"),
+            Err(chat_modrpc::ResumeSessionError::InvalidSession),
+        );
+    }
+
+    #[test]
+    fn expired_sessions_cannot_be_resumed() {
+        let state = state(|c| c.session_grace_period_secs = 0);
+        register(&state, 1, "alice", "token");
+        state.client_disconnected(1);
+
+        assert_eq!(
+            resume(&state, 2, "alice", "token"),
+            Err(chat_modrpc::ResumeSessionError::InvalidSession),
+        );
+        // The alias is free again.
+        register(&state, 2, "alice", "token");
+    }
+
+    #[test]
+    fn disconnecting_without_a_session_frees_the_alias() {
+        let state = state(|c| c.max_users = 1);
+        state.register(1, "alice", None).unwrap();
+        state.client_disconnected(1);
+        assert!(aliases(&state).is_empty());
+        register(&state, 2, "alice", "token");
+    }
+
+    #[test]
+    fn expired_sessions_make_room_for_new_users() {
+        let state = state(|c| {
+            c.max_users = 1;
+            c.session_grace_period_secs = 0;
+        });
+        register(&state, 1, "alice", "token");
+        state.client_disconnected(1);
+        register(&state, 2, "bob", "token");
+    }
+}
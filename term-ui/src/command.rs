@@ -0,0 +1,136 @@
+/// A slash command typed into the input box.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /// Register again under a different alias.
+    Nick(String),
+    /// Post an action, shown as "* alias does something".
+    Me(String),
+    /// List the registered users.
+    Who,
+    /// Search the message list, starting with this query.
+    Search(String),
+    /// Clear the message list.
+    Clear,
+    Quit,
+    Help,
+}
+
+/// What was typed into the input box.
+#[derive(Debug, PartialEq)]
+pub enum Input {
+    /// A message to post to the chat.
+    Message(String),
+    /// A command, or why it couldn't be parsed.
+    Command(Result<Command, String>),
+}
+
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub description: &'static str,
+}
+
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec { name: "nick", usage: "/nick <alias>", description: "Change your alias" },
+    CommandSpec { name: "me", usage: "/me <action>", description: "Describe what you're doing" },
+    CommandSpec { name: "who", usage: "/who", description: "List who's in the chat" },
+    CommandSpec {
+        name: "search",
+        usage: "/search [text]",
+        description: "Search the messages (also Ctrl+F)",
+    },
+    CommandSpec { name: "clear", usage: "/clear", description: "Clear the message list" },
+    CommandSpec { name: "quit", usage: "/quit", description: "Exit the chat" },
+    CommandSpec { name: "help", usage: "/help", description: "Show this list" },
+];
+
+/// Tell messages from commands. A message can start with a slash by doubling it.
+pub fn parse(text: String) -> Input {
+    if text.starts_with("//") {
+        return Input::Message(text[1..].to_string());
+    }
+    let Some(command) = text.strip_prefix('/') else {
+        return Input::Message(text);
+    };
+
+    let (name, args) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+    let args = args.trim();
+    let usage = |name: &str| {
+        let spec = COMMANDS.iter().find(|spec| spec.name == name).expect("known command");
+        Err(format!("Usage: {}", spec.usage))
+    };
+    let command = match name {
+        "nick" if args.is_empty() || args.contains(char::is_whitespace) => usage(name),
+        "nick" => Ok(Command::Nick(args.to_string())),
+        "me" if args.is_empty() => usage(name),
+        "me" => Ok(Command::Me(args.to_string())),
+        "who" => Ok(Command::Who),
+        "search" => Ok(Command::Search(args.to_string())),
+        "clear" => Ok(Command::Clear),
+        "quit" => Ok(Command::Quit),
+        "help" => Ok(Command::Help),
+        _ => Err(format!("Unknown command /{name}. Type /help to see the commands.")),
+    };
+    Input::Command(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(text: &str) -> Result<Command, String> {
+        match parse(text.to_string()) {
+            Input::Command(command) => command,
+            Input::Message(message) => panic!("{text:?} parsed as the message {message:?}"),
+        }
+    }
+
+    #[test]
+    fn plain_text_is_a_message() {
+        assert_eq!(parse("hello".to_string()), Input::Message("hello".to_string()));
+        assert_eq!(parse(" /who".to_string()), Input::Message(" /who".to_string()));
+    }
+
+    #[test]
+    fn doubled_slash_escapes_a_message() {
+        assert_eq!(parse("//who".to_string()), Input::Message("/who".to_string()));
+        assert_eq!(parse("//".to_string()), Input::Message("/".to_string()));
+    }
+
+    #[test]
+    fn parses_commands_and_their_arguments() {
+        assert_eq!(command("/nick  bob "), Ok(Command::Nick("bob".to_string())));
+        assert_eq!(command("/me waves  hello"), Ok(Command::Me("waves  hello".to_string())));
+        assert_eq!(command("/who"), Ok(Command::Who));
+        assert_eq!(command("/search"), Ok(Command::Search(String::new())));
+        assert_eq!(command("/search foo bar"), Ok(Command::Search("foo bar".to_string())));
+        assert_eq!(command("/clear"), Ok(Command::Clear));
+        assert_eq!(command("/quit"), Ok(Command::Quit));
+        assert_eq!(command("/help"), Ok(Command::Help));
+    }
+
+    #[test]
+    fn missing_or_extra_arguments_show_the_usage() {
+        assert_eq!(command("/nick"), Err("Usage: /nick <alias>".to_string()));
+        assert_eq!(command("/nick bob smith"), Err("Usage: /nick <alias>".to_string()));
+        assert_eq!(command("/me  "), Err("Usage: /me <action>".to_string()));
+    }
+
+    #[test]
+    fn unknown_commands_are_errors() {
+        assert_eq!(
+            command("/msg bob hi"),
+            Err("Unknown command /msg. Type /help to see the commands.".to_string()),
+        );
+        // Command names are case sensitive.
+        assert!(command("/WHO").is_err());
+    }
+
+    #[test]
+    fn every_command_is_parsed() {
+        for spec in COMMANDS {
+            let error = format!("Unknown command /{}. Type /help to see the commands.", spec.name);
+            assert_ne!(command(&format!("/{}", spec.name)), Err(error));
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{command::COMMANDS, editor::LineEditor};
+
+/// Tab completion of command names and aliases in the input box.
+///
+/// Pressing Tab again cycles through the other matches, until any other key is pressed.
+#[derive(Default)]
+pub struct Completion {
+    cycle: Option<Cycle>,
+}
+
+struct Cycle {
+    // Byte offset of the word being completed
+    start: usize,
+    candidates: Vec<String>,
+    index: usize,
+}
+
+impl Completion {
+    /// Complete the word before the cursor, or move on to the next match if we just did.
+    pub fn complete(&mut self, editor: &mut LineEditor, aliases: &[String]) {
+        if let Some(cycle) = &mut self.cycle {
+            cycle.index = (cycle.index + 1) % cycle.candidates.len();
+            editor.replace_before_cursor(cycle.start, &cycle.candidates[cycle.index]);
+            return;
+        }
+
+        let (start, word) = editor.word_before_cursor();
+        let candidates = candidates(start, word, aliases);
+        let Some(first) = candidates.first() else {
+            return;
+        };
+        editor.replace_before_cursor(start, first);
+        // Nothing to cycle through with a single match.
+        if candidates.len() > 1 {
+            self.cycle = Some(Cycle { start, candidates, index: 0 });
+        }
+    }
+
+    /// Stop cycling through matches. Called for every key other than Tab.
+    pub fn reset(&mut self) {
+        self.cycle = None;
+    }
+}
+
+/// Completions for `word`, each followed by a space. Commands are completed at the start of the
+/// input, aliases anywhere (with or without a leading `@`).
+fn candidates(start: usize, word: &str, aliases: &[String]) -> Vec<String> {
+    if start == 0
+        && let Some(prefix) = word.strip_prefix('/')
+    {
+        return COMMANDS
+            .iter()
+            .filter(|spec| spec.name.starts_with(prefix))
+            .map(|spec| format!("/{} ", spec.name))
+            .collect();
+    }
+
+    let (mention, prefix) = match word.strip_prefix('@') {
+        Some(prefix) => ("@", prefix),
+        None => ("", word),
+    };
+    if prefix.is_empty() && mention.is_empty() {
+        return Vec::new();
+    }
+    let prefix = prefix.to_lowercase();
+    aliases
+        .iter()
+        .filter(|alias| alias.to_lowercase().starts_with(&prefix))
+        .map(|alias| format!("{mention}{alias} "))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases() -> Vec<String> {
+        ["alice", "Alfred", "bob"].map(str::to_string).to_vec()
+    }
+
+    fn editor(text: &str) -> LineEditor {
+        let mut editor = LineEditor::default();
+        editor.set_text(text.to_string());
+        editor
+    }
+
+    #[test]
+    fn completes_commands_at_the_start_of_the_input() {
+        assert_eq!(candidates(0, "/s", &aliases()), ["/search "]);
+        assert_eq!(candidates(0, "/", &aliases()).len(), COMMANDS.len());
+        assert!(candidates(0, "/x", &aliases()).is_empty());
+        // Only at the start, elsewhere it's just text.
+        assert!(candidates(3, "/s", &aliases()).is_empty());
+    }
+
+    #[test]
+    fn completes_aliases_ignoring_case() {
+        assert_eq!(candidates(0, "al", &aliases()), ["alice ", "Alfred "]);
+        assert_eq!(candidates(4, "B", &aliases()), ["bob "]);
+        assert_eq!(candidates(4, "@AL", &aliases()), ["@alice ", "@Alfred "]);
+        // A bare `@` lists everyone, an empty word nobody.
+        assert_eq!(candidates(0, "@", &aliases()).len(), 3);
+        assert!(candidates(0, "", &aliases()).is_empty());
+    }
+
+    #[test]
+    fn tab_cycles_through_matches_until_reset() {
+        let mut completion = Completion::default();
+        let mut editor = editor("hi al");
+
+        completion.complete(&mut editor, &aliases());
+        assert_eq!(editor.text(), "hi alice ");
+        completion.complete(&mut editor, &aliases());
+        assert_eq!(editor.text(), "hi Alfred ");
+        completion.complete(&mut editor, &aliases());
+        assert_eq!(editor.text(), "hi alice ");
+
+        // After a reset, the word before the cursor is completed afresh.
+        completion.reset();
+        editor.insert_str("b");
+        completion.complete(&mut editor, &aliases());
+        assert_eq!(editor.text(), "hi alice bob ");
+    }
+
+    #[test]
+    fn single_match_does_not_cycle() {
+        let mut completion = Completion::default();
+        let mut editor = editor("/he");
+        completion.complete(&mut editor, &aliases());
+        assert_eq!(editor.text(), "/help ");
+        // Nothing before the cursor to complete now.
+        completion.complete(&mut editor, &aliases());
+        assert_eq!(editor.text(), "/help ");
+    }
+}
@@ -0,0 +1,206 @@
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use serde::Deserialize;
+
+use crate::{
+    notify::NotificationConfig,
+    theme::{ColorOverrides, Palette, Theme},
+    transcript::{TranscriptConfig, TranscriptFormat},
+};
+
+/// Format of message timestamps, see `chrono::format::strftime`.
+const DEFAULT_TIMESTAMP_FORMAT: &str = "%H:%M";
+
+/// modrpc chat terminal client
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+    /// Path to a TOML configuration file. Defaults to `modrpc-chat/term-ui.toml` in
+    /// `$XDG_CONFIG_HOME` (or `~/.config`) if it exists. Command-line options override values
+    /// from the file.
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+
+    /// Address of the chat server's modrpc-over-TCP listener, as host:port
+    #[arg(short, long)]
+    pub server: Option<String>,
+
+    /// Alias to register as. You're asked for one if it isn't set here or in the config file.
+    #[arg(short, long)]
+    pub alias: Option<String>,
+
+    /// Color theme
+    #[arg(long)]
+    pub theme: Option<Theme>,
+
+    /// Show the time each message was sent
+    #[arg(long)]
+    pub timestamps: bool,
+
+    /// Append every message shown to this file
+    #[arg(long)]
+    pub transcript: Option<PathBuf>,
+
+    /// Format of the transcript
+    #[arg(long)]
+    pub transcript_format: Option<TranscriptFormat>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientConfig {
+    pub server: String,
+    pub alias: Option<String>,
+    pub theme: Theme,
+    pub colors: ColorOverrides,
+    pub timestamps: bool,
+    pub timestamp_format: String,
+    /// Capture the mouse for scrolling and clicking. Without it the terminal's own text
+    /// selection works as usual.
+    pub mouse: bool,
+    pub notifications: NotificationConfig,
+    pub transcript: TranscriptConfig,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            server: "127.0.0.1:9095".into(),
+            alias: None,
+            // https://no-color.org
+            theme: if std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+                Theme::Monochrome
+            } else {
+                Theme::default()
+            },
+            colors: ColorOverrides::default(),
+            timestamps: false,
+            timestamp_format: DEFAULT_TIMESTAMP_FORMAT.into(),
+            mouse: true,
+            notifications: NotificationConfig::default(),
+            transcript: TranscriptConfig::default(),
+        }
+    }
+}
+
+impl ClientConfig {
+    /// Load the config file named on the command line or the default one (if any), apply
+    /// command-line overrides and validate the result.
+    pub fn load(cli: &Cli) -> Result<Self, String> {
+        let mut config = match &cli.config {
+            Some(path) => Self::from_file(path)?,
+            None => match Self::default_path().filter(|path| path.exists()) {
+                Some(path) => Self::from_file(&path)?,
+                None => Self::default(),
+            },
+        };
+
+        if let Some(server) = &cli.server {
+            config.server = server.clone();
+        }
+        if let Some(alias) = &cli.alias {
+            config.alias = Some(alias.clone());
+        }
+        if let Some(theme) = cli.theme {
+            config.theme = theme;
+        }
+        if cli.timestamps {
+            config.timestamps = true;
+        }
+        if let Some(path) = &cli.transcript {
+            config.transcript.enabled = true;
+            config.transcript.path = Some(path.clone());
+        }
+        if let Some(format) = cli.transcript_format {
+            config.transcript.format = format;
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn default_path() -> Option<PathBuf> {
+        Some(xdg_dir("XDG_CONFIG_HOME", ".config")?.join("modrpc-chat").join("term-ui.toml"))
+    }
+
+    fn from_file(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read config file {}: {e}", path.display()))?;
+        toml::from_str(&contents)
+            .map_err(|e| format!("failed to parse config file {}: {e}", path.display()))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.server.trim().is_empty() {
+            return Err("server must not be empty".into());
+        }
+        if let Some(alias) = &self.alias {
+            chat_common::validate_alias(alias).map_err(|e| format!("invalid alias: {e}"))?;
+        }
+        if self.colors.peer_senders.as_ref().is_some_and(Vec::is_empty) {
+            return Err("colors.peer_senders must not be empty".into());
+        }
+        let timestamp_format = chrono::format::StrftimeItems::new(&self.timestamp_format);
+        if timestamp_format.clone().any(|item| item == chrono::format::Item::Error) {
+            return Err(format!("invalid timestamp_format {:?}", self.timestamp_format));
+        }
+        if self.notifications.command.as_ref().is_some_and(Vec::is_empty) {
+            return Err("notifications.command must not be empty".into());
+        }
+        Ok(())
+    }
+
+    pub fn palette(&self) -> Palette {
+        let mut palette = self.theme.palette();
+        if self.theme != Theme::Monochrome {
+            palette.override_colors(&self.colors);
+        }
+        palette
+    }
+}
+
+/// The XDG base directory named by `env_var`, falling back to `home_relative` under the home
+/// directory.
+pub fn xdg_dir(env_var: &str, home_relative: &str) -> Option<PathBuf> {
+    std::env::var_os(env_var)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(home_relative)))
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Color;
+
+    use super::*;
+
+    fn config(theme: Theme) -> ClientConfig {
+        ClientConfig {
+            theme,
+            colors: ColorOverrides {
+                own_sender: Some(Color::Red),
+                peer_senders: Some(vec![Color::Red]),
+                mention: Some(Color::Red),
+                ..ColorOverrides::default()
+            },
+            ..ClientConfig::default()
+        }
+    }
+
+    #[test]
+    fn color_overrides_apply_to_color_themes() {
+        let palette = config(Theme::Dark).palette();
+        assert_eq!(palette.own_sender.fg, Some(Color::Red));
+        assert_eq!(palette.peer_sender("alice").fg, Some(Color::Red));
+        assert_eq!(palette.mention.fg, Some(Color::Red));
+    }
+
+    #[test]
+    fn monochrome_ignores_color_overrides() {
+        let palette = config(Theme::Monochrome).palette();
+        assert_eq!(palette.own_sender.fg, None);
+        assert_eq!(palette.peer_sender("alice").fg, None);
+        assert_eq!(palette.mention.fg, None);
+    }
+}
@@ -0,0 +1,495 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{layout::Rect, style::Style, text::Line, widgets::Paragraph, Frame};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Number of sent messages remembered for Up/Down recall.
+const MAX_HISTORY_LEN: usize = 100;
+
+/// A multi-line text input with a cursor, word-wise editing and history.
+///
+/// Text is soft-wrapped to the width it was last rendered at. The cursor is a byte offset into
+/// the text that always sits on a grapheme boundary.
+#[derive(Default)]
+pub struct LineEditor {
+    text: String,
+    cursor: usize,
+    // Longest text insertions are allowed to produce, in bytes
+    max_len: Option<usize>,
+    // Previously submitted texts, oldest first
+    history: Vec<String>,
+    // Position in `history` while browsing it, with what was being typed before
+    browsing: Option<(usize, String)>,
+    // Width of the area the text was last rendered into, used for moving between rows
+    width: u16,
+    // First visual row shown, kept so the cursor stays in view
+    scroll: u16,
+}
+
+/// One visual row of wrapped text.
+struct Row {
+    start: usize,
+    end: usize,
+    // Whether the row ends a logical line, i.e. it's followed by a newline or the end of the text
+    ends_line: bool,
+}
+
+impl LineEditor {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_max_len(&mut self, max_len: Option<usize>) {
+        self.max_len = max_len;
+    }
+
+    /// Replace the text, moving the cursor to the end.
+    pub fn set_text(&mut self, text: String) {
+        self.cursor = text.len();
+        self.text = text;
+        self.browsing = None;
+    }
+
+    /// Clear the editor, returning its text.
+    pub fn take(&mut self) -> String {
+        self.cursor = 0;
+        self.browsing = None;
+        std::mem::take(&mut self.text)
+    }
+
+    /// Clear the editor, returning its text and remembering it in the history.
+    pub fn submit(&mut self) -> String {
+        let text = self.take();
+        if !text.trim().is_empty() && self.history.last() != Some(&text) {
+            if self.history.len() == MAX_HISTORY_LEN {
+                self.history.remove(0);
+            }
+            self.history.push(text.clone());
+        }
+        text
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        let mut buf = [0; 4];
+        self.insert_str(c.encode_utf8(&mut buf));
+    }
+
+    pub fn insert_newline(&mut self) {
+        self.insert_str("\n");
+    }
+
+    pub fn insert_str(&mut self, s: &str) {
+        if self.max_len.is_some_and(|max_len| self.text.len() + s.len() > max_len) {
+            return;
+        }
+        self.text.insert_str(self.cursor, s);
+        self.cursor += s.len();
+    }
+
+    /// The whitespace-delimited word ending at the cursor, with its byte offset.
+    pub fn word_before_cursor(&self) -> (usize, &str) {
+        let start = self.text[..self.cursor]
+            .rfind(char::is_whitespace)
+            .map_or(0, |i| i + self.text[i..].chars().next().map_or(1, char::len_utf8));
+        (start, &self.text[start..self.cursor])
+    }
+
+    /// Replace the text from `start` up to the cursor, leaving the cursor after the replacement.
+    pub fn replace_before_cursor(&mut self, start: usize, replacement: &str) {
+        self.text.replace_range(start..self.cursor, replacement);
+        self.cursor = start + replacement.len();
+    }
+
+    /// Apply an editing or movement key. Returns false if the key isn't one the editor handles.
+    ///
+    /// Enter isn't handled here, it's up to the caller whether it submits or starts a new line.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            // AltGr shows up as Ctrl+Alt on some platforms.
+            KeyCode::Char(c) if ctrl && alt => self.insert_char(c),
+            KeyCode::Char('a') if ctrl => self.cursor = self.line_start(),
+            KeyCode::Char('e') if ctrl => self.cursor = self.line_end(),
+            KeyCode::Char('b') if alt => self.cursor = self.prev_word(),
+            KeyCode::Char('f') if alt => self.cursor = self.next_word(),
+            KeyCode::Char('w') if ctrl => self.delete_to(self.prev_word()),
+            KeyCode::Char('d') if alt => self.delete_to(self.next_word()),
+            KeyCode::Char('u') if ctrl => self.delete_to(self.line_start()),
+            KeyCode::Char('k') if ctrl => self.delete_to(self.line_end()),
+            KeyCode::Char(_) if ctrl || alt => return false,
+            KeyCode::Char(c) => self.insert_char(c),
+            KeyCode::Backspace if ctrl || alt => self.delete_to(self.prev_word()),
+            KeyCode::Backspace => self.delete_to(self.prev_grapheme()),
+            KeyCode::Delete if ctrl || alt => self.delete_to(self.next_word()),
+            KeyCode::Delete => self.delete_to(self.next_grapheme()),
+            KeyCode::Left if ctrl => self.cursor = self.prev_word(),
+            KeyCode::Left => self.cursor = self.prev_grapheme(),
+            KeyCode::Right if ctrl => self.cursor = self.next_word(),
+            KeyCode::Right => self.cursor = self.next_grapheme(),
+            KeyCode::Home if ctrl => self.cursor = 0,
+            KeyCode::Home => self.cursor = self.line_start(),
+            KeyCode::End if ctrl => self.cursor = self.text.len(),
+            KeyCode::End => self.cursor = self.line_end(),
+            KeyCode::Up => self.move_vertically(-1),
+            KeyCode::Down => self.move_vertically(1),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Delete the text between the cursor and `other`, leaving the cursor at the start of it.
+    fn delete_to(&mut self, other: usize) {
+        let (start, end) = (self.cursor.min(other), self.cursor.max(other));
+        self.text.replace_range(start..end, "");
+        self.cursor = start;
+    }
+
+    fn prev_grapheme(&self) -> usize {
+        self.text[..self.cursor]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    fn next_grapheme(&self) -> usize {
+        self.text[self.cursor..]
+            .graphemes(true)
+            .next()
+            .map_or(self.cursor, |g| self.cursor + g.len())
+    }
+
+    /// Start of the word before the cursor, skipping any non-word characters in between.
+    fn prev_word(&self) -> usize {
+        let mut start = self.cursor;
+        let mut graphemes = self.text[..self.cursor].grapheme_indices(true).rev().peekable();
+        while let Some((i, _)) = graphemes.next_if(|(_, g)| !is_word(g)) {
+            start = i;
+        }
+        while let Some((i, _)) = graphemes.next_if(|(_, g)| is_word(g)) {
+            start = i;
+        }
+        start
+    }
+
+    /// End of the word after the cursor, skipping any non-word characters in between.
+    fn next_word(&self) -> usize {
+        let mut end = self.cursor;
+        let mut graphemes = self.text[self.cursor..].graphemes(true).peekable();
+        while let Some(g) = graphemes.next_if(|g| !is_word(g)) {
+            end += g.len();
+        }
+        while let Some(g) = graphemes.next_if(|g| is_word(g)) {
+            end += g.len();
+        }
+        end
+    }
+
+    fn line_start(&self) -> usize {
+        self.text[..self.cursor].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    fn line_end(&self) -> usize {
+        self.text[self.cursor..].find('\n').map_or(self.text.len(), |i| self.cursor + i)
+    }
+
+    /// Move the cursor up or down a visual row, keeping its column where possible. Moving past
+    /// the first or last row browses the history instead.
+    fn move_vertically(&mut self, direction: i32) {
+        // Nothing is wrapped until the editor has been rendered.
+        let width = if self.width == 0 { u16::MAX } else { self.width };
+        let rows = self.rows(width);
+        let (row, col) = self.cursor_position(&rows, width);
+        let target = row as i32 + direction;
+        if target < 0 {
+            self.history_prev();
+            return;
+        }
+        let Some(target) = rows.get(target as usize) else {
+            self.history_next();
+            return;
+        };
+
+        // Walk along the target row until we reach the column we came from.
+        let mut cursor = target.start;
+        let mut width = 0;
+        for g in self.text[target.start..target.end].graphemes(true) {
+            if width + g.width() > col {
+                break;
+            }
+            width += g.width();
+            cursor += g.len();
+        }
+        self.cursor = cursor;
+    }
+
+    fn history_prev(&mut self) {
+        let index = match &self.browsing {
+            Some((0, _)) => return,
+            Some((index, _)) => index - 1,
+            None if self.history.is_empty() => return,
+            None => {
+                self.browsing = Some((self.history.len(), self.text.clone()));
+                self.history.len() - 1
+            }
+        };
+        self.show_history(index);
+    }
+
+    fn history_next(&mut self) {
+        let Some((index, draft)) = &self.browsing else {
+            return;
+        };
+        if index + 1 < self.history.len() {
+            self.show_history(index + 1);
+        } else {
+            // Back to what was being typed before browsing
+            let draft = draft.clone();
+            self.set_text(draft);
+        }
+    }
+
+    fn show_history(&mut self, index: usize) {
+        let draft = self.browsing.take().map(|(_, draft)| draft).unwrap_or_default();
+        self.set_text(self.history[index].clone());
+        self.browsing = Some((index, draft));
+    }
+
+    /// Split the text into visual rows no wider than `width`.
+    fn rows(&self, width: u16) -> Vec<Row> {
+        let width = usize::from(width.max(1));
+        let mut rows = Vec::new();
+        let mut line_start = 0;
+        for line in self.text.split('\n') {
+            let mut start = line_start;
+            let mut row_width = 0;
+            for (i, g) in line.grapheme_indices(true) {
+                if row_width + g.width() > width && row_width > 0 {
+                    rows.push(Row { start, end: line_start + i, ends_line: false });
+                    start = line_start + i;
+                    row_width = 0;
+                }
+                row_width += g.width();
+            }
+            rows.push(Row { start, end: line_start + line.len(), ends_line: true });
+            line_start += line.len() + 1;
+        }
+        rows
+    }
+
+    /// Row and column of the cursor. A cursor at the end of a full row sits at the start of the
+    /// next one, which may be past the last row.
+    fn cursor_position(&self, rows: &[Row], width: u16) -> (usize, usize) {
+        let row = rows
+            .iter()
+            .position(|row| self.cursor < row.end || (self.cursor == row.end && row.ends_line))
+            .unwrap_or(rows.len() - 1);
+        let col = self.text[rows[row].start..self.cursor].width();
+        if col >= usize::from(width.max(1)) {
+            (row + 1, 0)
+        } else {
+            (row, col)
+        }
+    }
+
+    /// Number of rows needed to show all of the text and the cursor at this width.
+    pub fn height(&self, width: u16) -> u16 {
+        let rows = self.rows(width);
+        let (cursor_row, _) = self.cursor_position(&rows, width);
+        rows.len().max(cursor_row + 1) as u16
+    }
+
+    /// Draw the text into `area`, scrolled so the cursor is visible, and place the terminal
+    /// cursor.
+    pub fn render(&mut self, f: &mut Frame, area: Rect, style: Style) {
+        self.width = area.width;
+        // Nowhere to put the cursor, e.g. in a terminal squeezed down to nothing.
+        if area.height == 0 {
+            return;
+        }
+        let rows = self.rows(area.width);
+        let (cursor_row, cursor_col) = self.cursor_position(&rows, area.width);
+        let cursor_row = cursor_row as u16;
+
+        if cursor_row < self.scroll {
+            self.scroll = cursor_row;
+        } else if cursor_row >= self.scroll + area.height {
+            self.scroll = cursor_row + 1 - area.height;
+        }
+
+        let lines: Vec<Line> = rows
+            .iter()
+            .map(|row| Line::raw(&self.text[row.start..row.end]))
+            .collect();
+        f.render_widget(Paragraph::new(lines).style(style).scroll((self.scroll, 0)), area);
+        f.set_cursor_position((area.x + cursor_col as u16, area.y + cursor_row - self.scroll));
+    }
+}
+
+fn is_word(grapheme: &str) -> bool {
+    grapheme.chars().next().is_some_and(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor(text: &str, cursor: usize) -> LineEditor {
+        let mut editor = LineEditor::default();
+        editor.set_text(text.to_string());
+        editor.cursor = cursor;
+        editor
+    }
+
+    fn row_texts(editor: &LineEditor, width: u16) -> Vec<&str> {
+        editor.rows(width).iter().map(|row| &editor.text[row.start..row.end]).collect()
+    }
+
+    fn cursor_position(text: &str, cursor: usize, width: u16) -> (usize, usize) {
+        let editor = editor(text, cursor);
+        editor.cursor_position(&editor.rows(width), width)
+    }
+
+    fn press(editor: &mut LineEditor, code: KeyCode) {
+        assert!(editor.handle_key(KeyEvent::new(code, KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn rows_wrap_at_the_width() {
+        let editor = editor("abcdefgh", 0);
+        assert_eq!(row_texts(&editor, 3), ["abc", "def", "gh"]);
+        let ends_line: Vec<bool> = editor.rows(3).iter().map(|row| row.ends_line).collect();
+        assert_eq!(ends_line, [false, false, true]);
+    }
+
+    #[test]
+    fn rows_break_at_newlines() {
+        let editor = editor("ab\n\ncd\n", 0);
+        assert_eq!(row_texts(&editor, 10), ["ab", "", "cd", ""]);
+        assert!(editor.rows(10).iter().all(|row| row.ends_line));
+    }
+
+    #[test]
+    fn wide_grapheme_moves_to_the_next_row_rather_than_straddling_the_edge() {
+        let editor = editor("ab界c", 0);
+        assert_eq!(row_texts(&editor, 3), ["ab", "界c"]);
+        // A grapheme wider than the row gets a row to itself.
+        assert_eq!(row_texts(&editor, 1), ["a", "b", "界", "c"]);
+    }
+
+    #[test]
+    fn cursor_position_counts_columns_by_width() {
+        assert_eq!(cursor_position("ab界c", 0, 10), (0, 0));
+        assert_eq!(cursor_position("ab界c", "ab界".len(), 10), (0, 4));
+        // At the start of a wrapped row
+        assert_eq!(cursor_position("ab界c", 2, 3), (1, 0));
+        assert_eq!(cursor_position("ab\ncd", 3, 10), (1, 0));
+    }
+
+    #[test]
+    fn cursor_at_the_end_of_a_full_row_sits_on_the_next() {
+        assert_eq!(cursor_position("abcdef", 3, 3), (1, 0));
+
+        // Past the last row, which needs an extra row to show it.
+        assert_eq!(cursor_position("abc", 3, 3), (1, 0));
+        assert_eq!(editor("abc", 3).height(3), 2);
+        assert_eq!(cursor_position("ab界c", "ab界c".len(), 3), (2, 0));
+        assert_eq!(editor("ab界c", "ab界c".len()).height(3), 3);
+
+        assert_eq!(editor("ab", 2).height(3), 1);
+    }
+
+    #[test]
+    fn renders_into_an_area_with_no_height() {
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(10, 1))
+            .expect("create test terminal");
+        let mut editor = editor("abcdef", 6);
+        terminal
+            .draw(|f| editor.render(f, Rect::new(0, 0, 3, 0), Style::default()))
+            .expect("draw with no height");
+        assert_eq!(editor.scroll, 0);
+
+        // The cursor is scrolled into view once there's room for it.
+        terminal
+            .draw(|f| editor.render(f, Rect::new(0, 0, 3, 1), Style::default()))
+            .expect("draw one row");
+        assert_eq!(editor.scroll, 2);
+    }
+
+    #[test]
+    fn moving_vertically_keeps_the_column() {
+        let mut editor = editor("abcdef", 4);
+        editor.width = 3;
+        press(&mut editor, KeyCode::Up);
+        assert_eq!(editor.cursor, 1);
+        press(&mut editor, KeyCode::Down);
+        assert_eq!(editor.cursor, 4);
+    }
+
+    #[test]
+    fn moving_vertically_stops_at_the_end_of_a_shorter_row() {
+        let mut editor = editor("abcdef\ngh", 5);
+        editor.width = 3;
+        press(&mut editor, KeyCode::Down);
+        assert_eq!(editor.cursor, "abcdef\ngh".len());
+    }
+
+    #[test]
+    fn moving_vertically_doesnt_land_inside_a_wide_grapheme() {
+        let mut editor = editor("界界\nabcd", "界界\nabc".len());
+        press(&mut editor, KeyCode::Up);
+        // Column 3 is the second half of the first 界, so the cursor goes before it.
+        assert_eq!(editor.cursor, "界".len());
+    }
+
+    #[test]
+    fn browses_history_past_the_first_and_last_rows() {
+        let mut editor = LineEditor::default();
+        for text in ["one", "two", "two", "  "] {
+            editor.set_text(text.to_string());
+            editor.submit();
+        }
+        editor.set_text("draft".to_string());
+
+        press(&mut editor, KeyCode::Up);
+        assert_eq!(editor.text(), "two");
+        assert_eq!(editor.cursor, 3);
+        // Blank and repeated submissions aren't remembered.
+        press(&mut editor, KeyCode::Up);
+        assert_eq!(editor.text(), "one");
+        press(&mut editor, KeyCode::Up);
+        assert_eq!(editor.text(), "one");
+
+        press(&mut editor, KeyCode::Down);
+        assert_eq!(editor.text(), "two");
+        press(&mut editor, KeyCode::Down);
+        assert_eq!(editor.text(), "draft");
+        press(&mut editor, KeyCode::Down);
+        assert_eq!(editor.text(), "draft");
+    }
+
+    #[test]
+    fn moves_within_the_text_before_browsing_history() {
+        let mut editor = LineEditor::default();
+        editor.set_text("old".to_string());
+        editor.submit();
+        editor.set_text("a\nb".to_string());
+
+        press(&mut editor, KeyCode::Up);
+        assert_eq!((editor.text(), editor.cursor), ("a\nb", 1));
+        press(&mut editor, KeyCode::Up);
+        assert_eq!(editor.text(), "old");
+    }
+
+    #[test]
+    fn editing_a_history_entry_leaves_the_history_alone() {
+        let mut editor = LineEditor::default();
+        editor.set_text("old".to_string());
+        editor.submit();
+
+        press(&mut editor, KeyCode::Up);
+        press(&mut editor, KeyCode::Char('!'));
+        assert_eq!(editor.submit(), "old!");
+        assert_eq!(editor.history, ["old", "old!"]);
+    }
+}
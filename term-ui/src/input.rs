@@ -0,0 +1,461 @@
+use chat_client::{DeliveryStatus, OutgoingMessage};
+use chat_common::ACTION_PREFIX;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Margin, Position, Rect};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    command::{self, Command, Input, COMMANDS},
+    search::Search,
+    App, ConnectionState, Focus, LineTarget, MessageAction, Registration, Sender, ServerSearch,
+};
+
+/// Results asked for at a time when searching the server's history.
+const SERVER_SEARCH_PAGE_LEN: u32 = 20;
+
+/// Rows the message list scrolls by per notch of the mouse wheel.
+const MOUSE_SCROLL_ROWS: usize = 3;
+
+/// Columns of a message quoted when replying to it.
+const REPLY_QUOTE_WIDTH: usize = 60;
+
+// Keys, clicks and commands from the user.
+impl App {
+    /// Buttons shown under a message once it's clicked.
+    pub fn message_actions(&self, index: usize) -> Vec<MessageAction> {
+        let message = &self.messages[index];
+        let mut actions = Vec::new();
+        if !matches!(message.sender, Sender::System) {
+            actions.push(MessageAction::Reply);
+        }
+        if let Some(outgoing) = &message.outgoing
+            && matches!(outgoing.status, DeliveryStatus::Failed(_))
+        {
+            actions.push(MessageAction::Retry);
+        }
+        actions
+    }
+
+    fn run_message_action(&mut self, index: usize, action: MessageAction) {
+        match action {
+            MessageAction::Reply => self.reply(index),
+            MessageAction::Retry => {
+                self.requeue(index);
+                self.request_flush();
+            }
+        }
+        self.selected_message = None;
+    }
+
+    /// Start a reply to the message at `index`, quoting the start of it above what's been typed.
+    fn reply(&mut self, index: usize) {
+        let message = &self.messages[index];
+        let alias = match &message.sender {
+            Sender::Own => self.own_alias().unwrap_or("You"),
+            sender => sender.label(),
+        };
+        let content = message.content.strip_prefix(ACTION_PREFIX).unwrap_or(&message.content);
+        let first_line = content.lines().next().unwrap_or_default();
+        let mut quote = truncate_to_width(first_line, REPLY_QUOTE_WIDTH).to_string();
+        // The quote is a prefix of the content, so it's shorter if anything was left out.
+        if quote.len() < content.len() {
+            quote.push('…');
+        }
+        let text = format!("> {alias}: {quote}\n{}", self.input.text());
+        self.input.set_text(text);
+        self.focus = Focus::Input;
+    }
+
+    /// Add an @-mention of `alias` at the cursor.
+    fn mention(&mut self, alias: &str) {
+        // Keep it apart from a word right before the cursor.
+        let separator = if self.input.word_before_cursor().1.is_empty() { "" } else { " " };
+        self.input.insert_str(&format!("{separator}@{alias} "));
+    }
+
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) {
+        // The registration view has nothing to click.
+        if !self.is_registered() {
+            return;
+        }
+        let position = Position::new(mouse.column, mouse.row);
+        match mouse.kind {
+            MouseEventKind::ScrollUp if self.pane_areas.messages.contains(position) => {
+                self.message_scroll.scroll_up(MOUSE_SCROLL_ROWS);
+            }
+            MouseEventKind::ScrollDown if self.pane_areas.messages.contains(position) => {
+                self.message_scroll.scroll_down(MOUSE_SCROLL_ROWS);
+            }
+            MouseEventKind::Down(MouseButton::Left) => self.click(position),
+            _ => {}
+        }
+    }
+
+    fn click(&mut self, position: Position) {
+        // Row and column inside the borders of `area`, if that's where the click was
+        let inside = |area: Rect| {
+            let inner = area.inner(Margin::new(1, 1));
+            inner.contains(position).then(|| (position.y - inner.y, position.x - inner.x))
+        };
+
+        if self.pane_areas.input.contains(position) {
+            self.focus = Focus::Input;
+        } else if self.pane_areas.users.contains(position) {
+            self.focus = Focus::Users;
+            let Some((row, _)) = inside(self.pane_areas.users) else {
+                return;
+            };
+            let index = self.users_list.offset() + usize::from(row);
+            if let Some(user) = self.users.users().into_iter().nth(index) {
+                self.users_list.select(Some(index));
+                if self.own_alias() != Some(user.alias.as_str()) {
+                    self.mention(&user.alias);
+                }
+            }
+        } else if self.pane_areas.messages.contains(position) {
+            self.focus = Focus::Messages;
+            let target = inside(self.pane_areas.messages).and_then(|(row, column)| {
+                let line = self.message_scroll.line_at(row)?;
+                Some((self.message_lines.get(line)?, column))
+            });
+            match target {
+                Some((LineTarget::Message(index), _)) => {
+                    let index = *index;
+                    // Clicking the selected message again hides its actions.
+                    self.selected_message =
+                        (self.selected_message != Some(index)).then_some(index);
+                }
+                Some((LineTarget::Actions(index, buttons), column)) => {
+                    let index = *index;
+                    let action = buttons
+                        .iter()
+                        .find(|(columns, _)| columns.contains(&column))
+                        .map(|&(_, action)| action);
+                    if let Some(action) = action {
+                        self.run_message_action(index, action);
+                    }
+                }
+                None => self.selected_message = None,
+            }
+        }
+    }
+
+    /// Queue a message and send it.
+    fn send(&mut self, content: String) {
+        let message = OutgoingMessage::new(content);
+        self.outbox.push(message.clone());
+        self.push_own_message(message);
+        self.request_flush();
+    }
+
+    fn run_command(&mut self, command: Command) {
+        match command {
+            Command::Nick(alias) => {
+                if let Err(error) = chat_common::validate_alias(&alias) {
+                    self.add_notice(format!("Can't use that alias: {error}."));
+                } else if self.own_alias() == Some(alias.as_str()) {
+                    self.add_notice(format!("You're already {alias}."));
+                } else if self.renaming.is_none() && self.aliases.try_send(alias.clone()).is_ok() {
+                    self.add_notice(format!("Changing your alias to {alias}..."));
+                    self.renaming = Some(alias);
+                }
+            }
+            Command::Me(action) => self.send(format!("{ACTION_PREFIX}{action}")),
+            Command::Who => {
+                let aliases: Vec<String> =
+                    self.users.users().into_iter().map(|user| user.alias).collect();
+                let content = match aliases.len() {
+                    1 => format!("1 user in the chat: {}", aliases[0]),
+                    n => format!("{n} users in the chat: {}", aliases.join(", ")),
+                };
+                self.add_notice(content);
+            }
+            Command::Search(query) => self.open_search(query),
+            Command::Clear => {
+                // Keep our messages that haven't gone through yet, so their status can still be
+                // updated.
+                self.messages.retain(|message| {
+                    message.outgoing.as_ref()
+                        .is_some_and(|outgoing| outgoing.status != DeliveryStatus::Delivered)
+                });
+                // Matches and the selection are tracked by message index, so they'd point at the
+                // wrong messages.
+                self.search = None;
+                self.selected_message = None;
+                self.message_scroll.scroll_to_bottom();
+            }
+            Command::Quit => self.quit = true,
+            Command::Help => {
+                self.add_notice("Commands:".to_string());
+                for spec in COMMANDS {
+                    self.add_notice(format!("  {:<24}{}", spec.usage, spec.description));
+                }
+                self.add_notice(
+                    "Tab completes commands and aliases. Start a message with // to send one \
+                    beginning with a slash. Ctrl+F searches the messages, or / while scrolled up. \
+                    Click a message to reply to it, or a user to mention them."
+                        .to_string(),
+                );
+            }
+        }
+    }
+
+    pub fn handle_input(&mut self, key: KeyEvent) {
+        match &self.registration {
+            Registration::Registered(_) => self.handle_chat_input(key),
+            Registration::ChoosingAlias { .. } => self.handle_alias_input(key),
+            // Wait for the server to respond
+            Registration::Registering(_) => {}
+        }
+    }
+
+    fn handle_alias_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter if !self.alias_input.text().trim().is_empty() => {
+                let alias = self.alias_input.text().trim().to_string();
+                if self.aliases.try_send(alias.clone()).is_ok() {
+                    self.registration = Registration::Registering(alias);
+                }
+            }
+            _ => {
+                self.alias_input.handle_key(key);
+            }
+        }
+    }
+
+    fn open_search(&mut self, query: String) {
+        let mut search = Search::new(query);
+        search.jump_to_newest(&self.message_texts());
+        self.search = Some(search);
+    }
+
+    /// Search the server's history for the words in the search box, or get the next page of
+    /// results if that's what we searched for last. `from:alias` limits it to one user.
+    fn search_server(&mut self) {
+        let Some(search) = &self.search else {
+            return;
+        };
+        let text = search.query.text().trim().to_string();
+        if !self.can_search_server() {
+            self.add_notice("This server doesn't support searching its history.".to_string());
+            return;
+        }
+        if self.connection_state != ConnectionState::Online {
+            self.add_notice("Can't search the server while disconnected.".to_string());
+            return;
+        }
+
+        let before_id = match &self.server_search {
+            Some(server_search) if server_search.pending => return,
+            Some(server_search) if server_search.query == text => {
+                match server_search.next_before_id {
+                    Some(next_before_id) => Some(next_before_id),
+                    None => {
+                        self.add_notice(format!("No more server results for \"{text}\"."));
+                        return;
+                    }
+                }
+            }
+            _ => None,
+        };
+        let mut alias = None;
+        let mut words = Vec::new();
+        for word in text.split_whitespace() {
+            match word.strip_prefix("from:") {
+                Some(from) => alias = Some(from.to_string()),
+                None => words.push(word),
+            }
+        }
+        let request = chat_modrpc::SearchMessagesRequest {
+            query: words.join(" "),
+            alias,
+            after_ms: None,
+            before_ms: None,
+            before_id,
+            limit: SERVER_SEARCH_PAGE_LEN,
+        };
+        if self.searches.try_send(request).is_ok() {
+            self.server_search = Some(ServerSearch {
+                query: text,
+                next_before_id: None,
+                pending: true,
+            });
+        }
+    }
+
+    fn message_texts(&self) -> Vec<&str> {
+        self.messages.iter().map(|message| message.content.as_str()).collect()
+    }
+
+    fn handle_chat_input(&mut self, key: KeyEvent) {
+        if self.search.is_some() {
+            self.handle_search_input(key);
+            return;
+        }
+        let handled = match self.focus {
+            Focus::Input => false,
+            Focus::Messages => self.handle_messages_key(key),
+            Focus::Users => self.handle_users_key(key),
+        };
+        if handled {
+            return;
+        }
+        self.focus = Focus::Input;
+        if key.code != KeyCode::Tab {
+            self.completion.reset();
+        }
+        match key.code {
+            KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.open_search(String::new());
+            }
+            // At the bottom of the list a slash starts a command, but while reading back through
+            // the messages it's more likely meant as a search.
+            KeyCode::Char('/')
+                if self.input.text().is_empty() && self.message_scroll.is_scrolled_up() =>
+            {
+                self.open_search(String::new());
+            }
+            // Plenty of terminals can't report Shift+Enter, so Alt+Enter works too.
+            KeyCode::Enter if key.modifiers.intersects(KeyModifiers::SHIFT | KeyModifiers::ALT) => {
+                self.input.insert_newline();
+            }
+            KeyCode::Enter if !self.input.text().trim().is_empty() => {
+                match command::parse(self.input.submit()) {
+                    Input::Message(content) => self.send(content),
+                    Input::Command(Ok(command)) => self.run_command(command),
+                    Input::Command(Err(error)) => self.add_notice(error),
+                }
+            }
+            KeyCode::Tab => {
+                let aliases: Vec<String> =
+                    self.users.users().into_iter().map(|user| user.alias).collect();
+                self.completion.complete(&mut self.input, &aliases);
+            }
+            KeyCode::PageUp => self.message_scroll.page_up(),
+            KeyCode::PageDown => self.message_scroll.page_down(),
+            KeyCode::Up if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.message_scroll.scroll_up(1);
+            }
+            KeyCode::Down if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.message_scroll.scroll_down(1);
+            }
+            // Home and End move within the input while there's something in it.
+            KeyCode::Home
+                if key.modifiers.contains(KeyModifiers::CONTROL) || self.input.text().is_empty() =>
+            {
+                self.message_scroll.scroll_to_top();
+            }
+            KeyCode::End
+                if key.modifiers.contains(KeyModifiers::CONTROL) || self.input.text().is_empty() =>
+            {
+                self.message_scroll.scroll_to_bottom();
+            }
+            _ => {
+                self.input.handle_key(key);
+            }
+        }
+    }
+
+    /// Keys while the message list has the focus. Returns false for keys meant for the input.
+    fn handle_messages_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Up => self.message_scroll.scroll_up(1),
+            KeyCode::Down => self.message_scroll.scroll_down(1),
+            KeyCode::PageUp => self.message_scroll.page_up(),
+            KeyCode::PageDown => self.message_scroll.page_down(),
+            KeyCode::Home => self.message_scroll.scroll_to_top(),
+            KeyCode::End => self.message_scroll.scroll_to_bottom(),
+            KeyCode::Enter if self.selected_message.is_some() => {
+                let index = self.selected_message.take().unwrap_or_default();
+                self.reply(index);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Keys while the user list has the focus. Returns false for keys meant for the input.
+    fn handle_users_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Up => self.users_list.select_previous(),
+            KeyCode::Down => self.users_list.select_next(),
+            KeyCode::Enter => {
+                let selected = self.users_list.selected().and_then(|index| {
+                    self.users.users().into_iter().nth(index)
+                });
+                if let Some(user) = selected {
+                    self.mention(&user.alias);
+                }
+                self.focus = Focus::Input;
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Keys while searching. Esc, which closes the search, is handled in `run_app`.
+    fn handle_search_input(&mut self, key: KeyEvent) {
+        let Some(search) = &mut self.search else {
+            return;
+        };
+        let texts: Vec<&str> =
+            self.messages.iter().map(|message| message.content.as_str()).collect();
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            // The search starts from the bottom, so Enter carries on back through older messages.
+            KeyCode::Enter if key.modifiers.intersects(KeyModifiers::SHIFT | KeyModifiers::ALT) => {
+                search.jump_newer(&texts);
+            }
+            KeyCode::Enter | KeyCode::Up => search.jump_older(&texts),
+            KeyCode::Char('f') if ctrl => search.jump_older(&texts),
+            KeyCode::Down => search.jump_newer(&texts),
+            KeyCode::Char('r') if alt => {
+                search.toggle_regex();
+                search.jump_to_newest(&texts);
+            }
+            KeyCode::Tab => search.toggle_filter(),
+            KeyCode::Char('s') if ctrl => self.search_server(),
+            KeyCode::PageUp => self.message_scroll.page_up(),
+            KeyCode::PageDown => self.message_scroll.page_down(),
+            _ => {
+                let query = search.query.text().to_string();
+                if search.query.handle_key(key) && search.query.text() != query {
+                    search.compile();
+                    search.jump_to_newest(&texts);
+                }
+            }
+        }
+    }
+}
+
+/// The longest run of whole graphemes at the start of `text` that fits in `width` columns.
+fn truncate_to_width(text: &str, width: usize) -> &str {
+    let mut end = 0;
+    let mut used = 0;
+    for g in text.graphemes(true) {
+        used += g.width();
+        if used > width {
+            break;
+        }
+        end += g.len();
+    }
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_to_width_counts_columns_and_keeps_graphemes_whole() {
+        assert_eq!(truncate_to_width("hello", 10), "hello");
+        assert_eq!(truncate_to_width("hello", 3), "hel");
+        // Wide characters take two columns, and one that would straddle the limit is left out.
+        assert_eq!(truncate_to_width("日本語", 4), "日本");
+        assert_eq!(truncate_to_width("日本語", 5), "日本");
+        // A letter with a combining accent is one grapheme.
+        assert_eq!(truncate_to_width("e\u{301}e\u{301}", 1), "e\u{301}");
+    }
+}
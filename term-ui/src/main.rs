@@ -1,27 +1,89 @@
 use std::{
-    cell::RefCell,
-    collections::HashMap,
     io::{self, Stdout},
+    ops::Range,
     rc::Rc,
     time::Duration,
 };
 
-use crossterm::event::{EventStream, KeyCode, KeyEventKind};
+use chat_client::{
+    ChatError, ChatEvent, ChatSession, Credentials, DeliveryStatus, Outbox, OutgoingMessage,
+    UserDirectory,
+};
+use chat_common::ACTION_PREFIX;
+use chrono::{DateTime, Local};
+use clap::Parser;
+use crossterm::{
+    event::{
+        DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture, Event,
+        EventStream, KeyCode, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
+        MouseEventKind, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
+    style::Print,
+    terminal::supports_keyboard_enhancement,
+};
 use modrpc_executor::ModrpcExecutor;
-use ratatui::{
-    backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
-    text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
-    Frame, Terminal,
+use ratatui::{backend::CrosstermBackend, layout::Rect, widgets::ListState, Terminal};
+
+use crate::{
+    completion::Completion,
+    config::{Cli, ClientConfig},
+    editor::LineEditor,
+    notify::Notifier,
+    scroll::ScrollView,
+    search::Search,
+    storage::FileStorage,
+    theme::Palette,
+    transcript::{EntryKind, Transcript},
 };
 
-struct ChatClientState {
-    users: RefCell<HashMap<u64, String>>,
+mod command;
+mod completion;
+mod config;
+mod editor;
+mod input;
+mod notify;
+mod render;
+mod scroll;
+mod search;
+mod storage;
+mod theme;
+mod transcript;
+
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_millis(500);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+const DEFAULT_SIDEBAR_WIDTH: u16 = 24;
+const MIN_SIDEBAR_WIDTH: u16 = 12;
+const MAX_SIDEBAR_WIDTH: u16 = 60;
+
+#[derive(Clone, Copy, PartialEq)]
+enum ConnectionState {
+    Connecting,
+    Online,
+    Reconnecting,
+}
+
+enum UiEvent {
+    ConnectionState(ConnectionState),
+    ServerInfo(chat_modrpc::ServerInfo),
+    /// Everyone registered with the server, sent on connecting and registering. Kept up to date
+    /// from chat events in between.
+    Users(UserDirectory),
+    /// We're registered (or our session was resumed) under this alias.
+    Registered(String),
+    /// The server's response to a search of its history.
+    SearchResults(Result<chat_modrpc::SearchMessagesSuccess, ChatError>),
 }
 
 fn main() {
+    let cli = Cli::parse();
+    let config = match ClientConfig::load(&cli) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(1);
+        }
+    };
     let mut ex = modrpc_executor::TokioExecutor::new();
     let spawner = ex.spawner();
     let _guard = ex.tokio_runtime().enter();
@@ -29,113 +91,281 @@ fn main() {
     let (rt, rt_shutdown) = modrpc::RuntimeHandle::single_threaded(&mut ex);
     let buffer_pool = modrpc::HeapBufferPool::new(65536, 4, 4);
 
-    let mut terminal = ratatui::init();
-    let result = ex.run_until(async {
-        // Connect to the chat server
-        let stream = tokio::net::TcpStream::connect("127.0.0.1:9095").await
-            .expect("tcp stream connect");
-        let connection =
-            modrpc::tcp_connect::<chat_modrpc::ChatClientRole>(
-                &rt,
-                buffer_pool.clone(),
-                modrpc::WorkerId::local(),
-                chat_modrpc::ChatClientConfig { },
-                stream,
-            )
-            .await
-            .unwrap();
-        let modrpc::TcpConnection { endpoint, init, role_handle: chat_client, .. } = connection;
-
-        let mut users = HashMap::new();
-        for user in init.users {
-            users.insert(user.endpoint, user.alias);
-        }
-
-        let state = Rc::new(ChatClientState {
-            users: RefCell::new(users),
-        });
+    // Messages we haven't been able to send yet, kept across restarts
+    let outbox = Rc::new(Outbox::new(FileStorage::new(FileStorage::default_path())));
 
-        // Subscriber to listen for new user registrations
-        chat_client.register.subscribe({
-            let state = state.clone();
-            async move |source, request, response_waiter| {
-                let Ok(response) = response_waiter.wait().await else {
-                    // Failed to decode response
-                    return;
-                };
-                if response.is_ok() {
-                    let Ok(alias) = request.alias() else {
-                        return;
-                    };
-                    state.users.borrow_mut().insert(source.endpoint, alias.to_string());
-                }
+    // Opened up front, since there's nowhere to report errors once the UI is up
+    let transcript = if config.transcript.enabled {
+        match Transcript::open(&config.transcript) {
+            Ok(transcript) => Some(transcript),
+            Err(e) => {
+                let path = config.transcript.path();
+                eprintln!("error: failed to open transcript {}: {e}", path.display());
+                std::process::exit(1);
             }
-        });
-
-        // Subscriber to listen for messages sent by peers and relay them to the UI.
-        let (recv_messages_tx, recv_messages_rx) = localq::mpsc::channel(16);
-        chat_client.send_message.subscribe({
-            let state = state.clone();
-            async move |source, request, response_waiter| {
-                if source == endpoint {
-                    // Ignore requests from self
-                    return;
-                }
-                let Ok(response) = response_waiter.wait().await else {
-                    // Failed to decode response
-                    return;
-                };
-                if response.is_ok() {
-                    let users = state.users.borrow();
-                    let alias = users.get(&source.endpoint)
-                        .map(AsRef::as_ref)
-                        .unwrap_or("<unknown>");
-                    let content = request.content().unwrap();
-                    let _ = recv_messages_tx.send(Message {
-                        sender: alias.into(),
-                        content: content.into(),
-                    })
-                    .await;
-                }
-            }
-        });
-
-        // Spawn a task to relay messages from the UI to the server.
-        let (send_messages_tx, mut send_messages_rx) = localq::mpsc::channel(16);
-        spawner.spawn(async move {
-            while let Ok(content) = send_messages_rx.recv().await {
-                let _response = chat_client.send_message.call(chat_modrpc::SendMessageRequest {
-                    content,
-                })
-                .await;
-            }
-        })
-        .expect("spawn send messages");
+        }
+    } else {
+        None
+    };
 
-        let _response = chat_client.register.call(chat_modrpc::RegisterRequestGen {
-            alias: "anonymous-rustacean",
-        })
-        .await;
+    let mut terminal = ratatui::init();
+    let terminal_modes = TerminalModes {
+        // Needed to tell Shift+Enter apart from Enter, on terminals that support it
+        keyboard_enhancement: supports_keyboard_enhancement().unwrap_or(false),
+        mouse_capture: config.mouse,
+        window_title: config.notifications.title,
+    };
+    terminal_modes.enable();
+    // The hook installed by `ratatui::init` only undoes what it set up itself.
+    let panic_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        terminal_modes.restore();
+        panic_hook(info);
+    }));
+    let result = ex.run_until(async {
+        // Spawn a task to keep us connected to the chat server and relay messages between the UI
+        // and the server.
+        let (ui_events_tx, ui_events_rx) = localq::mpsc::channel(16);
+        let (chat_events_tx, chat_events_rx) = localq::mpsc::channel(16);
+        // Nudges the connection task to flush the outbox. One pending nudge is enough.
+        let (flush_requests_tx, flush_requests_rx) = localq::mpsc::channel(1);
+        // Aliases entered in the registration view or with /nick
+        let (aliases_tx, aliases_rx) = localq::mpsc::channel(1);
+        // Searches of the server's history, one at a time
+        let (searches_tx, searches_rx) = localq::mpsc::channel(1);
+        spawner.spawn(run_connection(
+            rt.clone(),
+            buffer_pool.clone(),
+            config.server.clone(),
+            config.alias.clone(),
+            ui_events_tx,
+            chat_events_tx,
+            outbox.clone(),
+            flush_requests_rx,
+            aliases_rx,
+            searches_rx,
+        ))
+        .expect("spawn connection");
 
         // Run the UI
-        let app = App::new(send_messages_tx);
-        let result = run_app(&mut terminal, app, recv_messages_rx).await;
+        let app = App::new(
+            config.palette(),
+            Notifier::new(config.notifications),
+            transcript,
+            config.timestamps,
+            config.timestamp_format,
+            config.alias,
+            outbox,
+            flush_requests_tx,
+            aliases_tx,
+            searches_tx,
+        );
+        let result = run_app(&mut terminal, app, ui_events_rx, chat_events_rx).await;
 
         rt_shutdown.shutdown().await;
 
         result
     });
-    ratatui::restore();
+    terminal_modes.restore();
 
     if let Err(e) = result {
         println!("{e:?}");
     }
 }
 
+/// Terminal features used on top of what `ratatui::init` sets up.
+#[derive(Clone, Copy)]
+struct TerminalModes {
+    keyboard_enhancement: bool,
+    mouse_capture: bool,
+    // Whether we change the window title
+    window_title: bool,
+}
+
+impl TerminalModes {
+    fn enable(&self) {
+        let mut stdout = io::stdout();
+        if self.keyboard_enhancement {
+            let _ = crossterm::execute!(
+                stdout,
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES),
+            );
+        }
+        if self.mouse_capture {
+            let _ = crossterm::execute!(stdout, EnableMouseCapture);
+        }
+        // Lets us hold off on notifications while the user is looking at the chat
+        let _ = crossterm::execute!(stdout, EnableFocusChange);
+        if self.window_title {
+            // Save the title on the terminal's title stack (XTWINOPS), to put it back on exit.
+            let _ = crossterm::execute!(stdout, Print("\x1b[22;0t"));
+        }
+    }
+
+    /// Put the terminal back the way we found it, on exit or panic.
+    fn restore(&self) {
+        let mut stdout = io::stdout();
+        if self.window_title {
+            let _ = crossterm::execute!(stdout, Print("\x1b[23;0t"));
+        }
+        let _ = crossterm::execute!(stdout, DisableFocusChange);
+        if self.mouse_capture {
+            let _ = crossterm::execute!(stdout, DisableMouseCapture);
+        }
+        if self.keyboard_enhancement {
+            let _ = crossterm::execute!(stdout, PopKeyboardEnhancementFlags);
+        }
+        ratatui::restore();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_connection(
+    rt: modrpc::RuntimeHandle,
+    buffer_pool: modrpc::HeapBufferPool,
+    server_addr: String,
+    // Alias to register as, once known
+    mut alias: Option<String>,
+    ui_events: localq::mpsc::Sender<UiEvent>,
+    chat_events: localq::mpsc::Sender<ChatEvent>,
+    outbox: Rc<Outbox>,
+    mut flush_requests: localq::mpsc::Receiver<()>,
+    mut aliases: localq::mpsc::Receiver<String>,
+    mut searches: localq::mpsc::Receiver<chat_modrpc::SearchMessagesRequest>,
+) {
+    let mut connection_state = ConnectionState::Connecting;
+    let mut reconnect_delay = INITIAL_RECONNECT_DELAY;
+    // Lets us resume our session after reconnecting
+    let mut credentials: Option<Credentials> = None;
+
+    loop {
+        let _ = ui_events.send(UiEvent::ConnectionState(connection_state)).await;
+
+        // Connect to the chat server
+        let connection = match tokio::net::TcpStream::connect(&server_addr).await {
+            Ok(stream) => {
+                modrpc::tcp_connect::<chat_modrpc::ChatClientRole>(
+                    &rt,
+                    buffer_pool.clone(),
+                    buffer_pool.clone(),
+                    modrpc::WorkerId::local(),
+                    chat_modrpc::ChatClientConfig { },
+                    stream,
+                )
+                .await
+                .ok()
+            }
+            Err(_) => None,
+        };
+        let Some(connection) = connection else {
+            connection_state = ConnectionState::Reconnecting;
+            tokio::time::sleep(reconnect_delay).await;
+            reconnect_delay = std::cmp::min(reconnect_delay * 2, MAX_RECONNECT_DELAY);
+            continue;
+        };
+        reconnect_delay = INITIAL_RECONNECT_DELAY;
+        let modrpc::TcpConnection {
+            endpoint,
+            transport,
+            init,
+            role_handle: chat_client,
+        } = connection;
+
+        let session = ChatSession::new(endpoint, init, chat_client, chat_events.clone());
+        let _ = ui_events.send(UiEvent::ServerInfo(session.server_info().clone())).await;
+        let users = session.users().clone();
+        let _ = ui_events.send(UiEvent::Users(users)).await;
+
+        connection_state = ConnectionState::Online;
+        let _ = ui_events.send(UiEvent::ConnectionState(connection_state)).await;
+
+        // Register, then send queued messages until the connection is lost. Anything cut off
+        // mid-send stays in the outbox and is sent again on the next connection.
+        let relay = async {
+            let mut registered = match (credentials.take(), &alias) {
+                // The server holds on to our session if it can't resume it right now.
+                (Some(credentials), _) => loop {
+                    match session.reestablish(credentials.clone()).await {
+                        Ok(()) => break true,
+                        Err(error @ ChatError::ResumeSession(_)) => {
+                            let retry_after = error.retry_after().unwrap_or(reconnect_delay);
+                            tokio::time::sleep(retry_after).await;
+                        }
+                        Err(_) => break false,
+                    }
+                },
+                (None, Some(alias)) => session.register(alias).await.is_ok(),
+                (None, None) => false,
+            };
+            // Failures are reported to the UI as chat events, and the UI asks for another alias.
+            while !registered {
+                let Ok(next_alias) = aliases.recv().await else {
+                    // The UI has exited.
+                    return;
+                };
+                registered = session.register(&next_alias).await.is_ok();
+                alias = Some(next_alias);
+            }
+            credentials = session.credentials();
+            let users = session.users().clone();
+            let _ = ui_events.send(UiEvent::Users(users)).await;
+            if let Some(credentials) = &credentials {
+                let _ = ui_events.send(UiEvent::Registered(credentials.alias.clone())).await;
+            }
+
+            loop {
+                if let Err(error) = session.flush(&outbox).await
+                    && let Some(retry_after) = error.retry_after()
+                {
+                    tokio::time::sleep(retry_after).await;
+                    continue;
+                }
+                tokio::select! {
+                    request = flush_requests.recv() => {
+                        if request.is_err() {
+                            // The UI has exited.
+                            return;
+                        }
+                    }
+                    next_alias = aliases.recv() => {
+                        let Ok(next_alias) = next_alias else {
+                            return;
+                        };
+                        // Renaming. Failures are reported as chat events and we stay registered
+                        // under the old alias.
+                        if session.register(&next_alias).await.is_ok() {
+                            alias = Some(next_alias.clone());
+                            credentials = session.credentials();
+                            let users = session.users().clone();
+                            let _ = ui_events.send(UiEvent::Users(users)).await;
+                            let _ = ui_events.send(UiEvent::Registered(next_alias)).await;
+                        }
+                    }
+                    request = searches.recv() => {
+                        let Ok(request) = request else {
+                            return;
+                        };
+                        let results = session.search(request).await;
+                        let _ = ui_events.send(UiEvent::SearchResults(results)).await;
+                    }
+                }
+            }
+        };
+        tokio::select! {
+            _ = relay => return,
+            _ = session.track_departures() => {}
+            _ = transport.shutdown_signal.wait() => {}
+        }
+
+        connection_state = ConnectionState::Reconnecting;
+    }
+}
+
 async fn run_app(
     terminal: &mut Terminal<CrosstermBackend<Stdout>>,
     mut app: App,
-    mut recv_messages: localq::mpsc::Receiver<Message>,
+    mut ui_events: localq::mpsc::Receiver<UiEvent>,
+    mut chat_events: localq::mpsc::Receiver<ChatEvent>,
 ) -> io::Result<()> {
     use futures::StreamExt;
 
@@ -147,141 +377,599 @@ async fn run_app(
     loop {
         tokio::select! {
             _ = interval.tick() => {
-                terminal.draw(|f| ui(f, &mut app))?;
+                app.update_unread();
+                terminal.draw(|f| render::draw(f, &mut app))?;
             },
-            Ok(message) = recv_messages.recv() => {
-                app.add_message(message.sender, message.content);
+            Ok(event) = ui_events.recv() => {
+                match event {
+                    UiEvent::ConnectionState(state) => app.set_connection_state(state),
+                    UiEvent::ServerInfo(server_info) => app.set_server_info(server_info),
+                    UiEvent::Users(users) => app.users = users,
+                    UiEvent::Registered(alias) => app.registered(alias),
+                    UiEvent::SearchResults(results) => app.server_search_results(results),
+                }
             },
+            Ok(event) = chat_events.recv() => app.handle_chat_event(event),
             Some(Ok(event)) = events.next() => {
-                if let Some(key) = event.as_key_press_event() {
-                    if key.kind == KeyEventKind::Press {
-                        match key.code {
-                            KeyCode::Esc => return Ok(()), // Quit on Esc
-                            _ => app.handle_input(key.code),
+                if let Event::FocusGained | Event::FocusLost = event {
+                    app.notifier.set_focused(event == Event::FocusGained);
+                } else if let Event::Mouse(mouse) = event {
+                    // Every move of the pointer is reported, but there's nothing to hover over.
+                    if mouse.kind == MouseEventKind::Moved {
+                        continue;
+                    }
+                    app.handle_mouse(mouse);
+                } else if let Some(key) = event.as_key_press_event()
+                    && key.kind == KeyEventKind::Press
+                {
+                    match key.code {
+                        KeyCode::Esc if app.search.is_some() => app.search = None,
+                        KeyCode::Esc if app.focus != Focus::Input => app.focus = Focus::Input,
+                        KeyCode::Esc => return Ok(()), // Quit on Esc
+                        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.retry_failed();
+                        }
+                        KeyCode::F(2) => app.show_sidebar = !app.show_sidebar,
+                        KeyCode::F(3) => app.show_timestamps = !app.show_timestamps,
+                        // The sidebar is on the right, so moving its edge left widens it.
+                        KeyCode::Left if key.modifiers.contains(KeyModifiers::ALT) => {
+                            app.resize_sidebar(1);
+                        }
+                        KeyCode::Right if key.modifiers.contains(KeyModifiers::ALT) => {
+                            app.resize_sidebar(-1);
                         }
+                        _ => app.handle_input(key),
+                    }
+                    if app.quit {
+                        return Ok(());
                     }
                 }
-                terminal.draw(|f| ui(f, &mut app))?;
+                app.update_unread();
+                terminal.draw(|f| render::draw(f, &mut app))?;
             },
         }
     }
 }
 
+/// Who a message is from.
+enum Sender {
+    /// Notices from the client itself
+    System,
+    Own,
+    Peer(String),
+}
+
+impl Sender {
+    /// Name shown in front of the message.
+    fn label(&self) -> &str {
+        match self {
+            Sender::System => "System",
+            Sender::Own => "You",
+            Sender::Peer(alias) => alias,
+        }
+    }
+}
+
 struct Message {
-    sender: String,
+    sender: Sender,
     content: String,
+    // When the server logged it if we know, otherwise when we got it
+    time: DateTime<Local>,
+    // Set on messages we sent
+    outgoing: Option<Outgoing>,
+}
+
+struct Outgoing {
+    id: String,
+    status: DeliveryStatus,
+}
+
+/// The last search of the server's history, kept to fetch more results.
+struct ServerSearch {
+    query: String,
+    // Where the next page starts, None once there are no more results
+    next_before_id: Option<u64>,
+    // Waiting for the server to respond
+    pending: bool,
+}
+
+enum Registration {
+    /// Asking for an alias, with the reason the last attempt failed if there was one
+    ChoosingAlias { error: Option<ChatError> },
+    /// Waiting for the server to accept an alias
+    Registering(String),
+    Registered(String),
+}
+
+/// Pane the arrow keys act on, chosen by clicking. Typing always goes to the input, taking the
+/// focus back to it.
+#[derive(Clone, Copy, PartialEq)]
+enum Focus {
+    Input,
+    Messages,
+    Users,
+}
+
+/// Where the panes of the chat view were last drawn, to tell what the mouse is over. Empty while
+/// a pane isn't shown.
+#[derive(Default)]
+struct PaneAreas {
+    messages: Rect,
+    input: Rect,
+    users: Rect,
+}
+
+/// What a line in the message list was drawn for.
+enum LineTarget {
+    Message(usize),
+    /// Buttons under the selected message, with the columns each one spans
+    Actions(usize, Vec<(Range<u16>, MessageAction)>),
+}
+
+#[derive(Clone, Copy)]
+enum MessageAction {
+    /// Quote the message in the input
+    Reply,
+    /// Queue one of our failed messages again
+    Retry,
 }
 
 struct App {
+    palette: Palette,
+    notifier: Notifier,
+    transcript: Option<Transcript>,
+    show_timestamps: bool,
+    timestamp_format: String,
     messages: Vec<Message>,
-    message_list_state: ListState,
-    messages_rect: Rect,
-    input: String,
-    send_messages: localq::mpsc::Sender<String>,
+    message_scroll: ScrollView,
+    input: LineEditor,
+    outbox: Rc<Outbox>,
+    flush_requests: localq::mpsc::Sender<()>,
+    // Shown until we're registered, then the chat is
+    registration: Registration,
+    alias_input: LineEditor,
+    aliases: localq::mpsc::Sender<String>,
+    // Alias asked for with /nick, until the server responds
+    renaming: Option<String>,
+    completion: Completion,
+    // Open while searching the message list, in place of the input
+    search: Option<Search>,
+    server_search: Option<ServerSearch>,
+    searches: localq::mpsc::Sender<chat_modrpc::SearchMessagesRequest>,
+    // Set by /quit
+    quit: bool,
+    connection_state: ConnectionState,
+    server_info: Option<chat_modrpc::ServerInfo>,
+    users: UserDirectory,
+    show_sidebar: bool,
+    sidebar_width: u16,
+    focus: Focus,
+    pane_areas: PaneAreas,
+    // What each line of the message list is, as last drawn
+    message_lines: Vec<LineTarget>,
+    // Clicked on, showing its actions
+    selected_message: Option<usize>,
+    users_list: ListState,
 }
 
 impl App {
-    fn new(send_messages: localq::mpsc::Sender<String>) -> App {
-        App {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        palette: Palette,
+        notifier: Notifier,
+        transcript: Option<Transcript>,
+        show_timestamps: bool,
+        timestamp_format: String,
+        // Alias from the command line or config file, registered as straight away
+        alias: Option<String>,
+        outbox: Rc<Outbox>,
+        flush_requests: localq::mpsc::Sender<()>,
+        aliases: localq::mpsc::Sender<String>,
+        searches: localq::mpsc::Sender<chat_modrpc::SearchMessagesRequest>,
+    ) -> App {
+        let mut app = App {
+            palette,
+            notifier,
+            transcript,
+            show_timestamps,
+            timestamp_format,
             messages: vec![
                 Message {
-                    sender: "System".to_string(),
+                    sender: Sender::System,
                     content: "Welcome to the modrpc chat!".to_string(),
+                    time: Local::now(),
+                    outgoing: None,
                 },
                 Message {
-                    sender: "System".to_string(),
+                    sender: Sender::System,
                     content: "Type your message below and press Enter.".to_string(),
+                    time: Local::now(),
+                    outgoing: None,
+                },
+                Message {
+                    sender: Sender::System,
+                    content: "Press F2 to show or hide the user list, Alt+Left/Right to resize it, \
+                        F3 to show or hide timestamps."
+                        .to_string(),
+                    time: Local::now(),
+                    outgoing: None,
                 },
                 Message {
-                    sender: "System".to_string(),
+                    sender: Sender::System,
                     content: "Press Esc to exit.".to_string(),
+                    time: Local::now(),
+                    outgoing: None,
                 },
             ],
-            message_list_state: ListState::default(),
-            messages_rect: Rect::default(),
-            input: String::new(),
-            send_messages,
+            message_scroll: ScrollView::default(),
+            input: LineEditor::default(),
+            outbox,
+            flush_requests,
+            registration: match alias {
+                Some(alias) => Registration::Registering(alias),
+                None => Registration::ChoosingAlias { error: None },
+            },
+            alias_input: LineEditor::default(),
+            aliases,
+            renaming: None,
+            completion: Completion::default(),
+            search: None,
+            server_search: None,
+            searches,
+            quit: false,
+            connection_state: ConnectionState::Connecting,
+            server_info: None,
+            users: UserDirectory::default(),
+            show_sidebar: true,
+            sidebar_width: DEFAULT_SIDEBAR_WIDTH,
+            focus: Focus::Input,
+            pane_areas: PaneAreas::default(),
+            message_lines: Vec::new(),
+            selected_message: None,
+            users_list: ListState::default(),
+        };
+        app.notifier.set_title("modrpc chat".to_string());
+        // Messages left over from last time are sent once we're connected.
+        for message in app.outbox.messages() {
+            app.push_own_message(message);
         }
+        app
     }
 
-    fn add_message(&mut self, sender: String, content: String) {
-        self.messages.push(Message { sender, content });
-        // Scroll to the bottom when a new message is added
-        *self.message_list_state.offset_mut() =
-            self.messages.len().saturating_sub(self.messages_rect.height as usize);
+    fn set_server_info(&mut self, server_info: chat_modrpc::ServerInfo) {
+        if self.server_info.is_none() {
+            self.notifier.set_title(server_info.name.clone());
+            self.add_notice(format!("Connected to {}.", server_info.name));
+            if !server_info.motd.is_empty() {
+                self.add_notice(server_info.motd.clone());
+            }
+        }
+        self.server_info = Some(server_info);
+        // Don't let the input grow past what the server will accept.
+        self.input.set_max_len(self.max_message_len());
     }
 
-    fn handle_input(&mut self, key_code: KeyCode) {
-        match key_code {
-            KeyCode::Enter => {
-                if !self.input.trim().is_empty() {
-                    let message = std::mem::take(&mut self.input);
-                    if let Ok(()) = self.send_messages.try_send(message.clone()) {
-                        self.add_message("You".to_string(), message);
-                    }
+    fn set_connection_state(&mut self, state: ConnectionState) {
+        self.connection_state = state;
+        // A search in flight is dropped with the connection.
+        if state != ConnectionState::Online
+            && let Some(server_search) = &mut self.server_search
+        {
+            server_search.pending = false;
+        }
+    }
+
+    fn registered(&mut self, alias: String) {
+        if let Registration::Registered(old_alias) = &self.registration
+            && *old_alias != alias
+        {
+            self.add_notice(format!("You are now known as {alias}."));
+        }
+        self.renaming = None;
+        self.registration = Registration::Registered(alias);
+        self.alias_input.take();
+    }
+
+    fn resize_sidebar(&mut self, delta: i16) {
+        self.sidebar_width = self
+            .sidebar_width
+            .saturating_add_signed(delta)
+            .clamp(MIN_SIDEBAR_WIDTH, MAX_SIDEBAR_WIDTH);
+    }
+
+    /// Mentions count as seen once the user is looking at the bottom of the chat.
+    fn update_unread(&mut self) {
+        let at_bottom = self.is_registered() && !self.message_scroll.is_scrolled_up();
+        if self.notifier.is_focused() && at_bottom {
+            self.notifier.mark_seen();
+        }
+    }
+
+    /// The alias we're registered under.
+    fn own_alias(&self) -> Option<&str> {
+        match &self.registration {
+            Registration::Registered(alias) => Some(alias),
+            _ => None,
+        }
+    }
+
+    fn is_registered(&self) -> bool {
+        matches!(self.registration, Registration::Registered(_))
+    }
+
+    fn handle_chat_event(&mut self, event: ChatEvent) {
+        match event {
+            ChatEvent::Message(message) => {
+                let time = message.timestamp_ms
+                    .and_then(|ms| DateTime::from_timestamp_millis(ms.try_into().ok()?))
+                    .map_or_else(Local::now, |time| time.with_timezone(&Local));
+                // Our own messages are already listed, waiting to be marked delivered.
+                if let Some(id) = &message.outgoing_id
+                    && self.set_delivered(id, time)
+                {
+                    return;
+                }
+                let sender = if message.is_own { Sender::Own } else { Sender::Peer(message.alias) };
+                let content = message.content;
+                if !message.is_own
+                    && let Some(alias) = self.own_alias()
+                    && mentions(&content, alias)
+                {
+                    let in_view = !self.message_scroll.is_scrolled_up();
+                    self.notifier.mention(sender.label(), &content, in_view);
                 }
+                self.push_message(Message { sender, content, time, outgoing: None });
+            }
+            ChatEvent::UserJoined(user) => {
+                self.users.insert(user.endpoint, &user.alias);
+                self.add_notice(format!("{} joined the chat.", user.alias));
             }
-            KeyCode::Char(c) => self.input.push(c),
-            KeyCode::Backspace => {
-                let _ = self.input.pop();
+            ChatEvent::UserRenamed { endpoint, old_alias, new_alias } => {
+                self.users.insert(endpoint, &new_alias);
+                self.add_notice(format!("{old_alias} is now known as {new_alias}."));
             }
-            KeyCode::Up => {
-                *self.message_list_state.offset_mut() =
-                    self.message_list_state.offset().saturating_sub(1);
+            ChatEvent::UserLeft(user) => {
+                self.users.remove(user.endpoint);
+                self.add_notice(format!("{} left the chat.", user.alias));
             }
-            KeyCode::Down => {
-                *self.message_list_state.offset_mut() = std::cmp::min(
-                    self.messages.len(),
-                    self.message_list_state.offset() + 1,
-                );
+            ChatEvent::MissedMessagesTruncated => {
+                self.add_notice("Some messages sent while you were away are missing.".to_string());
+            }
+            ChatEvent::SendFailed { outgoing_id, error } => {
+                self.set_delivery_status(&outgoing_id, DeliveryStatus::Failed(error.to_string()));
+            }
+            ChatEvent::Error(error @ ChatError::Register(_)) if self.renaming.is_some() => {
+                let alias = self.renaming.take().unwrap_or_default();
+                self.add_notice(format!("Couldn't change your alias to {alias}: {error}."));
+            }
+            // Back to the registration view - this can also happen on reconnecting, if our alias
+            // was claimed while we were away.
+            ChatEvent::Error(error @ ChatError::Register(_)) => {
+                if let Registration::Registering(alias) | Registration::Registered(alias) =
+                    &self.registration
+                {
+                    self.alias_input.set_text(alias.clone());
+                }
+                self.registration = Registration::ChoosingAlias { error: Some(error) };
+            }
+            ChatEvent::Error(error) => {
+                self.add_notice(format!("Error: {error}"));
             }
-            _ => {}
         }
     }
-}
 
-fn ui(f: &mut Frame, app: &mut App) {
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Min(1), Constraint::Length(3)].as_ref())
-        .split(f.area());
-
-    let messages: Vec<ListItem> = app
-        .messages
-        .iter()
-        .map(|m| {
-            let sender_style = match m.sender.as_str() {
-                "You" => Style::default().fg(Color::LightBlue),
-                "System" => Style::default().fg(Color::Yellow),
-                _ => Style::default().fg(Color::Green),
-            };
-            ListItem::new(Line::from(vec![
-                Span::styled(format!("{}: ", m.sender), sender_style),
-                Span::raw(&m.content),
-            ]))
+    fn max_message_len(&self) -> Option<usize> {
+        self.server_info.as_ref().map(|server_info| server_info.max_message_len as usize)
+    }
+
+    /// Show a notice from the client in the message list.
+    fn add_notice(&mut self, content: String) {
+        self.push_message(Message {
+            sender: Sender::System,
+            content,
+            time: Local::now(),
+            outgoing: None,
+        });
+    }
+
+    fn push_message(&mut self, message: Message) {
+        // Our own messages are logged once they're delivered.
+        let log = message.outgoing.is_none();
+        self.messages.push(message);
+        self.message_scroll.message_added();
+        if log {
+            self.log(self.messages.len() - 1);
+        }
+    }
+
+    /// Append the message at `index` to the transcript, if we're keeping one.
+    fn log(&mut self, index: usize) {
+        let sender = match &self.messages[index].sender {
+            Sender::Own => self.own_alias().unwrap_or("You").to_string(),
+            sender => sender.label().to_string(),
+        };
+        let message = &self.messages[index];
+        let Some(transcript) = &mut self.transcript else {
+            return;
+        };
+        let (kind, content) = match message.content.strip_prefix(ACTION_PREFIX) {
+            _ if matches!(message.sender, Sender::System) => {
+                (EntryKind::System, message.content.as_str())
+            }
+            Some(action) => (EntryKind::Action, action),
+            None => (EntryKind::Message, message.content.as_str()),
+        };
+        if let Err(e) = transcript.log(message.time, &sender, kind, content) {
+            let path = transcript.path().display().to_string();
+            self.transcript = None;
+            self.add_notice(format!("Stopped writing the transcript to {path}: {e}."));
+        }
+    }
+
+    /// List one of our messages as pending.
+    fn push_own_message(&mut self, message: OutgoingMessage) {
+        self.push_message(Message {
+            sender: Sender::Own,
+            content: message.content,
+            time: Local::now(),
+            outgoing: Some(Outgoing {
+                id: message.id,
+                status: DeliveryStatus::Pending,
+            }),
+        });
+    }
+
+    /// Ask the connection task to send what's in the outbox.
+    fn request_flush(&self) {
+        // If the channel is full, a flush is already on its way.
+        let _ = self.flush_requests.try_send(());
+    }
+
+    /// Update the status of one of our messages. Returns false if there's no such message.
+    fn set_delivery_status(&mut self, id: &str, status: DeliveryStatus) -> bool {
+        let outgoing = self.messages.iter_mut()
+            .filter_map(|message| message.outgoing.as_mut())
+            .find(|outgoing| outgoing.id == id);
+        match outgoing {
+            Some(outgoing) => {
+                outgoing.status = status;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Mark one of our messages delivered at `time`, logging it the first time. Returns false if
+    /// there's no such message.
+    fn set_delivered(&mut self, id: &str, time: DateTime<Local>) -> bool {
+        let index = self.messages.iter().position(|message| {
+            message.outgoing.as_ref().is_some_and(|outgoing| outgoing.id == id)
+        });
+        let Some(index) = index else {
+            return false;
+        };
+        let message = &mut self.messages[index];
+        let Some(outgoing) = &mut message.outgoing else {
+            return false;
+        };
+        if outgoing.status != DeliveryStatus::Delivered {
+            outgoing.status = DeliveryStatus::Delivered;
+            message.time = time;
+            self.log(index);
+        }
+        true
+    }
+
+    /// Queue every message that failed to send again, in order.
+    fn retry_failed(&mut self) {
+        for index in 0..self.messages.len() {
+            self.requeue(index);
+        }
+        self.request_flush();
+    }
+
+    /// Queue the message at `index` again if it's one of ours that failed to send.
+    fn requeue(&mut self, index: usize) {
+        let message = &mut self.messages[index];
+        let Some(outgoing) = &mut message.outgoing else {
+            return;
+        };
+        if matches!(outgoing.status, DeliveryStatus::Failed(_)) {
+            outgoing.status = DeliveryStatus::Pending;
+            self.outbox.push(OutgoingMessage {
+                id: outgoing.id.clone(),
+                content: message.content.clone(),
+            });
+        }
+    }
+
+    fn can_search_server(&self) -> bool {
+        self.server_info.as_ref().is_some_and(|server_info| {
+            chat_common::has_capability(server_info, chat_common::capabilities::SEARCH_MESSAGES)
         })
-        .collect();
-
-    let messages_block = Block::default().borders(Borders::ALL).title("Chat History");
-    app.messages_rect = messages_block.inner(chunks[0]);
-    let messages_list = List::new(messages)
-        .block(messages_block)
-        .highlight_style(Style::default().bg(Color::DarkGray));
-    f.render_stateful_widget(messages_list, chunks[0], &mut app.message_list_state);
-
-    // Input text box
-    let input_block = Block::default().borders(Borders::ALL)
-        .title("Input (Press Enter to send, Up/Down to scroll)");
-    let input_paragraph = Paragraph::new(app.input.as_str())
-        .style(Style::default().fg(Color::White).bg(Color::Black))
-        .block(input_block);
-
-    f.render_widget(input_paragraph, chunks[1]);
-
-    // Position the cursor at the end of the input text
-    f.set_cursor_position((
-        chunks[1].x + app.input.len() as u16 + 1, // +1 for the border
-        chunks[1].y + 1, // +1 for the border
-    ));
+    }
+
+    fn server_search_results(
+        &mut self,
+        results: Result<chat_modrpc::SearchMessagesSuccess, ChatError>,
+    ) {
+        let Some(server_search) = &mut self.server_search else {
+            return;
+        };
+        server_search.pending = false;
+        let query = server_search.query.clone();
+        let page = match results {
+            Ok(page) => page,
+            Err(error) => {
+                self.add_notice(format!("Couldn't search the server: {error}."));
+                return;
+            }
+        };
+        server_search.next_before_id = page.next_before_id;
+
+        if page.results.is_empty() {
+            self.add_notice(format!("No messages on the server match \"{query}\"."));
+            return;
+        }
+        self.add_notice(format!("Server results for \"{query}\":"));
+        for result in page.results {
+            let content = format!("  {}: {}", result.alias, result.snippet);
+            self.add_notice(content);
+        }
+        if page.next_before_id.is_some() {
+            self.add_notice("Press Ctrl+S in the search box for more.".to_string());
+        }
+    }
 }
 
+/// Whether `content` mentions `alias` as a word of its own, with or without an @.
+fn mentions(content: &str, alias: &str) -> bool {
+    let alias = alias.to_lowercase();
+    content
+        .split(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.')))
+        .map(str::to_lowercase)
+        // Aliases can contain dots, but one ending a sentence isn't part of the mention.
+        .any(|word| word == alias || word.trim_end_matches('.') == alias)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mentions_match_whole_words() {
+        assert!(mentions("alice", "alice"));
+        assert!(mentions("hi alice, how are you?", "alice"));
+        assert!(mentions("(alice)", "alice"));
+        assert!(!mentions("malice aforethought", "alice"));
+        assert!(!mentions("alicefoo", "alice"));
+        // `-` and `_` can be part of an alias, so they don't end the word.
+        assert!(!mentions("alice_b", "alice"));
+        assert!(!mentions("alice-b", "alice"));
+        assert!(!mentions("", "alice"));
+    }
+
+    #[test]
+    fn mentions_ignore_case_and_the_at_sign() {
+        assert!(mentions("ALICE!", "alice"));
+        assert!(mentions("@Alice look", "alice"));
+        assert!(mentions("thanks @alice", "Alice"));
+    }
+
+    #[test]
+    fn mentions_of_aliases_with_dots() {
+        assert!(mentions("ask a.b about it", "a.b"));
+        assert!(mentions("ask @a.b.", "a.b"));
+        assert!(mentions("that was alice.", "alice"));
+        assert!(!mentions("alice.b", "alice"));
+        assert!(!mentions("a.b", "a"));
+    }
+
+    #[test]
+    fn mentions_only_the_given_alias() {
+        // The own alias is what's checked, not whoever else is named.
+        assert!(!mentions("hi bob", "alice"));
+        assert!(mentions("bob and alice", "alice"));
+        assert!(!mentions("alice", "bob"));
+    }
+}
@@ -0,0 +1,108 @@
+use std::{io, process::Stdio};
+
+use crossterm::{style::Print, terminal::SetTitle};
+use serde::Deserialize;
+
+/// Ways of getting the user's attention when they're mentioned while not looking. Mentions are
+/// all there is to notify about, since the chat has no direct messages.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotificationConfig {
+    /// Ring the terminal bell.
+    pub bell: bool,
+    /// Count unread mentions in the window title.
+    pub title: bool,
+    /// Program and arguments to run for each mention. The sender and message are passed in the
+    /// `MODRPC_CHAT_SENDER` and `MODRPC_CHAT_MESSAGE` environment variables.
+    pub command: Option<Vec<String>>,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            bell: true,
+            title: true,
+            command: None,
+        }
+    }
+}
+
+/// Notifies the user of mentions they haven't seen and keeps count of them.
+///
+/// A mention counts as seen if it arrives while the terminal has the focus and the message list
+/// is at the bottom. Not every terminal reports focus changes, so the terminal is taken to have
+/// the focus until it says otherwise.
+pub struct Notifier {
+    config: NotificationConfig,
+    focused: bool,
+    // Mentions since the user last looked
+    unread: usize,
+    // Window title without the unread count
+    title: String,
+}
+
+impl Notifier {
+    pub fn new(config: NotificationConfig) -> Self {
+        Self {
+            config,
+            focused: true,
+            unread: 0,
+            title: String::new(),
+        }
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    pub fn set_title(&mut self, title: String) {
+        self.title = title;
+        self.update_title();
+    }
+
+    /// A message from `sender` mentioned us. `in_view` is whether the message list is showing it.
+    pub fn mention(&mut self, sender: &str, content: &str, in_view: bool) {
+        if self.focused && in_view {
+            return;
+        }
+        self.unread += 1;
+        self.update_title();
+        if self.config.bell {
+            let _ = crossterm::execute!(io::stdout(), Print('\x07'));
+        }
+        if let Some((program, args)) = self.config.command.as_ref().and_then(|c| c.split_first()) {
+            // Its output would garble the UI. Tokio reaps it once it exits.
+            let _ = tokio::process::Command::new(program)
+                .args(args)
+                .env("MODRPC_CHAT_SENDER", sender)
+                .env("MODRPC_CHAT_MESSAGE", content)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn();
+        }
+    }
+
+    /// The user is looking at the latest messages, so every mention so far has been seen.
+    pub fn mark_seen(&mut self) {
+        if self.unread > 0 {
+            self.unread = 0;
+            self.update_title();
+        }
+    }
+
+    fn update_title(&self) {
+        if !self.config.title {
+            return;
+        }
+        let title = match self.unread {
+            0 => self.title.clone(),
+            unread => format!("({unread}) {}", self.title),
+        };
+        let _ = crossterm::execute!(io::stdout(), SetTitle(title));
+    }
+}
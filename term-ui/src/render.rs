@@ -0,0 +1,443 @@
+use chat_client::{ChatError, DeliveryStatus};
+use chat_common::ACTION_PREFIX;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    mentions,
+    search::{self, Search},
+    App, ConnectionState, Focus, LineTarget, MessageAction, Registration, Sender,
+};
+
+/// The message input grows with its text up to this many rows, then scrolls.
+const MAX_INPUT_ROWS: u16 = 6;
+
+pub fn draw(f: &mut Frame, app: &mut App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+        .split(f.area());
+
+    if app.is_registered() {
+        chat_view(f, app, chunks[0]);
+    } else {
+        registration_view(f, app, chunks[0]);
+    }
+    status_bar(f, app, chunks[1]);
+}
+
+fn chat_view(f: &mut Frame, app: &mut App, area: Rect) {
+    let area = if app.show_sidebar {
+        // Leave at least half the screen for the chat.
+        let width = app.sidebar_width.min(area.width / 2);
+        let [area, sidebar_area] = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(1), Constraint::Length(width)])
+            .areas(area);
+        user_sidebar(f, app, sidebar_area);
+        app.pane_areas.users = sidebar_area;
+        area
+    } else {
+        app.pane_areas.users = Rect::default();
+        area
+    };
+
+    // +2 for the borders
+    let input_rows = match &app.search {
+        Some(_) => 1,
+        None => app.input.height(area.width.saturating_sub(2)).clamp(1, MAX_INPUT_ROWS),
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(input_rows + 2)].as_ref())
+        .split(area);
+    app.pane_areas.messages = chunks[0];
+    app.pane_areas.input = chunks[1];
+    let palette = &app.palette;
+
+    // Until we're online, pending messages are just sitting in the outbox.
+    let pending_label = if app.connection_state == ConnectionState::Online {
+        " (sending...)"
+    } else {
+        " (queued)"
+    };
+    // Borrowed field by field, the message list is rendered through `app.message_scroll`.
+    let own_alias = match &app.registration {
+        Registration::Registered(alias) => alias.as_str(),
+        _ => "You",
+    };
+    // Peers' messages that mention us stand out.
+    let mentionable_alias =
+        matches!(app.registration, Registration::Registered(_)).then_some(own_alias);
+    // While searching, matches are highlighted and the view scrolls to the current one.
+    let reveal = app.search.as_mut().is_some_and(Search::take_reveal);
+    let search = app.search.as_ref();
+    let current_match = search.and_then(Search::current);
+    let mut lines: Vec<Line> = Vec::new();
+    let mut line_targets = Vec::new();
+    for (i, m) in app.messages.iter().enumerate() {
+        if let Some(search) = search
+            && search.is_filtering()
+            && !search.is_match(&m.content)
+        {
+            continue;
+        }
+        if reveal && current_match == Some(i) {
+            app.message_scroll.reveal(lines.len());
+        }
+
+        let sender_style = match &m.sender {
+            Sender::Own => palette.own_sender,
+            Sender::System => palette.system,
+            Sender::Peer(alias) => palette.peer_sender(alias),
+        };
+        let status = m.outgoing.as_ref().map(|outgoing| &outgoing.status);
+        let content_style = match status {
+            Some(DeliveryStatus::Pending) => palette.muted,
+            _ if matches!(m.sender, Sender::Peer(_))
+                && mentionable_alias.is_some_and(|alias| mentions(&m.content, alias)) =>
+            {
+                palette.mention
+            }
+            _ => Style::default(),
+        };
+        // Actions posted with /me read as "* alias does something".
+        let (prefix, content, content_style) = match m.content.strip_prefix(ACTION_PREFIX) {
+            Some(action) if !matches!(m.sender, Sender::System) => {
+                let name = match &m.sender {
+                    Sender::Own => own_alias,
+                    sender => sender.label(),
+                };
+                (format!("* {name} "), action, content_style.add_modifier(Modifier::ITALIC))
+            }
+            _ => (format!("{}: ", m.sender.label()), m.content.as_str(), content_style),
+        };
+        let highlight_style =
+            if current_match == Some(i) { palette.search_current } else { palette.search_match };
+        // Messages can span several lines. The sender goes in front of the first and the
+        // delivery status after the last.
+        let mut message_lines: Vec<Line> = content
+            .split('\n')
+            .map(|line| {
+                let matches = search.map(|search| search.find_ranges(line)).unwrap_or_default();
+                Line::from(search::highlight(line, &matches, content_style, highlight_style))
+            })
+            .collect();
+        message_lines[0].spans.insert(0, Span::styled(prefix, sender_style));
+        if app.show_timestamps {
+            let timestamp = format!("{} ", m.time.format(&app.timestamp_format));
+            message_lines[0].spans.insert(0, Span::styled(timestamp, palette.muted));
+        }
+        let last_line = message_lines.last_mut().expect("split always yields a line");
+        match status {
+            Some(DeliveryStatus::Pending) => {
+                last_line.push_span(Span::styled(pending_label, content_style));
+            }
+            Some(DeliveryStatus::Failed(reason)) => {
+                last_line.push_span(Span::styled(format!(" [failed: {reason}]"), palette.error));
+                last_line.push_span(Span::styled(" Ctrl+R to retry", palette.muted));
+            }
+            Some(DeliveryStatus::Delivered) | None => {}
+        }
+        line_targets.extend(message_lines.iter().map(|_| LineTarget::Message(i)));
+        lines.extend(message_lines);
+
+        let actions = if app.selected_message == Some(i) { app.message_actions(i) } else { vec![] };
+        if !actions.is_empty() {
+            let mut spans = vec![Span::raw("  ")];
+            let mut buttons = Vec::new();
+            for action in actions {
+                let label = match action {
+                    MessageAction::Reply => "[reply]",
+                    MessageAction::Retry => "[retry]",
+                };
+                let start = spans.iter().map(Span::width).sum::<usize>() as u16;
+                buttons.push((start..start + label.len() as u16, action));
+                spans.push(Span::styled(label, palette.highlight));
+                spans.push(Span::raw(" "));
+            }
+            lines.push(Line::from(spans));
+            line_targets.push(LineTarget::Actions(i, buttons));
+        }
+    }
+    app.message_lines = line_targets;
+
+    let messages_block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(pane_border(app.focus == Focus::Messages))
+        .border_style(palette.border)
+        .title("Chat History");
+    let messages_area = messages_block.inner(chunks[0]);
+    f.render_widget(messages_block, chunks[0]);
+    app.message_scroll.render(f, messages_area, lines);
+
+    let unseen = app.message_scroll.unseen();
+    if unseen > 0 && messages_area.height > 0 {
+        let label = match unseen {
+            1 => " ↓ 1 new message below (Ctrl+End to jump) ".to_string(),
+            n => format!(" ↓ {n} new messages below (Ctrl+End to jump) "),
+        };
+        let indicator_area = Rect {
+            y: messages_area.bottom() - 1,
+            height: 1,
+            ..messages_area
+        };
+        f.render_widget(Clear, indicator_area);
+        f.render_widget(
+            Paragraph::new(label).alignment(Alignment::Center).style(palette.highlight),
+            indicator_area,
+        );
+    }
+
+    if app.search.is_some() {
+        search_box(f, app, chunks[1]);
+        return;
+    }
+
+    // Input text box
+    let mut input_title = vec![Span::raw(
+        "Input (Enter to send, Shift+Enter for a new line, PgUp/PgDn/Home/End to scroll)",
+    )];
+    if let Some(max_message_len) = app.max_message_len() {
+        let input_len = app.input.text().len();
+        let counter_style = if input_len >= max_message_len {
+            palette.error
+        } else {
+            palette.muted
+        };
+        input_title.push(Span::styled(format!(" {input_len}/{max_message_len}"), counter_style));
+    }
+    let input_block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(pane_border(app.focus == Focus::Input))
+        .border_style(palette.border)
+        .title(Line::from(input_title));
+    let input_area = input_block.inner(chunks[1]);
+    let input_style = palette.input;
+    f.render_widget(input_block, chunks[1]);
+    app.input.render(f, input_area, input_style);
+}
+
+fn search_box(f: &mut Frame, app: &mut App, area: Rect) {
+    let mut hints = vec!["Enter/↑ older", "Shift+Enter/↓ newer", "Tab filter", "Alt+R regex"];
+    if app.can_search_server() {
+        hints.push("Ctrl+S server");
+    }
+    hints.push("Esc close");
+
+    let palette = &app.palette;
+    let Some(search) = &mut app.search else {
+        return;
+    };
+
+    let mut title = vec![Span::raw(format!("Search ({})", hints.join(", ")))];
+    if search.is_regex() {
+        title.push(Span::styled(" [regex]", palette.warning));
+    }
+    if search.is_filtering() {
+        title.push(Span::styled(" [filter]", palette.warning));
+    }
+    let status = if let Some(error) = search.error() {
+        Span::styled(format!(" {error}"), palette.error)
+    } else if search.query.text().is_empty() {
+        Span::raw("")
+    } else {
+        let matching: Vec<usize> = app
+            .messages
+            .iter()
+            .enumerate()
+            .filter(|(_, message)| search.is_match(&message.content))
+            .map(|(i, _)| i)
+            .collect();
+        match search.current().and_then(|current| matching.iter().position(|&i| i == current)) {
+            Some(position) => Span::styled(
+                format!(" {}/{}", position + 1, matching.len()),
+                palette.muted,
+            ),
+            None => Span::styled(" no matches", palette.error),
+        }
+    };
+    title.push(status);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(palette.border)
+        .title(Line::from(title));
+    let inner = block.inner(area);
+    let style = palette.input;
+    f.render_widget(block, area);
+    search.query.render(f, inner, style);
+}
+
+/// Borders of a pane, thicker while it has the focus.
+fn pane_border(focused: bool) -> BorderType {
+    if focused { BorderType::Thick } else { BorderType::Plain }
+}
+
+fn user_sidebar(f: &mut Frame, app: &mut App, area: Rect) {
+    let palette = &app.palette;
+    let own_alias = app.own_alias();
+    // The list is only as fresh as our connection.
+    let online = app.connection_state == ConnectionState::Online;
+
+    let users: Vec<ListItem> = app
+        .users
+        .users()
+        .into_iter()
+        .map(|user| {
+            let (presence, presence_style) = if online {
+                ("● ", palette.ok)
+            } else {
+                ("○ ", palette.muted)
+            };
+            let mut spans = vec![Span::styled(presence, presence_style)];
+            if own_alias == Some(user.alias.as_str()) {
+                spans.push(Span::styled(user.alias, palette.own_sender));
+                spans.push(Span::styled(" (you)", palette.muted));
+                ListItem::new(Line::from(spans)).style(palette.highlight)
+            } else {
+                let style = palette.peer_sender(&user.alias);
+                spans.push(Span::styled(user.alias, style));
+                ListItem::new(Line::from(spans))
+            }
+        })
+        .collect();
+
+    let mut title = vec![Span::raw(format!("Users ({})", app.users.len()))];
+    if !online {
+        title.push(Span::styled(" [offline]", palette.muted));
+    }
+    let focused = app.focus == Focus::Users;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(pane_border(focused))
+        .border_style(palette.border)
+        .title(Line::from(title));
+    let mut list = List::new(users).block(block);
+    // The selection is only for picking someone with the arrow keys.
+    if focused {
+        list = list.highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    }
+    f.render_stateful_widget(list, area, &mut app.users_list);
+}
+
+fn registration_view(f: &mut Frame, app: &mut App, area: Rect) {
+    let palette = &app.palette;
+
+    // A fixed-size box in the middle of the screen
+    let [_, area, _] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(11), Constraint::Min(0)])
+        .areas(area);
+    let [_, area, _] = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Max(60), Constraint::Min(0)])
+        .areas(area);
+
+    let title = app
+        .server_info
+        .as_ref()
+        .map_or("modrpc chat", |server_info| server_info.name.as_str());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(palette.border)
+        .title(format!(" {title} "));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let [prompt_area, input_area, status_area, hint_area] = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(3),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+
+    f.render_widget(Paragraph::new("Choose an alias to join the chat."), prompt_area);
+
+    // The alias being registered, or None while it's being typed
+    let (alias, status) = match &app.registration {
+        Registration::ChoosingAlias { error: None } => (None, Line::default()),
+        Registration::ChoosingAlias { error: Some(error) } => {
+            let hint = match error {
+                ChatError::Register(
+                    chat_modrpc::RegisterError::UserAlreadyExists
+                    | chat_modrpc::RegisterError::InvalidAlias,
+                ) => "Pick a different alias.",
+                ChatError::Register(chat_modrpc::RegisterError::ChatFull) => "Try again later.",
+                _ => "Try again.",
+            };
+            let status = Line::styled(
+                format!("Couldn't register: {error}. {hint}"),
+                palette.error,
+            );
+            (None, status)
+        }
+        Registration::Registering(alias) => (
+            Some(alias.as_str()),
+            Line::styled(format!("Registering as {alias}..."), palette.muted),
+        ),
+        // Not shown once we're registered
+        Registration::Registered(alias) => (Some(alias.as_str()), Line::default()),
+    };
+
+    let input_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(palette.border)
+        .title("Alias");
+    let input_style = palette.input;
+    match alias {
+        Some(alias) => {
+            let input_paragraph = Paragraph::new(alias).style(input_style).block(input_block);
+            f.render_widget(input_paragraph, input_area);
+        }
+        None => {
+            let inner = input_block.inner(input_area);
+            f.render_widget(input_block, input_area);
+            app.alias_input.render(f, inner, input_style);
+        }
+    }
+    f.render_widget(Paragraph::new(status).wrap(Wrap { trim: true }), status_area);
+    f.render_widget(
+        Paragraph::new(Line::styled(
+            "Enter to register, Esc to quit",
+            palette.muted,
+        )),
+        hint_area,
+    );
+}
+
+fn status_bar(f: &mut Frame, app: &App, area: Rect) {
+    let palette = &app.palette;
+
+    let (connection_label, connection_style) = match app.connection_state {
+        ConnectionState::Connecting => ("connecting", palette.warning),
+        ConnectionState::Online => ("online", palette.ok),
+        ConnectionState::Reconnecting => ("reconnecting", palette.error),
+    };
+    let mut spans = vec![
+        Span::styled(format!("[{connection_label}]"), connection_style),
+        Span::raw(" "),
+    ];
+    match &app.registration {
+        Registration::Registered(alias) => {
+            spans.push(Span::styled(alias.clone(), palette.own_sender));
+        }
+        _ => spans.push(Span::styled("not registered", palette.muted)),
+    }
+    if let Some(server_info) = &app.server_info {
+        spans.push(Span::styled(
+            format!(" @ {}", server_info.name),
+            palette.muted,
+        ));
+    }
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
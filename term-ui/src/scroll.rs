@@ -0,0 +1,353 @@
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Scroll position of a list of wrapped lines, counted in rendered rows.
+///
+/// Follows the bottom of the list as lines are added, unless the user has scrolled up, in which
+/// case the view stays put and lines added below are counted as unseen.
+pub struct ScrollView {
+    // First row shown
+    offset: usize,
+    // Whether we're pinned to the bottom
+    follow: bool,
+    // Messages added below the view since the user scrolled up
+    unseen: usize,
+    // Size of the view and its content when last rendered
+    height: usize,
+    total_rows: usize,
+    // Line to bring into view on the next render
+    reveal: Option<usize>,
+    // Index of the line each row in view was wrapped from, as last rendered
+    visible_lines: Vec<usize>,
+}
+
+impl Default for ScrollView {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            follow: true,
+            unseen: 0,
+            height: 0,
+            total_rows: 0,
+            reveal: None,
+            visible_lines: Vec::new(),
+        }
+    }
+}
+
+impl ScrollView {
+    /// Number of messages added below the view while scrolled up.
+    pub fn unseen(&self) -> usize {
+        self.unseen
+    }
+
+    /// Whether the user has scrolled up from the bottom.
+    pub fn is_scrolled_up(&self) -> bool {
+        !self.follow
+    }
+
+    /// Note that a message was added at the bottom.
+    pub fn message_added(&mut self) {
+        if !self.follow {
+            self.unseen += 1;
+        }
+    }
+
+    pub fn scroll_up(&mut self, rows: usize) {
+        if self.max_offset() == 0 {
+            // Everything fits, so there's nowhere to scroll.
+            return;
+        }
+        self.offset = self.offset.min(self.max_offset()).saturating_sub(rows);
+        self.follow = false;
+    }
+
+    pub fn scroll_down(&mut self, rows: usize) {
+        self.offset = (self.offset + rows).min(self.max_offset());
+        if self.offset == self.max_offset() {
+            self.scroll_to_bottom();
+        }
+    }
+
+    pub fn page_up(&mut self) {
+        self.scroll_up(self.page_len());
+    }
+
+    pub fn page_down(&mut self) {
+        self.scroll_down(self.page_len());
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.scroll_up(self.offset);
+    }
+
+    pub fn scroll_to_bottom(&mut self) {
+        self.offset = self.max_offset();
+        self.follow = true;
+        self.unseen = 0;
+    }
+
+    /// Scroll so the line at this index in the next rendered list is in view, a third of the way
+    /// down.
+    pub fn reveal(&mut self, line: usize) {
+        self.reveal = Some(line);
+    }
+
+    /// Index of the line shown at `row` of the view, counting from the top of the area it was
+    /// last rendered into.
+    pub fn line_at(&self, row: u16) -> Option<usize> {
+        self.visible_lines.get(usize::from(row)).copied()
+    }
+
+    fn max_offset(&self) -> usize {
+        self.total_rows.saturating_sub(self.height)
+    }
+
+    /// Rows moved by PageUp/PageDown, keeping one row of context.
+    fn page_len(&self) -> usize {
+        self.height.saturating_sub(1).max(1)
+    }
+
+    /// Wrap `lines` to the width of `area` and draw the rows currently in view.
+    pub fn render(&mut self, f: &mut Frame, area: Rect, lines: Vec<Line<'_>>) {
+        let reveal = self.reveal.take();
+        let mut rows: Vec<Line> = Vec::new();
+        let mut row_lines = Vec::new();
+        // First row of the line being revealed
+        let mut reveal_row = None;
+        for (i, line) in lines.into_iter().enumerate() {
+            if reveal == Some(i) {
+                reveal_row = Some(rows.len());
+            }
+            let wrapped = wrap(line, area.width.into());
+            row_lines.extend(std::iter::repeat_n(i, wrapped.len()));
+            rows.extend(wrapped);
+        }
+
+        self.height = area.height.into();
+        self.total_rows = rows.len();
+        if let Some(row) = reveal_row {
+            self.offset = row.saturating_sub(self.height / 3).min(self.max_offset());
+            self.follow = false;
+        }
+        if self.follow || (reveal_row.is_some() && self.offset == self.max_offset()) {
+            self.scroll_to_bottom();
+        } else {
+            self.offset = self.offset.min(self.max_offset());
+        }
+
+        let visible: Vec<Line> = rows.into_iter().skip(self.offset).take(self.height).collect();
+        self.visible_lines = row_lines.into_iter().skip(self.offset).take(self.height).collect();
+        f.render_widget(Paragraph::new(visible), area);
+    }
+}
+
+/// Word-wrap a line to rows no wider than `width`, keeping the styles of its spans. Words longer
+/// than a row are broken wherever they hit the edge.
+pub fn wrap(line: Line<'_>, width: usize) -> Vec<Line<'static>> {
+    let width = width.max(1);
+    let line_style = line.style;
+    let mut rows = Vec::new();
+    // Graphemes of the row being built, with their styles
+    let mut row: Vec<(&str, Style)> = Vec::new();
+    let mut row_width = 0;
+    // Where the row can be broken, after the last whitespace in it
+    let mut break_at = None;
+
+    for span in &line.spans {
+        for g in span.content.graphemes(true) {
+            let g_width = g.width();
+            if row_width + g_width > width && !row.is_empty() {
+                // Carry the partial word over to the next row, if the row has anywhere to break.
+                let carried = match break_at {
+                    Some(i) if i < row.len() => row.split_off(i),
+                    _ => Vec::new(),
+                };
+                rows.push(to_line(&row, line_style));
+                row = carried;
+                row_width = row.iter().map(|(g, _)| g.width()).sum();
+                break_at = None;
+            }
+            // Whitespace at the wrap point is dropped, however much of it there is.
+            if row.is_empty() && !rows.is_empty() && g.trim().is_empty() {
+                continue;
+            }
+            row.push((g, span.style));
+            row_width += g_width;
+            if g.trim().is_empty() {
+                break_at = Some(row.len());
+            }
+        }
+    }
+    rows.push(to_line(&row, line_style));
+    rows
+}
+
+/// Join a row of graphemes back into a line, merging runs with the same style into one span.
+fn to_line(row: &[(&str, Style)], line_style: Style) -> Line<'static> {
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    for &(g, style) in row {
+        match spans.last_mut() {
+            Some(span) if span.style == style => span.content.to_mut().push_str(g),
+            _ => spans.push(Span::styled(g.to_string(), style)),
+        }
+    }
+    Line::from(spans).style(line_style)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{backend::TestBackend, style::Color, Terminal};
+
+    use super::*;
+
+    fn row_texts(rows: &[Line<'_>]) -> Vec<String> {
+        rows.iter()
+            .map(|row| row.spans.iter().map(|span| span.content.as_ref()).collect())
+            .collect()
+    }
+
+    fn wrap_text(text: &str, width: usize) -> Vec<String> {
+        row_texts(&wrap(Line::raw(text), width))
+    }
+
+    /// Render `len` one-row lines into a view `height` rows tall.
+    fn render(view: &mut ScrollView, height: u16, len: usize) {
+        let mut terminal = Terminal::new(TestBackend::new(20, height)).unwrap();
+        let lines = || (0..len).map(|i| Line::raw(i.to_string())).collect();
+        terminal.draw(|f| view.render(f, f.area(), lines())).unwrap();
+    }
+
+    #[test]
+    fn wrap_keeps_lines_that_fit() {
+        assert_eq!(wrap_text("hello world", 11), ["hello world"]);
+        assert_eq!(wrap_text("", 5), [""]);
+    }
+
+    #[test]
+    fn wrap_carries_a_partial_word_to_the_next_row() {
+        assert_eq!(wrap_text("hello world", 8), ["hello ", "world"]);
+        assert_eq!(wrap_text("one two three", 9), ["one two ", "three"]);
+    }
+
+    #[test]
+    fn wrap_drops_whitespace_at_the_break() {
+        assert_eq!(wrap_text("abc def", 3), ["abc", "def"]);
+        assert_eq!(wrap_text("abc   def", 3), ["abc", "def"]);
+    }
+
+    #[test]
+    fn wrap_breaks_words_wider_than_the_row() {
+        assert_eq!(wrap_text("abcdefgh", 3), ["abc", "def", "gh"]);
+        assert_eq!(wrap_text("a bcdefg", 3), ["a ", "bcd", "efg"]);
+    }
+
+    #[test]
+    fn wrap_moves_a_wide_grapheme_that_doesnt_fit() {
+        assert_eq!(wrap_text("ab界", 3), ["ab", "界"]);
+        // Even when it's wider than a row
+        assert_eq!(wrap_text("界", 1), ["界"]);
+    }
+
+    #[test]
+    fn wrap_keeps_span_styles() {
+        let red = Style::default().fg(Color::Red);
+        let line = Line::from(vec![Span::styled("ab", red), Span::raw("cd")]);
+        let rows = wrap(line, 3);
+
+        assert_eq!(rows[0].spans, [Span::styled("ab", red), Span::raw("c")]);
+        assert_eq!(rows[1].spans, [Span::raw("d")]);
+    }
+
+    #[test]
+    fn follows_the_bottom_as_lines_are_added() {
+        let mut view = ScrollView::default();
+        render(&mut view, 5, 20);
+        assert_eq!(view.line_at(0), Some(15));
+
+        view.message_added();
+        render(&mut view, 5, 21);
+        assert_eq!(view.line_at(0), Some(16));
+        assert!(!view.is_scrolled_up());
+        assert_eq!(view.unseen(), 0);
+    }
+
+    #[test]
+    fn stays_put_and_counts_unseen_lines_while_scrolled_up() {
+        let mut view = ScrollView::default();
+        render(&mut view, 5, 20);
+        view.scroll_up(3);
+        assert!(view.is_scrolled_up());
+
+        view.message_added();
+        view.message_added();
+        render(&mut view, 5, 22);
+        assert_eq!(view.line_at(0), Some(12));
+        assert_eq!(view.unseen(), 2);
+
+        // Getting back to the bottom follows it again.
+        view.scroll_down(100);
+        assert!(!view.is_scrolled_up());
+        assert_eq!(view.unseen(), 0);
+        render(&mut view, 5, 22);
+        assert_eq!(view.line_at(0), Some(17));
+    }
+
+    #[test]
+    fn doesnt_scroll_when_everything_fits() {
+        let mut view = ScrollView::default();
+        render(&mut view, 5, 3);
+        view.scroll_up(1);
+        assert!(!view.is_scrolled_up());
+        assert_eq!(view.line_at(0), Some(0));
+        assert_eq!(view.line_at(3), None);
+    }
+
+    #[test]
+    fn pages_keep_a_row_of_context() {
+        let mut view = ScrollView::default();
+        render(&mut view, 5, 20);
+        view.page_up();
+        render(&mut view, 5, 20);
+        assert_eq!(view.line_at(0), Some(11));
+
+        view.scroll_to_top();
+        render(&mut view, 5, 20);
+        assert_eq!(view.line_at(0), Some(0));
+        view.page_down();
+        render(&mut view, 5, 20);
+        assert_eq!(view.line_at(0), Some(4));
+    }
+
+    #[test]
+    fn reveal_brings_a_line_a_third_of_the_way_down() {
+        let mut view = ScrollView::default();
+        render(&mut view, 6, 50);
+        view.reveal(20);
+        render(&mut view, 6, 50);
+        assert_eq!(view.line_at(2), Some(20));
+        assert!(view.is_scrolled_up());
+
+        // Only on the next render
+        render(&mut view, 6, 50);
+        assert_eq!(view.line_at(2), Some(20));
+    }
+
+    #[test]
+    fn revealing_a_line_at_the_bottom_follows_it() {
+        let mut view = ScrollView::default();
+        render(&mut view, 6, 50);
+        view.scroll_up(20);
+        view.reveal(49);
+        render(&mut view, 6, 50);
+        assert_eq!(view.line_at(5), Some(49));
+        assert!(!view.is_scrolled_up());
+    }
+}
@@ -0,0 +1,283 @@
+use std::ops::Range;
+
+use ratatui::{style::Style, text::Span};
+use regex::{Regex, RegexBuilder};
+
+use crate::editor::LineEditor;
+
+/// Searching the message list for a substring or regex.
+///
+/// Matching is case-insensitive unless the query has an uppercase letter in it. The current match
+/// is tracked by message index, which stays valid as messages are only ever appended while a
+/// search is open.
+#[derive(Default)]
+pub struct Search {
+    pub query: LineEditor,
+    regex: bool,
+    // Whether only matching messages are shown
+    filter: bool,
+    // Compiled from the query, None while it's empty
+    pattern: Option<Regex>,
+    // Why the query isn't a valid regex
+    error: Option<String>,
+    // Index of the message the current match is in
+    current: Option<usize>,
+    // Set when the current match moves, until the view has scrolled to it
+    reveal: bool,
+}
+
+impl Search {
+    pub fn new(query: String) -> Self {
+        let mut search = Self::default();
+        search.query.set_text(query);
+        search.compile();
+        search
+    }
+
+    pub fn is_regex(&self) -> bool {
+        self.regex
+    }
+
+    pub fn toggle_regex(&mut self) {
+        self.regex = !self.regex;
+        self.compile();
+    }
+
+    pub fn is_filtering(&self) -> bool {
+        self.filter
+    }
+
+    pub fn toggle_filter(&mut self) {
+        self.filter = !self.filter;
+        self.reveal = true;
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Recompile the pattern after the query or mode changed.
+    pub fn compile(&mut self) {
+        let query = self.query.text();
+        self.error = None;
+        self.pattern = None;
+        if query.is_empty() {
+            return;
+        }
+        let source = if self.regex { query.to_string() } else { regex::escape(query) };
+        let case_insensitive = !query.chars().any(char::is_uppercase);
+        match RegexBuilder::new(&source).case_insensitive(case_insensitive).build() {
+            Ok(pattern) => self.pattern = Some(pattern),
+            Err(error) => self.error = Some(error_message(&error)),
+        }
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.pattern.as_ref().is_some_and(|pattern| pattern.is_match(text))
+    }
+
+    /// Byte ranges of the non-empty matches in `text`.
+    pub fn find_ranges(&self, text: &str) -> Vec<Range<usize>> {
+        let Some(pattern) = &self.pattern else {
+            return Vec::new();
+        };
+        pattern
+            .find_iter(text)
+            .filter(|m| !m.is_empty())
+            .map(|m| m.range())
+            .collect()
+    }
+
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    /// Move to the newest match, as if searching back from the bottom of the list.
+    pub fn jump_to_newest(&mut self, texts: &[&str]) {
+        self.current = None;
+        self.jump_older(texts);
+    }
+
+    /// Move to the match before the current one. Stays put if there isn't one.
+    pub fn jump_older(&mut self, texts: &[&str]) {
+        let end = self.current.unwrap_or(texts.len());
+        let older = texts[..end.min(texts.len())].iter().rposition(|text| self.is_match(text));
+        if let Some(index) = older {
+            self.move_to(index);
+        }
+    }
+
+    /// Move to the match after the current one. Stays put if there isn't one.
+    pub fn jump_newer(&mut self, texts: &[&str]) {
+        let Some(current) = self.current else {
+            return;
+        };
+        let newer = texts.iter().skip(current + 1).position(|text| self.is_match(text));
+        if let Some(offset) = newer {
+            self.move_to(current + 1 + offset);
+        }
+    }
+
+    fn move_to(&mut self, index: usize) {
+        self.current = Some(index);
+        self.reveal = true;
+    }
+
+    /// Whether the view should scroll to the current match, clearing the request.
+    pub fn take_reveal(&mut self) -> bool {
+        std::mem::take(&mut self.reveal) && self.current.is_some()
+    }
+}
+
+/// Split `text` into spans, patching `match_style` onto the byte ranges in `matches`.
+pub fn highlight<'a>(
+    text: &'a str,
+    matches: &[Range<usize>],
+    style: Style,
+    match_style: Style,
+) -> Vec<Span<'a>> {
+    let mut spans = Vec::new();
+    let mut end = 0;
+    for range in matches {
+        if range.start > end {
+            spans.push(Span::styled(&text[end..range.start], style));
+        }
+        spans.push(Span::styled(&text[range.clone()], style.patch(match_style)));
+        end = range.end;
+    }
+    if end < text.len() || spans.is_empty() {
+        spans.push(Span::styled(&text[end..], style));
+    }
+    spans
+}
+
+/// The last line of a regex error, which says what's wrong without repeating the pattern.
+fn error_message(error: &regex::Error) -> String {
+    match error {
+        regex::Error::Syntax(message) => message
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .map_or(message.as_str(), |line| line.trim().trim_start_matches("error: "))
+            .to_string(),
+        _ => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXTS: &[&str] = &["hello", "unrelated", "Hello again", "HELLO?", "bye"];
+
+    fn search(query: &str) -> Search {
+        Search::new(query.to_string())
+    }
+
+    fn regex(query: &str) -> Search {
+        let mut search = search(query);
+        search.toggle_regex();
+        search
+    }
+
+    #[test]
+    fn matching_ignores_case_unless_the_query_has_uppercase() {
+        let matching = |search: Search| -> Vec<&str> {
+            TEXTS.iter().copied().filter(|text| search.is_match(text)).collect()
+        };
+        assert_eq!(matching(search("hello")), ["hello", "Hello again", "HELLO?"]);
+        assert_eq!(matching(search("Hello")), ["Hello again"]);
+        assert!(matching(search("")).is_empty());
+    }
+
+    #[test]
+    fn plain_queries_are_not_regexes() {
+        assert!(search("?").is_match("HELLO?"));
+        assert!(!search("h.llo").is_match("hello"));
+        assert!(regex("h.llo").is_match("hello"));
+        assert!(regex("^bye$").is_match("bye"));
+    }
+
+    #[test]
+    fn invalid_regex_reports_an_error_and_matches_nothing() {
+        let mut search = regex("(hello");
+        assert_eq!(search.error(), Some("unclosed group"));
+        assert!(!search.is_match("(hello"));
+
+        // Fine as a plain query
+        search.toggle_regex();
+        assert_eq!(search.error(), None);
+        assert!(search.is_match("(hello"));
+    }
+
+    #[test]
+    fn find_ranges_skips_empty_matches() {
+        assert_eq!(search("lo").find_ranges("hello, lo"), [3..5, 7..9]);
+        assert_eq!(regex("l*").find_ranges("hello lo"), [2..4, 6..7]);
+        assert!(search("").find_ranges("hello").is_empty());
+    }
+
+    #[test]
+    fn highlight_splits_text_around_matches() {
+        let style = Style::default();
+        let match_style = Style::default().bg(ratatui::style::Color::Yellow);
+        let spans = |text, matches: &[Range<usize>]| -> Vec<(String, bool)> {
+            highlight(text, matches, style, match_style)
+                .into_iter()
+                .map(|span| (span.content.into_owned(), span.style == match_style))
+                .collect()
+        };
+        let span = |text: &str, is_match| (text.to_string(), is_match);
+
+        assert_eq!(
+            spans("hello world", &[2..4, 6..11]),
+            [span("he", false), span("ll", true), span("o ", false), span("world", true)],
+        );
+        assert_eq!(
+            spans("abc", &[0..1, 1..2]),
+            [span("a", true), span("b", true), span("c", false)],
+        );
+        assert_eq!(spans("abc", &[]), [span("abc", false)]);
+        // Empty text still gets a span to carry the style.
+        assert_eq!(spans("", &[]), [span("", false)]);
+    }
+
+    #[test]
+    fn jumps_between_matching_messages() {
+        let mut search = search("hello");
+        assert_eq!(search.current(), None);
+
+        search.jump_to_newest(TEXTS);
+        assert_eq!(search.current(), Some(3));
+        search.jump_older(TEXTS);
+        assert_eq!(search.current(), Some(2));
+        search.jump_older(TEXTS);
+        assert_eq!(search.current(), Some(0));
+        // Stays on the oldest match
+        search.jump_older(TEXTS);
+        assert_eq!(search.current(), Some(0));
+
+        search.jump_newer(TEXTS);
+        assert_eq!(search.current(), Some(2));
+        search.jump_newer(TEXTS);
+        search.jump_newer(TEXTS);
+        assert_eq!(search.current(), Some(3));
+    }
+
+    #[test]
+    fn reveals_the_current_match_once() {
+        let mut search = search("bye");
+        // Nothing to reveal without a current match
+        search.toggle_filter();
+        assert!(!search.take_reveal());
+
+        search.jump_to_newest(TEXTS);
+        assert!(search.take_reveal());
+        assert!(!search.take_reveal());
+
+        // Toggling the filter changes what's shown, so the match is revealed again.
+        search.toggle_filter();
+        assert!(!search.is_filtering());
+        assert!(search.take_reveal());
+    }
+}
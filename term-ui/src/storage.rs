@@ -0,0 +1,50 @@
+use std::{fs, path::PathBuf};
+
+use chat_client::{OutboxStorage, OutgoingMessage};
+
+use crate::config::xdg_dir;
+
+/// Keeps the outbox in a JSON file so queued messages survive restarts.
+pub struct FileStorage {
+    path: PathBuf,
+}
+
+impl FileStorage {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// `$XDG_STATE_HOME/modrpc-chat/outbox.json`, falling back to `~/.local/state` and then the
+    /// working directory.
+    pub fn default_path() -> PathBuf {
+        match xdg_dir("XDG_STATE_HOME", ".local/state") {
+            Some(state_dir) => state_dir.join("modrpc-chat").join("outbox.json"),
+            None => PathBuf::from("modrpc-chat-outbox.json"),
+        }
+    }
+}
+
+impl OutboxStorage for FileStorage {
+    fn load(&self) -> Vec<OutgoingMessage> {
+        // A missing or unreadable outbox is treated as empty - there's nowhere to report it
+        // while the terminal is in raw mode.
+        fs::read(&self.path)
+            .ok()
+            .and_then(|contents| serde_json::from_slice(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, messages: &[OutgoingMessage]) {
+        let Ok(contents) = serde_json::to_vec(messages) else {
+            return;
+        };
+        if let Some(dir) = self.path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        // Write to a temporary file first so a crash mid-write doesn't lose the outbox.
+        let temp_path = self.path.with_extension("json.tmp");
+        if fs::write(&temp_path, contents).is_ok() {
+            let _ = fs::rename(&temp_path, &self.path);
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    /// Light text on a dark terminal background
+    #[default]
+    Dark,
+    /// Dark text on a light terminal background
+    Light,
+    /// No colors, only bold, dim and reversed text. Used by default if `NO_COLOR` is set.
+    Monochrome,
+}
+
+/// Colors set in the config file, overriding those of the theme. Ignored by the monochrome theme.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorOverrides {
+    pub own_sender: Option<Color>,
+    /// Peers are each given one of these, picked by their alias.
+    pub peer_senders: Option<Vec<Color>>,
+    pub system: Option<Color>,
+    pub mention: Option<Color>,
+    pub border: Option<Color>,
+}
+
+/// Styles the UI is drawn with.
+pub struct Palette {
+    pub own_sender: Style,
+    // Never empty
+    peer_senders: Vec<Style>,
+    pub system: Style,
+    /// Messages that mention us
+    pub mention: Style,
+    pub border: Style,
+    /// Hints, timestamps and messages that haven't been sent yet
+    pub muted: Style,
+    pub ok: Style,
+    pub warning: Style,
+    pub error: Style,
+    pub input: Style,
+    pub highlight: Style,
+    /// Search matches, and the ones in the message the search is currently on
+    pub search_match: Style,
+    pub search_current: Style,
+}
+
+impl Theme {
+    pub fn palette(self) -> Palette {
+        let fg = |color| Style::default().fg(color);
+        match self {
+            Theme::Dark => Palette {
+                own_sender: fg(Color::LightBlue),
+                peer_senders: [
+                    Color::Green,
+                    Color::Cyan,
+                    Color::Magenta,
+                    Color::LightGreen,
+                    Color::LightCyan,
+                    Color::LightMagenta,
+                ]
+                .map(fg)
+                .to_vec(),
+                system: fg(Color::Yellow),
+                mention: fg(Color::LightYellow).add_modifier(Modifier::BOLD),
+                border: Style::default(),
+                muted: fg(Color::DarkGray),
+                ok: fg(Color::Green),
+                warning: fg(Color::Yellow),
+                error: fg(Color::Red),
+                input: fg(Color::White).bg(Color::Black),
+                highlight: fg(Color::White).bg(Color::DarkGray),
+                search_match: fg(Color::Black).bg(Color::Yellow),
+                search_current: fg(Color::Black).bg(Color::LightRed),
+            },
+            Theme::Light => Palette {
+                own_sender: fg(Color::Blue),
+                // Bright colors are hard to read on white, so some come from the 256-color range.
+                peer_senders: [
+                    Color::Green,
+                    Color::Cyan,
+                    Color::Indexed(130), // brown
+                    Color::Indexed(91),  // purple
+                    Color::Indexed(24),  // navy
+                    Color::Indexed(28),  // dark green
+                ]
+                .map(fg)
+                .to_vec(),
+                // Yellow is hard to read on white
+                system: fg(Color::Magenta),
+                mention: fg(Color::Red).add_modifier(Modifier::BOLD),
+                border: Style::default(),
+                muted: fg(Color::Gray),
+                ok: fg(Color::Green),
+                warning: fg(Color::Magenta),
+                error: fg(Color::Red),
+                input: fg(Color::Black).bg(Color::White),
+                highlight: fg(Color::Black).bg(Color::Gray),
+                search_match: fg(Color::Black).bg(Color::LightYellow),
+                search_current: fg(Color::Black).bg(Color::LightRed),
+            },
+            Theme::Monochrome => {
+                let plain = Style::default();
+                let bold = plain.add_modifier(Modifier::BOLD);
+                let dim = plain.add_modifier(Modifier::DIM);
+                let reversed = plain.add_modifier(Modifier::REVERSED);
+                Palette {
+                    own_sender: bold,
+                    peer_senders: vec![bold],
+                    system: plain.add_modifier(Modifier::ITALIC),
+                    mention: bold.add_modifier(Modifier::UNDERLINED),
+                    border: plain,
+                    muted: dim,
+                    ok: plain,
+                    warning: bold,
+                    error: bold.add_modifier(Modifier::UNDERLINED),
+                    input: plain,
+                    highlight: reversed,
+                    search_match: plain.add_modifier(Modifier::UNDERLINED),
+                    search_current: reversed,
+                }
+            }
+        }
+    }
+}
+
+impl Palette {
+    /// Style for a peer's alias. The same alias always gets the same color.
+    pub fn peer_sender(&self, alias: &str) -> Style {
+        // FNV-1a, which unlike the standard library's hashers is guaranteed to stay the same
+        let hash = alias.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+            (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+        });
+        self.peer_senders[hash as usize % self.peer_senders.len()]
+    }
+
+    /// Apply colors from the config file on top of this palette.
+    pub fn override_colors(&mut self, colors: &ColorOverrides) {
+        if let Some(color) = colors.own_sender {
+            self.own_sender = self.own_sender.fg(color);
+        }
+        if let Some(peer_senders) = &colors.peer_senders {
+            self.peer_senders =
+                peer_senders.iter().map(|&color| Style::default().fg(color)).collect();
+        }
+        if let Some(color) = colors.system {
+            self.system = self.system.fg(color);
+        }
+        if let Some(color) = colors.mention {
+            self.mention = self.mention.fg(color);
+        }
+        if let Some(color) = colors.border {
+            self.border = self.border.fg(color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn styles(palette: &Palette) -> Vec<Style> {
+        let mut styles = palette.peer_senders.clone();
+        styles.extend([
+            palette.own_sender,
+            palette.system,
+            palette.mention,
+            palette.border,
+            palette.muted,
+            palette.ok,
+            palette.warning,
+            palette.error,
+            palette.input,
+            palette.highlight,
+            palette.search_match,
+            palette.search_current,
+        ]);
+        styles
+    }
+
+    #[test]
+    fn peer_colors_are_picked_by_alias() {
+        let palette = Theme::Dark.palette();
+        assert_eq!(palette.peer_sender("alice"), Theme::Dark.palette().peer_sender("alice"));
+        // The hash mustn't change between runs or releases, or everyone's color would.
+        assert_eq!(palette.peer_sender("alice").fg, Some(Color::LightMagenta));
+        assert_eq!(palette.peer_sender("bob").fg, Some(Color::Magenta));
+
+        let colors: Vec<_> = ["alice", "bob", "carol", "dave", "erin", "frank"]
+            .iter()
+            .map(|alias| palette.peer_sender(alias))
+            .collect();
+        assert!(colors.iter().any(|&color| color != colors[0]));
+    }
+
+    #[test]
+    fn peer_colors_can_be_overridden() {
+        let mut palette = Theme::Light.palette();
+        palette.override_colors(&ColorOverrides {
+            peer_senders: Some(vec![Color::Red]),
+            mention: Some(Color::Blue),
+            ..ColorOverrides::default()
+        });
+        assert_eq!(palette.peer_sender("alice").fg, Some(Color::Red));
+        assert_eq!(palette.peer_sender("bob").fg, Some(Color::Red));
+        assert_eq!(palette.mention.fg, Some(Color::Blue));
+        // Only the color is overridden.
+        assert!(palette.mention.add_modifier.contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn monochrome_has_no_colors() {
+        for style in styles(&Theme::Monochrome.palette()) {
+            assert_eq!((style.fg, style.bg), (None, None), "{style:?}");
+        }
+        // Matches still stand out without color.
+        let palette = Theme::Monochrome.palette();
+        assert_ne!(palette.search_match, Style::default());
+        assert_ne!(palette.search_match, palette.search_current);
+    }
+}
@@ -0,0 +1,336 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local, SecondsFormat};
+use serde::{Deserialize, Serialize};
+
+use crate::config::xdg_dir;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptFormat {
+    /// `[time] alias: message`, one message per line with continuation lines indented
+    #[default]
+    Plain,
+    /// One JSON object per line with `time`, `sender`, `kind` and `content` fields
+    Ndjson,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TranscriptConfig {
+    pub enabled: bool,
+    /// Defaults to `$XDG_STATE_HOME/modrpc-chat/transcript.log`.
+    pub path: Option<PathBuf>,
+    pub format: TranscriptFormat,
+    /// Start a new file once the current one would grow past this. 0 never rotates.
+    pub max_file_size: u64,
+    /// Rotated files kept, as `<path>.1` (the newest) up to `<path>.<max_files>`.
+    pub max_files: u32,
+}
+
+impl Default for TranscriptConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            format: TranscriptFormat::default(),
+            max_file_size: 10 * 1024 * 1024,
+            max_files: 5,
+        }
+    }
+}
+
+impl TranscriptConfig {
+    pub fn path(&self) -> PathBuf {
+        self.path.clone().unwrap_or_else(|| match xdg_dir("XDG_STATE_HOME", ".local/state") {
+            Some(state_dir) => state_dir.join("modrpc-chat").join("transcript.log"),
+            None => PathBuf::from("modrpc-chat-transcript.log"),
+        })
+    }
+}
+
+/// What a transcript entry records, besides who said what.
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    Message,
+    /// Posted with /me
+    Action,
+    /// Shown by the client, not sent by anyone
+    System,
+}
+
+#[derive(Serialize)]
+struct Entry<'a> {
+    time: String,
+    sender: &'a str,
+    kind: EntryKind,
+    content: &'a str,
+}
+
+/// Appends the messages shown in the chat to a log file, rotating it once it gets too big.
+pub struct Transcript {
+    path: PathBuf,
+    format: TranscriptFormat,
+    max_file_size: u64,
+    max_files: u32,
+    file: File,
+    // Bytes in `file`
+    size: u64,
+}
+
+impl Transcript {
+    pub fn open(config: &TranscriptConfig) -> io::Result<Self> {
+        let path = config.path();
+        let (file, size) = open_append(&path)?;
+        Ok(Self {
+            path,
+            format: config.format,
+            max_file_size: config.max_file_size,
+            max_files: config.max_files,
+            file,
+            size,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn log(
+        &mut self,
+        time: DateTime<Local>,
+        sender: &str,
+        kind: EntryKind,
+        content: &str,
+    ) -> io::Result<()> {
+        let entry = match self.format {
+            TranscriptFormat::Plain => {
+                let time = time.format("%Y-%m-%d %H:%M:%S");
+                let head = match kind {
+                    EntryKind::Action => format!("[{time}] * {sender} "),
+                    EntryKind::Message | EntryKind::System => format!("[{time}] {sender}: "),
+                };
+                // Continuation lines are indented so every entry starts with its timestamp.
+                format!("{head}{}\n", content.replace('\n', "\n    "))
+            }
+            TranscriptFormat::Ndjson => {
+                let entry = Entry {
+                    time: time.to_rfc3339_opts(SecondsFormat::Millis, false),
+                    sender,
+                    kind,
+                    content,
+                };
+                let mut line = serde_json::to_string(&entry).map_err(io::Error::other)?;
+                line.push('\n');
+                line
+            }
+        };
+
+        let len = entry.len() as u64;
+        if self.max_file_size > 0 && self.size > 0 && self.size + len > self.max_file_size {
+            self.rotate()?;
+        }
+        self.file.write_all(entry.as_bytes())?;
+        self.size += len;
+        Ok(())
+    }
+
+    /// Shift the rotated files along, dropping the oldest, and start a new file.
+    fn rotate(&mut self) -> io::Result<()> {
+        let rotated = |n: u32| {
+            let mut path = self.path.clone().into_os_string();
+            path.push(format!(".{n}"));
+            PathBuf::from(path)
+        };
+        for n in (1..self.max_files).rev() {
+            let from = rotated(n);
+            if from.exists() {
+                fs::rename(from, rotated(n + 1))?;
+            }
+        }
+        if self.max_files > 0 {
+            fs::rename(&self.path, rotated(1))?;
+        } else {
+            fs::remove_file(&self.path)?;
+        }
+        (self.file, self.size) = open_append(&self.path)?;
+        Ok(())
+    }
+}
+
+/// Open `path` for appending, creating it and its directory if needed, along with its size.
+fn open_append(path: &Path) -> io::Result<(File, u64)> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let file = File::options().create(true).append(true).open(path)?;
+    let size = file.metadata()?.len();
+    Ok((file, size))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn config(dir: &tempfile::TempDir, format: TranscriptFormat) -> TranscriptConfig {
+        TranscriptConfig {
+            enabled: true,
+            path: Some(dir.path().join("logs").join("transcript.log")),
+            format,
+            ..TranscriptConfig::default()
+        }
+    }
+
+    fn time() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).single().expect("unambiguous time")
+    }
+
+    fn read(path: &Path) -> String {
+        fs::read_to_string(path).expect("read transcript")
+    }
+
+    fn rotated(config: &TranscriptConfig, n: u32) -> PathBuf {
+        PathBuf::from(format!("{}.{n}", config.path().display()))
+    }
+
+    // Each of these entries is 27 bytes.
+    fn log_numbered(transcript: &mut Transcript, numbers: std::ops::RangeInclusive<u32>) {
+        for n in numbers {
+            let content = n.to_string();
+            transcript.log(time(), "a", EntryKind::Message, &content).expect("log entry");
+        }
+    }
+
+    fn entries(n: &[u32]) -> String {
+        n.iter().map(|n| format!("[2024-01-02 03:04:05] a: {n}\n")).collect()
+    }
+
+    #[test]
+    fn plain_entries() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let config = config(&dir, TranscriptFormat::Plain);
+        let mut transcript = Transcript::open(&config).expect("open transcript");
+
+        transcript.log(time(), "alice", EntryKind::Message, "hi\nthere").unwrap();
+        transcript.log(time(), "bob", EntryKind::Action, "waves").unwrap();
+        transcript.log(time(), "*", EntryKind::System, "bob left the chat.").unwrap();
+
+        assert_eq!(
+            read(transcript.path()),
+            "[2024-01-02 03:04:05] alice: hi\n    there\n\
+            [2024-01-02 03:04:05] * bob waves\n\
+            [2024-01-02 03:04:05] *: bob left the chat.\n",
+        );
+    }
+
+    #[test]
+    fn ndjson_entries() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let config = config(&dir, TranscriptFormat::Ndjson);
+        let mut transcript = Transcript::open(&config).expect("open transcript");
+
+        transcript.log(time(), "alice", EntryKind::Message, "hi\nthere").unwrap();
+        transcript.log(time(), "bob", EntryKind::Action, "waves").unwrap();
+
+        let lines: Vec<serde_json::Value> = read(transcript.path())
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("entry is JSON"))
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0]["time"].as_str().unwrap().starts_with("2024-01-02T03:04:05.000"));
+        assert_eq!(lines[0]["sender"], "alice");
+        assert_eq!(lines[0]["kind"], "message");
+        assert_eq!(lines[0]["content"], "hi\nthere");
+        assert_eq!(lines[1]["kind"], "action");
+    }
+
+    #[test]
+    fn rotates_past_the_size_limit() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let config = TranscriptConfig {
+            max_file_size: 60,
+            max_files: 2,
+            ..config(&dir, TranscriptFormat::Plain)
+        };
+        let mut transcript = Transcript::open(&config).expect("open transcript");
+
+        log_numbered(&mut transcript, 1..=7);
+
+        // Two entries fit in a file, and the oldest files are dropped.
+        assert_eq!(read(&config.path()), entries(&[7]));
+        assert_eq!(read(&rotated(&config, 1)), entries(&[5, 6]));
+        assert_eq!(read(&rotated(&config, 2)), entries(&[3, 4]));
+        assert!(!rotated(&config, 3).exists());
+    }
+
+    #[test]
+    fn rotating_without_keeping_files_starts_over() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let config = TranscriptConfig {
+            max_file_size: 60,
+            max_files: 0,
+            ..config(&dir, TranscriptFormat::Plain)
+        };
+        let mut transcript = Transcript::open(&config).expect("open transcript");
+
+        log_numbered(&mut transcript, 1..=3);
+
+        assert_eq!(read(&config.path()), entries(&[3]));
+        assert!(!rotated(&config, 1).exists());
+    }
+
+    #[test]
+    fn counts_what_is_already_in_the_file() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let config = TranscriptConfig {
+            max_file_size: 60,
+            ..config(&dir, TranscriptFormat::Plain)
+        };
+        log_numbered(&mut Transcript::open(&config).expect("open transcript"), 1..=1);
+
+        let mut transcript = Transcript::open(&config).expect("reopen transcript");
+        log_numbered(&mut transcript, 2..=3);
+
+        assert_eq!(read(&config.path()), entries(&[3]));
+        assert_eq!(read(&rotated(&config, 1)), entries(&[1, 2]));
+    }
+
+    #[test]
+    fn oversized_entry_gets_a_file_of_its_own() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let config = TranscriptConfig {
+            max_file_size: 10,
+            ..config(&dir, TranscriptFormat::Plain)
+        };
+        let mut transcript = Transcript::open(&config).expect("open transcript");
+
+        log_numbered(&mut transcript, 1..=2);
+
+        // Nothing to rotate away before the first entry, however big it is.
+        assert_eq!(read(&config.path()), entries(&[2]));
+        assert_eq!(read(&rotated(&config, 1)), entries(&[1]));
+        assert!(!rotated(&config, 2).exists());
+    }
+
+    #[test]
+    fn never_rotates_without_a_size_limit() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let config = TranscriptConfig {
+            max_file_size: 0,
+            ..config(&dir, TranscriptFormat::Plain)
+        };
+        let mut transcript = Transcript::open(&config).expect("open transcript");
+
+        log_numbered(&mut transcript, 1..=5);
+
+        assert_eq!(read(&config.path()), entries(&[1, 2, 3, 4, 5]));
+        assert!(!rotated(&config, 1).exists());
+    }
+}
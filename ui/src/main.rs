@@ -1,11 +1,19 @@
 #![allow(non_snake_case)]
 
-use std::collections::HashMap;
+use std::{pin::pin, rc::Rc, time::Duration};
 
+use chat_client::{
+    ChatError, ChatEvent, ChatSession, Credentials, DeliveryStatus, Outbox, OutgoingMessage, UserDirectory,
+};
 use dioxus::prelude::*;
 use dioxus_logger::tracing::{info, Level};
 use modrpc_executor::ModrpcExecutor;
 
+use crate::storage::LocalStorage;
+
+mod markdown;
+mod storage;
+
 fn main() {
     dioxus_logger::init(Level::INFO).expect("failed to init logger");
     info!("starting app");
@@ -16,145 +24,438 @@ fn main() {
 struct Message {
     sender: String,
     content: String,
+    // Set on messages we sent
+    outgoing: Option<Outgoing>,
+}
+
+#[derive(Clone)]
+struct Outgoing {
+    id: String,
+    status: DeliveryStatus,
+}
+
+const SERVER_URL: &str = "ws://127.0.0.1:9096";
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_millis(500);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+// How long members who left are still listed as away, in case they reconnect
+const AWAY_PERIOD: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Copy, Clone, PartialEq)]
+enum ConnectionState {
+    Connecting,
+    Online,
+    Reconnecting,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Presence {
+    Online,
+    // Left recently, and may still resume their session
+    Away,
+    // We're disconnected, so we can't tell
+    Unknown,
 }
 
 #[derive(Copy, Clone)]
 pub struct AppContext {
     compose_message: Signal<String>,
-    users: Signal<HashMap<u64, String>>,
-    my_alias: Signal<Option<String>>,
+    users: Signal<UserDirectory>,
+    // Aliases of members who left in the last `AWAY_PERIOD`, until they come back
+    away: Signal<Vec<Rc<str>>>,
+    // Set once registered, lets us resume our session after reconnecting
+    credentials: Signal<Option<Credentials>>,
     messages: Signal<Vec<Message>>,
+    // Messages we haven't been able to send yet, kept across page reloads
+    outbox: Signal<Rc<Outbox>>,
+    session: Signal<Option<Rc<ChatSession>>>,
+    connection_state: Signal<ConnectionState>,
+    server_info: Signal<Option<chat_modrpc::ServerInfo>>,
+    // Why we aren't registered, shown on the registration form
+    registration_error: Signal<Option<String>>,
 }
 
 fn App() -> Element {
-    use_context_provider(|| AppContext {
-        compose_message: Signal::new("".to_string()),
-        users: Signal::new(HashMap::new()),
-        my_alias: Signal::new(None),
-        messages: Signal::new(vec![]),
+    use_context_provider(|| {
+        let outbox = Outbox::new(LocalStorage);
+        // Messages left over from last time are sent once we're registered.
+        let messages = outbox.messages().into_iter().map(own_message).collect();
+        AppContext {
+            compose_message: Signal::new("".to_string()),
+            users: Signal::new(UserDirectory::default()),
+            away: Signal::new(Vec::new()),
+            credentials: Signal::new(None),
+            messages: Signal::new(messages),
+            outbox: Signal::new(Rc::new(outbox)),
+            session: Signal::new(None),
+            connection_state: Signal::new(ConnectionState::Connecting),
+            server_info: Signal::new(None),
+            registration_error: Signal::new(None),
+        }
     });
 
-    let mut cx = consume_context::<AppContext>();
-    let chat_client = use_resource(move || async move {
-        let mut ex = modrpc_executor::DioxusExecutor::new();
-        let spawner = ex.spawner();
-        let (rt, _rt_shutdown) = modrpc::RuntimeHandle::single_threaded(&mut ex);
-        let buffer_pool = modrpc::HeapBufferPool::new(65536, 4, 4);
+    let cx = consume_context::<AppContext>();
+    use_future(move || run_connection(cx));
+
+    let connecting_label = match *cx.connection_state.read() {
+        ConnectionState::Reconnecting => "Reconnecting...",
+        _ => "Connecting...",
+    };
+
+    rsx! {
+        document::Stylesheet { href: asset!("/assets/main.css") }
+
+        if cx.credentials.read().is_some() {
+            Chat {}
+        } else if cx.server_info.read().is_some() {
+            // Stays up while reconnecting so the form isn't lost
+            Registration {}
+        } else {
+            div {
+                class: "connecting",
+                "{connecting_label}"
+            }
+        }
+    }
+}
+
+/// Keep a connection to the chat server open, reconnecting with exponential backoff whenever it
+/// is lost.
+async fn run_connection(mut cx: AppContext) {
+    let mut ex = modrpc_executor::DioxusExecutor::new();
+    let (rt, _rt_shutdown) = modrpc::RuntimeHandle::single_threaded(&mut ex);
+    let buffer_pool = modrpc::HeapBufferPool::new(65536, 4, 4);
+    let mut reconnect_delay = INITIAL_RECONNECT_DELAY;
+
+    let (chat_events_tx, chat_events_rx) = localq::mpsc::channel(16);
+    spawn(handle_chat_events(cx, chat_events_rx));
 
-        let connection =
+    loop {
+        let Ok(connection) =
             modrpc::web_ws_connect::<chat_modrpc::ChatClientRole>(
-                &rt, buffer_pool.clone(), "ws://127.0.0.1:9096",
+                &rt, buffer_pool.clone(), SERVER_URL,
                 chat_modrpc::ChatClientConfig {},
             )
             .await
-            .unwrap();
-        let modrpc::WebSocketConnection { init, role_handle: chat_client, .. } = connection;
+        else {
+            info!("Failed to connect, retrying in {reconnect_delay:?}");
+            cx.connection_state.set(ConnectionState::Reconnecting);
+            gloo_timers::future::sleep(reconnect_delay).await;
+            reconnect_delay = std::cmp::min(reconnect_delay * 2, MAX_RECONNECT_DELAY);
+            continue;
+        };
+        reconnect_delay = INITIAL_RECONNECT_DELAY;
+        let modrpc::WebSocketConnection {
+            endpoint: local_endpoint,
+            transport,
+            init,
+            role_handle: chat_client,
+        } = connection;
 
-        for user in init.users {
-            info!("Registered user: {}", user.alias);
-            cx.users.write().insert(user.endpoint, user.alias);
-        }
+        let session = Rc::new(ChatSession::new(
+            local_endpoint, init, chat_client, chat_events_tx.clone(),
+        ));
+        cx.server_info.set(Some(session.server_info().clone()));
 
-        let local_endpoint = connection.endpoint;
-        chat_client.register.subscribe(async move |source, request, response_waiter| {
-            let Ok(response) = response_waiter.wait().await else {
-                // Failed to decode response
-                return;
+        let credentials = cx.credentials.peek().clone();
+        if let Some(credentials) = credentials {
+            let reestablish = pin!(reestablish(&session, credentials, reconnect_delay));
+            let shutdown = pin!(transport.shutdown_signal.wait());
+            let futures::future::Either::Left((response, _)) =
+                futures::future::select(reestablish, shutdown).await
+            else {
+                info!("Lost connection to the chat server");
+                cx.connection_state.set(ConnectionState::Reconnecting);
+                continue;
             };
-            if response.is_ok() {
-                let Ok(alias) = request.alias() else {
-                    return;
-                };
-                cx.users.write().insert(source.endpoint, alias.into());
-
-                // XXX: Doing this directly in the task that makes the request causes this
-                // subscription future to be cancelled before it can handle the response, so
-                // handling the response to the local registration request here instead.
-                if source == local_endpoint {
-                    *cx.my_alias.write() = Some(alias.into());
-                }
+            info!("Re-established session: {}", response.is_ok());
+            // If our alias was claimed while we were away, this sends us back to the
+            // registration form.
+            cx.credentials.set(session.credentials());
+        }
+        cx.users.set(session.users().clone());
+
+        cx.session.set(Some(session.clone()));
+        cx.connection_state.set(ConnectionState::Online);
+        flush_outbox(cx);
+
+        let departures = pin!(session.track_departures());
+        let shutdown = pin!(transport.shutdown_signal.wait());
+        futures::future::select(departures, shutdown).await;
+        info!("Lost connection to the chat server");
+
+        cx.session.set(None);
+        cx.connection_state.set(ConnectionState::Reconnecting);
+    }
+}
+
+/// Pick up our session on a new connection, trying again for as long as the server holds on to it
+/// but won't resume it yet.
+async fn reestablish(
+    session: &ChatSession,
+    credentials: Credentials,
+    retry_delay: Duration,
+) -> Result<(), ChatError> {
+    loop {
+        match session.reestablish(credentials.clone()).await {
+            Err(error @ ChatError::ResumeSession(_)) => {
+                info!("Couldn't resume the session: {error}");
+                gloo_timers::future::sleep(error.retry_after().unwrap_or(retry_delay)).await;
             }
-        });
-        chat_client.send_message.subscribe(async move |source, request, response_waiter| {
-            let Ok(response) = response_waiter.wait().await else {
-                // Failed to decode response
-                return;
-            };
-            if response.is_ok() {
-                let users = cx.users.read();
-                let alias = users.get(&source.endpoint)
-                    .map(AsRef::as_ref)
-                    .unwrap_or("<unknown>");
-                let Ok(content) = request.content() else {
-                    return;
-                };
+            response => return response,
+        }
+    }
+}
+
+async fn handle_chat_events(
+    mut cx: AppContext,
+    mut chat_events: localq::mpsc::Receiver<ChatEvent>,
+) {
+    while let Ok(event) = chat_events.recv().await {
+        match event {
+            ChatEvent::Message(message) => {
+                // Our own messages are already listed, waiting to be marked delivered.
+                if let Some(id) = &message.outgoing_id
+                    && set_delivery_status(cx, id, DeliveryStatus::Delivered)
+                {
+                    continue;
+                }
                 cx.messages.push(Message {
-                    sender: alias.into(),
-                    content: content.into(),
+                    sender: message.alias,
+                    content: message.content,
+                    outgoing: None,
                 });
             }
-        });
+            ChatEvent::UserJoined(user) => {
+                info!("User joined: {}", user.alias);
+                cx.users.write().insert(user.endpoint, &user.alias);
+                cx.away.write().retain(|alias| **alias != *user.alias);
+            }
+            ChatEvent::UserLeft(user) => {
+                info!("User left: {}", user.alias);
+                cx.users.write().remove(user.endpoint);
+                mark_away(cx, &user.alias);
+            }
+            ChatEvent::UserRenamed { endpoint, old_alias, new_alias } => {
+                info!("User renamed: {old_alias} -> {new_alias}");
+                cx.users.write().insert(endpoint, &new_alias);
+                cx.away.write().retain(|alias| **alias != *new_alias);
+            }
+            ChatEvent::MissedMessagesTruncated => {
+                info!("Only the most recent missed messages were replayed");
+            }
+            ChatEvent::SendFailed { outgoing_id, error } => {
+                set_delivery_status(cx, &outgoing_id, DeliveryStatus::Failed(error.to_string()));
+            }
+            // Covers both the registration form and re-registering after reconnecting, if our
+            // alias was claimed while we were away.
+            ChatEvent::Error(error @ ChatError::Register(_)) => {
+                info!("Registration failed: {error}");
+                cx.registration_error.set(Some(registration_error_message(&error)));
+            }
+            ChatEvent::Error(error) => {
+                info!("Chat error: {error}");
+            }
+        }
+    }
+}
 
-        chat_client
+/// List a member who left as away for a while, since they may be back once they reconnect.
+fn mark_away(mut cx: AppContext, alias: &str) {
+    let entry: Rc<str> = Rc::from(alias);
+    cx.away.with_mut(|away| {
+        away.retain(|away_alias| **away_alias != *alias);
+        away.push(entry.clone());
+    });
+    spawn(async move {
+        gloo_timers::future::sleep(AWAY_PERIOD).await;
+        // Unless they came back, or left again and started a new away period, in the meantime
+        cx.away.write().retain(|away_alias| !Rc::ptr_eq(away_alias, &entry));
     });
-    use_context_provider(|| chat_client);
+}
 
-    rsx! {
-        document::Stylesheet { href: asset!("/assets/main.css") }
+/// What to tell the user when a registration fails.
+fn registration_error_message(error: &ChatError) -> String {
+    let hint = match error {
+        ChatError::Register(
+            chat_modrpc::RegisterError::UserAlreadyExists
+            | chat_modrpc::RegisterError::InvalidAlias,
+        ) => "Please choose a different alias.",
+        ChatError::Register(chat_modrpc::RegisterError::ChatFull) => "Please try again later.",
+        // The wait is part of the error itself.
+        ChatError::Register(chat_modrpc::RegisterError::RateLimited { .. }) => "",
+        ChatError::Disconnected => "Please try again once reconnected.",
+        _ => "Please try again.",
+    };
+    format!("Couldn't register: {error}. {hint}").trim_end().to_string()
+}
 
-        if chat_client.read().is_some() {
-            if cx.my_alias.read().is_some() {
-                Chat {}
-            } else {
-                Registration {}
-            }
-        } else {
-            div {
-                class: "connecting",
-                "Connecting..."
-            }
+/// Who to show as the sender of `message`. Our own actions are shown under our alias, since
+/// "* You waves" doesn't read well.
+fn message_sender(message: &Message, own_alias: Option<&str>) -> String {
+    let own_action = message.outgoing.is_some() && markdown::action(&message.content).is_some();
+    match own_alias {
+        Some(alias) if own_action => alias.to_string(),
+        _ => message.sender.clone(),
+    }
+}
+
+/// One of our messages, listed as pending until the server accepts it.
+fn own_message(message: OutgoingMessage) -> Message {
+    Message {
+        sender: "You".to_string(),
+        content: message.content,
+        outgoing: Some(Outgoing {
+            id: message.id,
+            status: DeliveryStatus::Pending,
+        }),
+    }
+}
+
+/// Send whatever is queued in the outbox, if we're connected. Delivery is reported through chat
+/// events.
+fn flush_outbox(cx: AppContext) {
+    spawn(async move {
+        loop {
+            let Some(session) = cx.session.peek().clone() else {
+                return;
+            };
+            let outbox = cx.outbox.peek().clone();
+            let Err(error) = session.flush(&outbox).await else {
+                return;
+            };
+            // Anything left queued goes out when we next flush, after registering or
+            // reconnecting.
+            let Some(retry_after) = error.retry_after() else {
+                return;
+            };
+            gloo_timers::future::sleep(retry_after).await;
         }
+    });
+}
+
+/// Queue one of our messages and send it.
+fn send(mut cx: AppContext, message: OutgoingMessage) {
+    cx.outbox.peek().push(message.clone());
+    cx.messages.push(own_message(message));
+    flush_outbox(cx);
+}
+
+/// Queue the message at `index` again if it's one of ours that failed to send.
+fn retry(cx: AppContext, index: usize) {
+    let message = cx.messages.peek().get(index).and_then(|message| {
+        let outgoing = message.outgoing.as_ref()?;
+        matches!(outgoing.status, DeliveryStatus::Failed(_)).then(|| OutgoingMessage {
+            id: outgoing.id.clone(),
+            content: message.content.clone(),
+        })
+    });
+    if let Some(message) = message {
+        set_delivery_status(cx, &message.id, DeliveryStatus::Pending);
+        cx.outbox.peek().push(message);
+        flush_outbox(cx);
+    }
+}
+
+/// Update the status of one of our messages. Returns false if there's no such message.
+fn set_delivery_status(mut cx: AppContext, id: &str, status: DeliveryStatus) -> bool {
+    let mut messages = cx.messages.write();
+    let outgoing = messages.iter_mut()
+        .filter_map(|message| message.outgoing.as_mut())
+        .find(|outgoing| outgoing.id == id);
+    match outgoing {
+        Some(outgoing) => {
+            outgoing.status = status;
+            true
+        }
+        None => false,
     }
 }
 
 #[component]
 fn Registration() -> Element {
     let mut cx = consume_context::<AppContext>();
-    let mut chat_client_cx = consume_context::<Resource<chat_modrpc::ChatClientHooks>>();
-    let mut register_alias = use_signal(|| String::new());
+    let mut register_alias = use_signal(String::new);
+    // Set while a register request is in flight
+    let mut registering = use_signal(|| false);
+
+    // A request in flight when the connection drops never gets a response.
+    use_effect(move || {
+        if cx.session.read().is_none() && *registering.peek() {
+            registering.set(false);
+            cx.registration_error.set(Some(registration_error_message(&ChatError::Disconnected)));
+        }
+    });
+
+    let connected = cx.session.read().is_some();
+    let invalid_alias = chat_common::validate_alias(&register_alias.read()).err();
+    let can_register = connected && !registering() && invalid_alias.is_none();
 
     let mut register = move || {
-        let alias = register_alias.read().clone();
-        if alias.is_empty() {
+        if !can_register {
             return;
         }
+        let Some(session) = cx.session.peek().clone() else { return; };
+        let alias = register_alias.peek().clone();
 
+        registering.set(true);
+        cx.registration_error.set(None);
         spawn(async move {
-            let Some(chat_client) = &*chat_client_cx.read() else { return; };
             info!("Sending registration request");
-            let response = chat_client.register.call(chat_modrpc::RegisterRequestGen {
-                alias: &alias,
-            })
-            .await;
+            // Failures are reported through chat events.
+            let response = session.register(&alias).await;
             info!("Got registration response: {:?}", response);
-            if let Err(_) = response {
-                // TODO display a helpful error
+
+            // Ignore the response if the connection was lost in the meantime.
+            let current_session = cx.session.peek().clone();
+            if !current_session.is_some_and(|current| Rc::ptr_eq(&current, &session)) {
+                return;
             }
+            registering.set(false);
+            if response.is_err() {
+                return;
+            }
+            cx.users.set(session.users().clone());
+            cx.credentials.set(session.credentials());
+            flush_outbox(cx);
         });
     };
 
+    // Only complain about the alias once something has been typed.
+    let error = match &invalid_alias {
+        Some(error) if !register_alias.read().is_empty() => {
+            Some(format!("Can't use that alias: {error}."))
+        }
+        _ => cx.registration_error.read().clone(),
+    };
+
+    let server_name = cx.server_info.read().as_ref()
+        .map_or("modrpc chat".to_string(), |server_info| server_info.name.clone());
+
     rsx! {
         div {
             class: "chat-container",
             div {
                 class: "chat-header",
-                "modrpc chat"
+                "{server_name}"
+            }
+            if let Some(error) = error {
+                div {
+                    class: "registration-error",
+                    "{error}"
+                }
+            }
+            if !connected {
+                div {
+                    class: "registration-status",
+                    "Reconnecting to the chat server..."
+                }
             }
             div {
                 class: "input-form",
                 input {
                     class: "input-field",
                     placeholder: "your desired alias",
+                    disabled: registering(),
                     value: "{register_alias}",
                     oninput: move |event| {
                         register_alias.set(event.value());
@@ -167,10 +468,16 @@ fn Registration() -> Element {
                 }
                 button {
                     class: "send-button",
+                    disabled: !can_register,
                     onclick: move |_| {
                         register();
                     },
-                    "Register"
+                    if registering() {
+                        span { class: "spinner" }
+                        "Registering..."
+                    } else {
+                        "Register"
+                    }
                 }
             }
         }
@@ -178,16 +485,45 @@ fn Registration() -> Element {
 }
 
 #[component]
-fn MessageBubble(sender: String, content: String) -> Element {
+fn MessageBubble(
+    sender: String,
+    content: String,
+    status: Option<DeliveryStatus>,
+    onretry: EventHandler,
+) -> Element {
+    let class = match &status {
+        Some(DeliveryStatus::Pending) => "message-bubble pending",
+        Some(DeliveryStatus::Failed(_)) => "message-bubble failed",
+        Some(DeliveryStatus::Delivered) | None => "message-bubble",
+    };
+    // Actions posted with /me read as "* alias does something".
+    let (class, head, body) = match markdown::action(&content) {
+        Some(action) => (format!("{class} action"), format!("* {sender} "), action),
+        None => (class.to_string(), format!("{sender}: "), content.as_str()),
+    };
+
     rsx! {
         div {
-            class: "message-bubble",
+            class,
             onmounted: move |cx| {
                 spawn(async move {
                     let _ = cx.data.scroll_to(ScrollBehavior::Smooth).await;
                 });
             },
-            b { "{sender}: " } "{content}"
+            b { "{head}" }
+            // Escaped by the renderer, so chat content can't inject markup
+            span { class: "message-content", dangerous_inner_html: markdown::render(body) }
+            if let Some(DeliveryStatus::Failed(reason)) = status {
+                div {
+                    class: "delivery-failed",
+                    "Not sent: {reason}"
+                    button {
+                        class: "retry-button",
+                        onclick: move |_| onretry.call(()),
+                        "Retry"
+                    }
+                }
+            }
         }
     }
 }
@@ -195,38 +531,119 @@ fn MessageBubble(sender: String, content: String) -> Element {
 #[component]
 fn Chat() -> Element {
     let mut cx = consume_context::<AppContext>();
-    let mut chat_client_cx = consume_context::<Resource<chat_modrpc::ChatClientHooks>>();
+    let mut show_members = use_signal(|| true);
+    // Focused again after a member is mentioned
+    let mut compose_input = use_signal(|| None::<Rc<MountedData>>);
+
+    let max_message_len = cx.server_info.read().as_ref()
+        .map(|server_info| server_info.max_message_len as usize);
+    let message_len = cx.compose_message.read().len();
+    let message_too_long = max_message_len.is_some_and(|max| message_len > max);
 
     let mut send_message = move || {
-        let message = cx.compose_message.read().clone();
-        if message.is_empty() {
+        let content = cx.compose_message.read().clone();
+        if content.is_empty() || message_too_long {
             return;
         }
 
-        spawn(async move {
-            let Some(chat_client) = &*chat_client_cx.read() else { return; };
-            let response = chat_client.send_message.call(chat_modrpc::SendMessageRequest {
-                content: message,
-            })
-            .await;
-        });
+        send(cx, OutgoingMessage::new(content));
 
         cx.compose_message.set("".to_string());
     };
 
+    let (connection_class, connection_label) = match *cx.connection_state.read() {
+        ConnectionState::Connecting => ("state-connecting", "connecting"),
+        ConnectionState::Online => ("state-online", "online"),
+        ConnectionState::Reconnecting => ("state-reconnecting", "reconnecting"),
+    };
+
+    let server_name = cx.server_info.read().as_ref()
+        .map_or("modrpc chat".to_string(), |server_info| server_info.name.clone());
+    let motd = cx.server_info.read().as_ref()
+        .map(|server_info| server_info.motd.clone())
+        .filter(|motd| !motd.is_empty());
+
+    let own_alias = cx.credentials.read().as_ref().map(|credentials| credentials.alias.clone());
+    let online = *cx.connection_state.read() == ConnectionState::Online;
+    let members = cx.users.read().users();
+    let member_count = members.len();
+    // Someone else may have taken the alias of a member who left.
+    let mut away: Vec<Rc<str>> = cx.away.read().iter()
+        .filter(|alias| !cx.users.read().contains_alias(alias))
+        .cloned()
+        .collect();
+    away.sort();
+
+    let mention = move |alias: String| {
+        let mut compose_message = cx.compose_message.write();
+        if !compose_message.is_empty() && !compose_message.ends_with(' ') {
+            compose_message.push(' ');
+        }
+        compose_message.push_str(&format!("@{alias} "));
+        if let Some(input) = compose_input() {
+            spawn(async move {
+                let _ = input.set_focus(true).await;
+            });
+        }
+    };
+
     rsx! {
         div {
             class: "chat-container",
             div {
                 class: "chat-header",
-                "modrpc chat"
+                "{server_name}"
+                span {
+                    class: "connection-state {connection_class}",
+                    "{connection_label}"
+                }
+                button {
+                    class: "members-toggle",
+                    title: if show_members() { "Hide members" } else { "Show members" },
+                    onclick: move |_| show_members.toggle(),
+                    "Members ({member_count})"
+                }
+            }
+            if let Some(motd) = motd {
+                div {
+                    class: "motd",
+                    "{motd}"
+                }
             }
             div {
-                class: "message-area",
-                for message in (cx.messages)() {
-                    MessageBubble {
-                        sender: message.sender.clone(),
-                        content: message.content.clone(),
+                class: "chat-body",
+                div {
+                    class: "message-area",
+                    for (index, message) in (cx.messages)().into_iter().enumerate() {
+                        MessageBubble {
+                            sender: message_sender(&message, own_alias.as_deref()),
+                            content: message.content.clone(),
+                            status: message.outgoing.map(|outgoing| outgoing.status),
+                            onretry: move |_| retry(cx, index),
+                        }
+                    }
+                }
+                if show_members() {
+                    div {
+                        class: "member-panel",
+                        for member in members {
+                            MemberEntry {
+                                key: "{member.endpoint}",
+                                is_own: own_alias.as_deref() == Some(member.alias.as_str()),
+                                presence: if online { Presence::Online } else { Presence::Unknown },
+                                onmention: mention,
+                                alias: member.alias,
+                            }
+                        }
+                        for alias in away {
+                            MemberEntry {
+                                key: "away-{alias}",
+                                is_own: false,
+                                presence: if online { Presence::Away } else { Presence::Unknown },
+                                onmention: mention,
+                                alias: alias.to_string(),
+                            }
+                        }
                     }
                 }
             }
@@ -236,6 +653,7 @@ fn Chat() -> Element {
                     class: "input-field",
                     placeholder: "Type a message...",
                     value: "{cx.compose_message}",
+                    onmounted: move |event| compose_input.set(Some(event.data())),
                     oninput: move |event| {
                         cx.compose_message.set(event.value());
                     },
@@ -245,8 +663,15 @@ fn Chat() -> Element {
                         }
                     }
                 }
+                if let Some(max_message_len) = max_message_len {
+                    span {
+                        class: if message_too_long { "message-counter too-long" } else { "message-counter" },
+                        "{message_len}/{max_message_len}"
+                    }
+                }
                 button {
                     class: "send-button",
+                    disabled: message_too_long,
                     onclick: move |_| {
                         send_message();
                     },
@@ -256,3 +681,45 @@ fn Chat() -> Element {
         }
     }
 }
+
+/// One user in the member panel. Clicking someone else mentions them in the composer.
+#[component]
+fn MemberEntry(
+    alias: String,
+    is_own: bool,
+    presence: Presence,
+    onmention: EventHandler<String>,
+) -> Element {
+    let class = if is_own { "member own" } else { "member" };
+    let (presence_class, presence_title) = match presence {
+        Presence::Online => ("presence online", "Online"),
+        Presence::Away => ("presence away", "Left recently, may be back soon"),
+        Presence::Unknown => ("presence unknown", "Unknown while disconnected"),
+    };
+
+    rsx! {
+        div {
+            class,
+            title: if is_own { "You" } else { "Mention @{alias}" },
+            onclick: {
+                let alias = alias.clone();
+                move |_| {
+                    if !is_own {
+                        onmention.call(alias.clone());
+                    }
+                }
+            },
+            span {
+                class: presence_class,
+                title: presence_title,
+            }
+            "{alias}"
+            if is_own {
+                span {
+                    class: "member-you",
+                    " (you)"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,405 @@
+//! The subset of Markdown that chat messages are rendered with: code spans, fenced code blocks
+//! with syntax highlighting, bold, italic and autolinked URLs.
+//!
+//! Actions posted with `/me` are recognized by [`action`], so the prefix is left out and the rest
+//! rendered after the sender.
+//!
+//! Everything else is escaped, so the HTML is safe to set as an element's inner HTML whatever a
+//! message contains. Kept apart from the UI so it can be tested natively.
+
+/// Render a chat message as HTML.
+pub fn render(content: &str) -> String {
+    let mut html = String::new();
+    // Lines of text since the last code block
+    let mut text: Vec<&str> = Vec::new();
+    let mut lines = content.split('\n');
+    while let Some(line) = lines.next() {
+        let Some(info) = line.trim_start().strip_prefix("```") else {
+            text.push(line);
+            continue;
+        };
+        render_inline(&mut html, &text.join("\n"));
+        text.clear();
+
+        // An unclosed block runs to the end of the message.
+        let code: Vec<&str> =
+            lines.by_ref().take_while(|line| !line.trim_start().starts_with("```")).collect();
+        let language = info.split_whitespace().next().unwrap_or_default();
+        render_code_block(&mut html, language, &code.join("\n"));
+    }
+    render_inline(&mut html, &text.join("\n"));
+    html
+}
+
+/// What a message says its sender is doing, if it's an action posted with `/me`.
+pub fn action(content: &str) -> Option<&str> {
+    content.strip_prefix(chat_common::ACTION_PREFIX)
+}
+
+fn render_code_block(html: &mut String, language: &str, code: &str) {
+    html.push_str("<pre class=\"code-block\"><code>");
+    match Syntax::for_language(language) {
+        Some(syntax) => highlight(html, code, &syntax),
+        None => escape_into(html, code),
+    }
+    html.push_str("</code></pre>");
+}
+
+fn render_inline(html: &mut String, text: &str) {
+    let mut rest = text;
+    // Character before `rest`, for telling where words start and end
+    let mut prev: Option<char> = None;
+    while let Some(c) = rest.chars().next() {
+        let consumed = match c {
+            '`' => code_span(html, rest),
+            'h' if !prev.is_some_and(char::is_alphanumeric) => autolink(html, rest),
+            '*' | '_' => emphasis(html, rest, prev),
+            _ => None,
+        };
+        let consumed = consumed.unwrap_or_else(|| {
+            match c {
+                '\n' => html.push_str("<br>"),
+                c => escape_char_into(html, c),
+            }
+            c.len_utf8()
+        });
+        prev = rest[..consumed].chars().next_back();
+        rest = &rest[consumed..];
+    }
+}
+
+/// A code span starting at a run of backticks and ending at a run of the same length. Returns the
+/// bytes used, or None if the run is never closed.
+fn code_span(html: &mut String, text: &str) -> Option<usize> {
+    let ticks = text.len() - text.trim_start_matches('`').len();
+    let end = text[ticks..].find(&text[..ticks])?;
+    html.push_str("<code>");
+    escape_into(html, &text[ticks..ticks + end]);
+    html.push_str("</code>");
+    Some(ticks + end + ticks)
+}
+
+/// Link an http(s) URL at the start of `text`. Returns the bytes used, or None if there's no URL.
+fn autolink(html: &mut String, text: &str) -> Option<usize> {
+    let scheme_len = ["https://", "http://"]
+        .into_iter()
+        .find(|scheme| text.starts_with(scheme))?
+        .len();
+    let end = text.find(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"' | '`'));
+    let mut url = &text[..end.unwrap_or(text.len())];
+    // Punctuation after a URL usually ends the sentence rather than the URL, and a closing
+    // parenthesis with no opening one in the URL is probably around it.
+    loop {
+        let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?', '\'', '*', '_']);
+        let trimmed = match trimmed.strip_suffix(')') {
+            Some(inner) if trimmed.matches('(').count() < trimmed.matches(')').count() => inner,
+            _ => trimmed,
+        };
+        if trimmed == url {
+            break;
+        }
+        url = trimmed;
+    }
+    if url.len() == scheme_len {
+        return None;
+    }
+    html.push_str("<a href=\"");
+    escape_into(html, url);
+    html.push_str("\" target=\"_blank\" rel=\"noopener noreferrer\">");
+    escape_into(html, url);
+    html.push_str("</a>");
+    Some(url.len())
+}
+
+/// Bold (`**text**`, `__text__`) or italic (`*text*`, `_text_`) starting at `text`. Returns the
+/// bytes used, or None if the delimiter doesn't open a closed span.
+fn emphasis(html: &mut String, text: &str, prev: Option<char>) -> Option<usize> {
+    let marker = text.chars().next()?;
+    let len = if text[1..].starts_with(marker) { 2 } else { 1 };
+    let delimiter = &text[..len];
+    // Underscores inside words, as in snake_case, are left alone.
+    if marker == '_' && prev.is_some_and(char::is_alphanumeric) {
+        return None;
+    }
+    let inner = &text[len..];
+    if inner.chars().next().is_none_or(char::is_whitespace) {
+        return None;
+    }
+
+    let end = (1..inner.len()).find(|&i| {
+        if !(inner.is_char_boundary(i) && inner[i..].starts_with(delimiter)) {
+            return false;
+        }
+        let before = inner[..i].chars().next_back();
+        let after = inner[i + len..].chars().next();
+        !before.is_some_and(char::is_whitespace)
+            // A pair closes at the end of a run, as in ***, and a lone marker mustn't be half of
+            // a pair.
+            && after != Some(marker)
+            && (len == 2 || before != Some(marker))
+            && !(marker == '_' && after.is_some_and(char::is_alphanumeric))
+    })?;
+    let tag = if len == 2 { "strong" } else { "em" };
+    html.push_str(&format!("<{tag}>"));
+    render_inline(html, &inner[..end]);
+    html.push_str(&format!("</{tag}>"));
+    Some(len + end + len)
+}
+
+/// What the highlighter knows about a language.
+struct Syntax {
+    keywords: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    // Whether /* */ comments exist
+    block_comments: bool,
+    quotes: &'static [char],
+}
+
+impl Syntax {
+    fn for_language(language: &str) -> Option<Syntax> {
+        let syntax = match language.to_ascii_lowercase().as_str() {
+            "rust" | "rs" => Syntax {
+                keywords: &[
+                    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else",
+                    "enum", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match",
+                    "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static",
+                    "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+                ],
+                line_comments: &["//"],
+                block_comments: true,
+                // Single quotes are as often lifetimes as chars.
+                quotes: &['"'],
+            },
+            "js" | "javascript" | "ts" | "typescript" => Syntax {
+                keywords: &[
+                    "async", "await", "break", "case", "catch", "class", "const", "continue",
+                    "default", "delete", "else", "export", "extends", "false", "finally", "for",
+                    "function", "if", "import", "in", "instanceof", "interface", "let", "new",
+                    "null", "return", "switch", "this", "throw", "true", "try", "type",
+                    "typeof", "undefined", "var", "while", "yield",
+                ],
+                line_comments: &["//"],
+                block_comments: true,
+                quotes: &['"', '\'', '`'],
+            },
+            "python" | "py" => Syntax {
+                keywords: &[
+                    "and", "as", "assert", "async", "await", "break", "class", "continue", "def",
+                    "del", "elif", "else", "except", "False", "finally", "for", "from", "if",
+                    "import", "in", "is", "lambda", "None", "not", "or", "pass", "raise",
+                    "return", "True", "try", "while", "with", "yield",
+                ],
+                line_comments: &["#"],
+                block_comments: false,
+                quotes: &['"', '\''],
+            },
+            "sh" | "bash" | "shell" | "zsh" => Syntax {
+                keywords: &[
+                    "case", "do", "done", "elif", "else", "esac", "export", "fi", "for",
+                    "function", "if", "in", "local", "return", "then", "until", "while",
+                ],
+                line_comments: &["#"],
+                block_comments: false,
+                quotes: &['"', '\''],
+            },
+            "c" | "h" | "cpp" | "c++" | "go" | "java" => Syntax {
+                keywords: &[
+                    "break", "case", "char", "class", "const", "continue", "default", "defer",
+                    "do", "double", "else", "enum", "false", "float", "for", "func", "go", "if",
+                    "import", "int", "interface", "long", "new", "nil", "null", "package",
+                    "private", "public", "return", "static", "struct", "switch", "true", "type",
+                    "unsigned", "var", "void", "while",
+                ],
+                line_comments: &["//"],
+                block_comments: true,
+                quotes: &['"', '\''],
+            },
+            "json" | "toml" => Syntax {
+                keywords: &["true", "false", "null"],
+                line_comments: &["#"],
+                block_comments: false,
+                quotes: &['"', '\''],
+            },
+            _ => return None,
+        };
+        Some(syntax)
+    }
+}
+
+/// Escape `code`, wrapping keywords, strings, numbers and comments in spans classed for styling.
+fn highlight(html: &mut String, code: &str, syntax: &Syntax) {
+    let mut rest = code;
+    let mut prev: Option<char> = None;
+    while let Some(c) = rest.chars().next() {
+        let is_word_start = !prev.is_some_and(|prev| prev.is_alphanumeric() || prev == '_');
+        let (len, class) = if syntax.line_comments.iter().any(|marker| rest.starts_with(marker)) {
+            (rest.find('\n').unwrap_or(rest.len()), Some("comment"))
+        } else if syntax.block_comments && rest.starts_with("/*") {
+            (rest[2..].find("*/").map_or(rest.len(), |end| end + 4), Some("comment"))
+        } else if syntax.quotes.contains(&c) {
+            (string_len(rest, c), Some("string"))
+        } else if c.is_ascii_digit() && is_word_start {
+            let end = rest.find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '.')));
+            (end.unwrap_or(rest.len()), Some("number"))
+        } else if (c.is_alphabetic() || c == '_') && is_word_start {
+            let len = rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len());
+            (len, syntax.keywords.contains(&&rest[..len]).then_some("keyword"))
+        } else {
+            (c.len_utf8(), None)
+        };
+
+        let token = &rest[..len];
+        match class {
+            Some(class) => {
+                html.push_str(&format!("<span class=\"tok-{class}\">"));
+                escape_into(html, token);
+                html.push_str("</span>");
+            }
+            None => escape_into(html, token),
+        }
+        prev = token.chars().next_back();
+        rest = &rest[len..];
+    }
+}
+
+/// Length of the string literal opened by `quote` at the start of `code`, up to its closing quote
+/// or, if it isn't closed, the end of the line.
+fn string_len(code: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (i, c) in code.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '\n' => return i,
+            c if c == quote => return i + c.len_utf8(),
+            _ => {}
+        }
+    }
+    code.len()
+}
+
+fn escape_into(html: &mut String, text: &str) {
+    for c in text.chars() {
+        escape_char_into(html, c);
+    }
+}
+
+fn escape_char_into(html: &mut String, c: char) {
+    match c {
+        '&' => html.push_str("&amp;"),
+        '<' => html.push_str("&lt;"),
+        '>' => html.push_str("&gt;"),
+        '"' => html.push_str("&quot;"),
+        '\'' => html.push_str("&#39;"),
+        c => html.push(c),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{action, render};
+
+    #[test]
+    fn escapes_markup() {
+        assert_eq!(
+            render("<script>alert('hi') & \"bye\"</script>"),
+            "&lt;script&gt;alert(&#39;hi&#39;) &amp; &quot;bye&quot;&lt;/script&gt;",
+        );
+        assert_eq!(render("<img src=x onerror=alert(1)>"), "&lt;img src=x onerror=alert(1)&gt;");
+    }
+
+    #[test]
+    fn recognizes_actions() {
+        assert_eq!(action("/me waves"), Some("waves"));
+        assert_eq!(action("/me  *waves* <b>"), Some(" *waves* <b>"));
+        assert_eq!(action("/meow"), None);
+        assert_eq!(action("/me"), None);
+        assert_eq!(action(" /me waves"), None);
+        assert_eq!(action("she said /me waves"), None);
+        // The action is rendered like any other message.
+        assert_eq!(render(action("/me *waves* <b>").unwrap()), "<em>waves</em> &lt;b&gt;");
+    }
+
+    #[test]
+    fn bold_and_italic() {
+        assert_eq!(render("**bold** and *italic*"), "<strong>bold</strong> and <em>italic</em>");
+        assert_eq!(render("__bold__ _italic_"), "<strong>bold</strong> <em>italic</em>");
+        assert_eq!(render("**bold *and italic***"), "<strong>bold <em>and italic</em></strong>");
+        assert_eq!(render("***both***"), "<strong><em>both</em></strong>");
+        assert_eq!(render("**<b>**"), "<strong>&lt;b&gt;</strong>");
+    }
+
+    #[test]
+    fn stray_delimiters_are_literal() {
+        assert_eq!(render("2 * 3 * 4"), "2 * 3 * 4");
+        assert_eq!(render("**unclosed"), "**unclosed");
+        assert_eq!(render("snake_case_name"), "snake_case_name");
+        assert_eq!(render("*"), "*");
+    }
+
+    #[test]
+    fn code_spans_are_escaped_and_not_formatted() {
+        assert_eq!(render("run `a **b** <c>`"), "run <code>a **b** &lt;c&gt;</code>");
+        assert_eq!(render("``a ` b``"), "<code>a ` b</code>");
+        assert_eq!(render("`unclosed"), "`unclosed");
+    }
+
+    #[test]
+    fn autolinks_urls() {
+        assert_eq!(
+            render("see https://example.com/a_b?c=1&d=2."),
+            "see <a href=\"https://example.com/a_b?c=1&amp;d=2\" target=\"_blank\" \
+            rel=\"noopener noreferrer\">https://example.com/a_b?c=1&amp;d=2</a>.",
+        );
+        assert_eq!(
+            render("(http://example.com/x_(y))"),
+            "(<a href=\"http://example.com/x_(y)\" target=\"_blank\" \
+            rel=\"noopener noreferrer\">http://example.com/x_(y)</a>)",
+        );
+        assert_eq!(
+            render("http://a.b\"onmouseover=\"x"),
+            "<a href=\"http://a.b\" target=\"_blank\" rel=\"noopener noreferrer\">http://a.b</a>\
+            &quot;onmouseover=&quot;x",
+        );
+    }
+
+    #[test]
+    fn only_http_urls_are_linked() {
+        assert_eq!(render("javascript:alert(1)"), "javascript:alert(1)");
+        assert_eq!(render("https:// nothing"), "https:// nothing");
+        assert_eq!(render("xhttp://example.com"), "xhttp://example.com");
+    }
+
+    #[test]
+    fn line_breaks() {
+        assert_eq!(render("one\ntwo"), "one<br>two");
+    }
+
+    #[test]
+    fn fenced_code_blocks_are_highlighted() {
+        assert_eq!(
+            render("look:\n```rust\nlet s = \"<x>\"; // 1\n```\ndone"),
+            "look:<pre class=\"code-block\"><code><span class=\"tok-keyword\">let</span> s = \
+            <span class=\"tok-string\">&quot;&lt;x&gt;&quot;</span>; \
+            <span class=\"tok-comment\">// 1</span></code></pre>done",
+        );
+        assert_eq!(
+            render("```py\nx = 10 if y else None\n```"),
+            "<pre class=\"code-block\"><code>x = <span class=\"tok-number\">10</span> \
+            <span class=\"tok-keyword\">if</span> y <span class=\"tok-keyword\">else</span> \
+            <span class=\"tok-keyword\">None</span></code></pre>",
+        );
+    }
+
+    #[test]
+    fn unknown_languages_and_unclosed_blocks_are_escaped() {
+        assert_eq!(
+            render("```\n**not bold** <b>\n"),
+            "<pre class=\"code-block\"><code>**not bold** &lt;b&gt;\n</code></pre>",
+        );
+        assert_eq!(
+            render("```brainfuck\nletter</code>"),
+            "<pre class=\"code-block\"><code>letter&lt;/code&gt;</code></pre>",
+        );
+    }
+}
@@ -0,0 +1,29 @@
+use chat_client::{OutboxStorage, OutgoingMessage};
+
+const OUTBOX_KEY: &str = "modrpc-chat.outbox";
+
+/// Keeps the outbox in the browser's `localStorage` so queued messages survive page reloads.
+pub struct LocalStorage;
+
+impl LocalStorage {
+    fn storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok().flatten()
+    }
+}
+
+impl OutboxStorage for LocalStorage {
+    fn load(&self) -> Vec<OutgoingMessage> {
+        Self::storage()
+            .and_then(|storage| storage.get_item(OUTBOX_KEY).ok().flatten())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, messages: &[OutgoingMessage]) {
+        let (Some(storage), Ok(contents)) = (Self::storage(), serde_json::to_string(messages)) else {
+            return;
+        };
+        // Storage can be full or disabled - the outbox still works for this page load.
+        let _ = storage.set_item(OUTBOX_KEY, &contents);
+    }
+}